@@ -6,86 +6,57 @@ use criterion::Criterion;
 use indexmap::IndexMap;
 use mutable::MutMap;
 use mutable::MutVec;
+use mutable::{MutMapLike, MutSequence};
 use rand::{thread_rng, Rng};
 use std::hash::Hash;
 
-trait VecTrait<T>: Default {
-    fn push_value(&mut self, value: T);
-}
-
-impl<T> VecTrait<T> for Vec<T> {
-    fn push_value(&mut self, value: T) {
-        self.push(value);
-    }
-}
-
-impl<T> VecTrait<T> for MutVec<T> {
-    fn push_value(&mut self, value: T) {
-        self.push(value);
-    }
-}
-
 fn push_data<V>(n: u64)
 where
-    V: VecTrait<u64>,
+    V: MutSequence<u64> + Default,
 {
     let mut vec = V::default();
     for i in 0..n {
-        vec.push_value(i);
+        vec.push(i);
     }
 }
 
-trait MapTrait<K, V, Variant>: Default {
-    fn insert_value(&mut self, key: K, value: V);
-    fn remove_value(&mut self, key: &K);
-}
-
-struct Variant0;
-struct Variant1;
+/// A `MutMap` that uses the `_not_panic_safe` operations.
+struct NotPanicSafe<K, V>(MutMap<K, V>);
 
-impl<K, V> MapTrait<K, V, Variant0> for IndexMap<K, V>
+impl<K, V> Default for NotPanicSafe<K, V>
 where
     K: Eq + Hash,
 {
-    fn insert_value(&mut self, key: K, value: V) {
-        self.insert(key, value);
-    }
-
-    fn remove_value(&mut self, key: &K) {
-        self.remove(key);
+    fn default() -> Self {
+        NotPanicSafe(MutMap::default())
     }
 }
 
-impl<K, V> MapTrait<K, V, Variant0> for MutMap<K, V>
+impl<K, V> MutMapLike<K, V> for NotPanicSafe<K, V>
 where
     K: Eq + Hash,
 {
-    fn insert_value(&mut self, key: K, value: V) {
-        self.insert(key, value);
+    fn len(&self) -> usize {
+        self.0.len()
     }
 
-    fn remove_value(&mut self, key: &K) {
-        self.remove(key);
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.0.insert_not_panic_safe(key, value)
     }
-}
 
-impl<K, V> MapTrait<K, V, Variant1> for MutMap<K, V>
-where
-    K: Eq + Hash,
-{
-    fn insert_value(&mut self, key: K, value: V) {
-        self.insert_not_panic_safe(key, value);
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.0.remove_not_panic_safe(key)
     }
 
-    fn remove_value(&mut self, key: &K) {
-        self.remove_not_panic_safe(key);
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.0.get(key)
     }
 }
 
-fn map_insert_rand_bench<Variant, M: MapTrait<u64, u64, Variant>>(
-    n: u64,
-    b: &mut criterion::Bencher,
-) {
+fn map_insert_rand_bench<M: MutMapLike<u64, u64> + Default>(n: u64, b: &mut criterion::Bencher) {
     let mut map = M::default();
 
     // setup
@@ -93,14 +64,14 @@ fn map_insert_rand_bench<Variant, M: MapTrait<u64, u64, Variant>>(
 
     for _ in 0..n {
         let i = rng.gen::<u64>() % n;
-        map.insert_value(i, i);
+        map.insert(i, i);
     }
 
     // measure
     b.iter(|| {
         let k = rng.gen::<u64>() % n;
-        map.insert_value(k, k);
-        map.remove_value(&k);
+        map.insert(k, k);
+        map.remove(&k);
     });
 
     black_box(map);
@@ -113,14 +84,22 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("push_1024_mutvec", |b| {
         b.iter(|| push_data::<MutVec<u64>>(black_box(1024)))
     });
+    c.bench_function("at_1024_vec", |b| {
+        let vec: Vec<u64> = (0..1024).collect();
+        b.iter(|| (0..1024).map(|i| vec[black_box(i)]).sum::<u64>())
+    });
+    c.bench_function("at_1024_mutvec", |b| {
+        let vec: MutVec<u64> = (0..1024).collect();
+        b.iter(|| (0..1024).map(|i| vec.at(black_box(i))).sum::<u64>())
+    });
     c.bench_function("map_insert_1024_indexmap", |b| {
-        map_insert_rand_bench::<Variant0, IndexMap<u64, u64>>(black_box(1024), b)
+        map_insert_rand_bench::<IndexMap<u64, u64>>(black_box(1024), b)
     });
     c.bench_function("map_insert_1024_mutmap0", |b| {
-        map_insert_rand_bench::<Variant0, MutMap<u64, u64>>(black_box(1024), b)
+        map_insert_rand_bench::<MutMap<u64, u64>>(black_box(1024), b)
     });
     c.bench_function("map_insert_1024_mutmap1", |b| {
-        map_insert_rand_bench::<Variant1, MutMap<u64, u64>>(black_box(1024), b)
+        map_insert_rand_bench::<NotPanicSafe<u64, u64>>(black_box(1024), b)
     });
 }
 
@@ -0,0 +1,239 @@
+//! Derive macros for the `mutable` crate. Use these through the
+//! `derive` feature of `mutable` rather than depending on this crate
+//! directly.
+
+extern crate proc_macro;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Field, Fields, GenericArgument, PathArguments, Type,
+};
+
+/// For a struct `Foo` with named fields, generates a parallel struct
+/// `FooMut` with the same fields wrapped in the crate's cells, along
+/// with `Foo::thaw(self) -> FooMut` and `FooMut::freeze(self) -> Foo`.
+///
+/// Fields of type `Vec<T>` become `MutVec<T>`, fields of type
+/// `IndexMap<K, V>` become `MutMap<K, V>`, and all other fields
+/// become `Mut<T>`. A field marked `#[mutable(skip)]` is copied over
+/// as-is (the equivalent of a `final` field).
+#[proc_macro_derive(Mutable, attributes(mutable))]
+pub fn derive_mutable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Implements `mutable::EnumKey` for a fieldless enum, numbering its
+/// variants in declaration order.
+#[proc_macro_derive(EnumKey)]
+pub fn derive_enum_key(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_enum_key(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_enum_key(input: DeriveInput) -> syn::Result<TokenStream> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`EnumKey` can only be derived for enums",
+            ))
+        }
+    };
+    if let Some(variant) = variants.iter().find(|v| !matches!(v.fields, Fields::Unit)) {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "`EnumKey` can only be derived for enums without fields",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let count = variants.len();
+    let names: Vec<_> = variants.iter().map(|v| &v.ident).collect();
+    let indices: Vec<_> = (0..count).collect();
+    Ok(quote! {
+        impl #impl_generics ::mutable::EnumKey for #name #ty_generics #where_clause {
+            const COUNT: usize = #count;
+
+            fn index(&self) -> usize {
+                match self {
+                    #(#name::#names => #indices,)*
+                }
+            }
+
+            fn from_index(index: usize) -> Self {
+                match index {
+                    #(#indices => #name::#names,)*
+                    _ => panic!("variant index {} out of range", index),
+                }
+            }
+        }
+    })
+}
+
+/// How a field is represented in the mutable view.
+enum Kind {
+    Cell,
+    Vec,
+    Map,
+    Skip,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`Mutable` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`Mutable` can only be derived for structs",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let mut_name = format_ident!("{}Mut", name);
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let doc = format!(
+        "Mutable view of [`{}`], generated by `#[derive(Mutable)]`.",
+        name
+    );
+
+    let mut mut_fields = vec![];
+    let mut thaw_fields = vec![];
+    let mut freeze_fields = vec![];
+    for field in fields {
+        let field_vis = &field.vis;
+        let field_name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let (mut_ty, thaw, freeze) = match classify(field)? {
+            Kind::Cell => (
+                quote!(::mutable::Mut<#ty>),
+                quote!(::mutable::Mut::new(self.#field_name)),
+                quote!(self.#field_name.into_inner()),
+            ),
+            Kind::Vec => {
+                let args = generic_args(ty);
+                (
+                    quote!(::mutable::MutVec<#(#args),*>),
+                    quote!(::mutable::MutVec::from(self.#field_name)),
+                    quote!(self.#field_name.into_inner()),
+                )
+            }
+            Kind::Map => {
+                let args = generic_args(ty);
+                (
+                    quote!(::mutable::MutMap<#(#args),*>),
+                    quote!(::mutable::MutMap::from(self.#field_name)),
+                    quote!(self.#field_name.into_inner()),
+                )
+            }
+            Kind::Skip => (
+                quote!(#ty),
+                quote!(self.#field_name),
+                quote!(self.#field_name),
+            ),
+        };
+        mut_fields.push(quote!(#field_vis #field_name: #mut_ty));
+        thaw_fields.push(quote!(#field_name: #thaw));
+        freeze_fields.push(quote!(#field_name: #freeze));
+    }
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #mut_name #impl_generics #where_clause {
+            #(#mut_fields,)*
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Convert into the mutable view of this struct.
+            #vis fn thaw(self) -> #mut_name #ty_generics {
+                #mut_name {
+                    #(#thaw_fields,)*
+                }
+            }
+        }
+
+        impl #impl_generics #mut_name #ty_generics #where_clause {
+            /// Convert back into the plain struct, taking ownership
+            /// of the current contents of each cell.
+            #vis fn freeze(self) -> #name #ty_generics {
+                #name {
+                    #(#freeze_fields,)*
+                }
+            }
+        }
+    })
+}
+
+fn classify(field: &Field) -> syn::Result<Kind> {
+    let mut skip = false;
+    for attr in &field.attrs {
+        if attr.path().is_ident("mutable") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `mutable` attribute"))
+                }
+            })?;
+        }
+    }
+    if skip {
+        return Ok(Kind::Skip);
+    }
+
+    let segment = match &field.ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last().unwrap(),
+        _ => return Ok(Kind::Cell),
+    };
+    let num_args = generic_args(&field.ty).len();
+    Ok(if segment.ident == "Vec" && num_args == 1 {
+        Kind::Vec
+    } else if segment.ident == "IndexMap" && num_args == 2 {
+        Kind::Map
+    } else {
+        Kind::Cell
+    })
+}
+
+/// The generic type arguments of the last segment of a path type,
+/// e.g. `K` and `V` for `indexmap::IndexMap<K, V>`.
+fn generic_args(ty: &Type) -> Vec<&Type> {
+    let segment = match ty {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => segment,
+            None => return vec![],
+        },
+        _ => return vec![],
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
@@ -0,0 +1,65 @@
+use indexmap::IndexMap;
+use mutable::{EnumKey, MutEnumMap, Mutable};
+use std::rc::Rc;
+
+#[derive(Mutable, Debug, PartialEq)]
+pub struct Model {
+    pub name: String,
+    pub count: u32,
+    pub items: Vec<u32>,
+    pub index: IndexMap<String, usize>,
+    #[mutable(skip)]
+    pub id: u64,
+}
+
+#[derive(Mutable)]
+struct Wrapper<T> {
+    value: Rc<T>,
+}
+
+#[test]
+fn thaw_mutate_freeze() {
+    let model = Model {
+        name: "m".to_string(),
+        count: 1,
+        items: vec![1, 2],
+        index: IndexMap::new(),
+        id: 22,
+    };
+
+    let m: ModelMut = model.thaw();
+    m.count.set(m.count.get() + 1);
+    m.items.push(3);
+    m.index.insert("three".to_string(), 2);
+    assert_eq!(m.id, 22);
+
+    let model = m.freeze();
+    assert_eq!(model.count, 2);
+    assert_eq!(model.items, vec![1, 2, 3]);
+    assert_eq!(model.index.get("three"), Some(&2));
+}
+
+#[test]
+fn generic() {
+    let w = Wrapper { value: Rc::new(1) }.thaw();
+    w.value.set(Rc::new(2));
+    assert_eq!(*w.freeze().value, 2);
+}
+
+#[derive(EnumKey, Debug, PartialEq)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[test]
+fn enum_key() {
+    assert_eq!(Color::COUNT, 3);
+    assert_eq!(Color::Blue.index(), 2);
+    assert_eq!(Color::from_index(1), Color::Green);
+
+    let map: MutEnumMap<Color, u32> = MutEnumMap::new();
+    map.set(&Color::Red, 1);
+    assert_eq!(map.iter().map(|(_, v)| v).sum::<u32>(), 1);
+}
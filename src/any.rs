@@ -0,0 +1,84 @@
+//! A cell whose contents may be of any type.
+
+use crate::mutbl::Mut;
+use crate::observe::SubscriptionId;
+use std::any::{Any, TypeId};
+use std::fmt;
+
+mod test;
+
+/// A `Mut<Box<dyn Any>>`: a shared slot whose type is only known at
+/// runtime, e.g. for the per-plugin state of a plugin system. The
+/// accessors name the type they expect and find nothing if the slot
+/// holds some other type.
+pub struct MutAny {
+    data: Mut<Box<dyn Any>>,
+}
+
+impl MutAny {
+    #[track_caller]
+    pub fn new<T: 'static>(value: T) -> Self {
+        MutAny {
+            data: Mut::new(Box::new(value)),
+        }
+    }
+
+    /// Replace the value, which may be of a different type than
+    /// before.
+    pub fn set<T: 'static>(&self, value: T) {
+        self.data.set(Box::new(value))
+    }
+
+    /// A clone of the value, if it is a `T`.
+    pub fn get<T: Clone + 'static>(&self) -> Option<T> {
+        self.data.data.borrow().downcast_ref::<T>().cloned()
+    }
+
+    /// Runs `f` with a reference to the value, if it is a `T`. All
+    /// mut-cells are read-only while `f` runs.
+    #[track_caller]
+    pub fn with<T: 'static, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        Some(f(self.data.data.borrow().downcast_ref::<T>()?))
+    }
+
+    /// True if the value is a `T`.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.data.data.borrow().is::<T>()
+    }
+
+    /// The `TypeId` of the value.
+    pub fn value_type_id(&self) -> TypeId {
+        (**self.data.data.borrow()).type_id()
+    }
+
+    /// Take ownership of the value, consuming the cell.
+    pub fn into_inner(self) -> Box<dyn Any> {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each `set`. See the
+    /// `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        self.data.subscribe(callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        self.data.unsubscribe(subscription)
+    }
+}
+
+/// Holds `()`.
+impl Default for MutAny {
+    #[track_caller]
+    fn default() -> Self {
+        MutAny::new(())
+    }
+}
+
+impl fmt::Debug for MutAny {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutAny").finish_non_exhaustive()
+    }
+}
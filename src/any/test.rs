@@ -0,0 +1,44 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn downcast() {
+    let slot = MutAny::new(22_u32);
+    assert!(slot.is::<u32>());
+    assert_eq!(slot.get::<u32>(), Some(22));
+    assert_eq!(slot.get::<String>(), None);
+    assert_eq!(slot.value_type_id(), TypeId::of::<u32>());
+
+    slot.set(String::from("hello"));
+    assert!(!slot.is::<u32>());
+    assert_eq!(slot.with(|s: &String| s.len()), Some(5));
+    assert_eq!(slot.with(|n: &u32| *n), None);
+}
+
+#[test]
+fn non_clone_values() {
+    struct State {
+        hits: u32,
+    }
+
+    let slot = MutAny::new(State { hits: 3 });
+    assert_eq!(slot.with(|s: &State| s.hits), Some(3));
+    let state = slot.into_inner().downcast::<State>().ok().unwrap();
+    assert_eq!(state.hits, 3);
+}
+
+#[test]
+fn set_notifies() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let slot = MutAny::default();
+    assert!(slot.is::<()>());
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+    slot.subscribe(move || c.set(c.get() + 1));
+    slot.set(1_u8);
+    slot.set("two");
+    assert_eq!(count.get(), 2);
+}
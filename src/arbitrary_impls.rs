@@ -0,0 +1,57 @@
+//! `Arbitrary` impls for fuzzing, enabled by the `arbitrary` feature.
+//! Each generates the contents and wraps them in a fresh cell.
+
+use crate::map::MutMap;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use indexmap::IndexMap;
+use std::hash::Hash;
+
+mod test;
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Mut<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Mut::new(T::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        T::size_hint(depth)
+    }
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for MutVec<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(MutVec::from(Vec::arbitrary(u)?))
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        Ok(MutVec::from(Vec::arbitrary_take_rest(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        Vec::<T>::size_hint(depth)
+    }
+}
+
+/// Duplicate keys are merged, so the map may have fewer entries than
+/// were generated.
+impl<'a, K, V> Arbitrary<'a> for MutMap<K, V>
+where
+    K: Arbitrary<'a> + Eq + Hash,
+    V: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let entries: IndexMap<K, V> = u.arbitrary_iter()?.collect::<Result<_>>()?;
+        Ok(MutMap::from(entries))
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        let entries: IndexMap<K, V> = u.arbitrary_take_rest_iter()?.collect::<Result<_>>()?;
+        Ok(MutMap::from(entries))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        Vec::<(K, V)>::size_hint(depth)
+    }
+}
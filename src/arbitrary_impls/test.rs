@@ -0,0 +1,24 @@
+#![cfg(test)]
+
+use super::*;
+
+const DATA: &[u8] = &[7, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+#[test]
+fn generate() {
+    let mut u = Unstructured::new(DATA);
+    let m: Mut<u8> = Mut::arbitrary(&mut u).unwrap();
+    assert_eq!(m.get(), 7);
+
+    let v: MutVec<u8> = MutVec::arbitrary(&mut u).unwrap();
+    assert!(v.len() <= DATA.len());
+}
+
+#[test]
+fn take_rest() {
+    let v = MutVec::<u8>::arbitrary_take_rest(Unstructured::new(DATA)).unwrap();
+    assert!(!v.is_empty() && v.len() <= DATA.len());
+
+    let m = MutMap::<u8, u8>::arbitrary_take_rest(Unstructured::new(DATA)).unwrap();
+    assert!(!m.is_empty());
+}
@@ -0,0 +1,82 @@
+//! Read-copy-update for large, read-mostly values.
+
+use crate::mutbl::Mut;
+use crate::observe::SubscriptionId;
+use std::fmt;
+use std::sync::Arc;
+
+mod test;
+
+/// A `Mut<Arc<T>>` for values that are read often and replaced
+/// rarely. `load` hands out the current `Arc`, so a reader holds no
+/// lock while it looks at the value (however long that takes), and
+/// updates build a new value and swap it in rather than modifying the
+/// shared one in place.
+pub struct MutArcCell<T> {
+    data: Mut<Arc<T>>,
+}
+
+impl<T> MutArcCell<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        MutArcCell {
+            data: Mut::new(Arc::new(value)),
+        }
+    }
+
+    /// The current value. This is O(1), and the result is unaffected
+    /// by later stores.
+    pub fn load(&self) -> Arc<T> {
+        self.data.get()
+    }
+
+    /// Replace the current value.
+    pub fn store(&self, value: Arc<T>) {
+        self.data.set(value)
+    }
+
+    /// Replace the current value, returning the old one.
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        self.data.replace(value)
+    }
+
+    /// Read-copy-update: computes the new value with `f` from the
+    /// current one, then stores it. No lock is held while `f` runs.
+    /// If `f` itself stores a new value into `self`, that value is
+    /// passed to `f` again, so the final value always reflects `f`
+    /// applied to the value it replaced. Returns the new value.
+    #[track_caller]
+    pub fn rcu(&self, f: impl Fn(&T) -> T) -> Arc<T> {
+        loop {
+            let current = self.load();
+            let new = Arc::new(f(&current));
+            if Arc::ptr_eq(&self.data.data.borrow(), &current) {
+                self.store(new.clone());
+                return new;
+            }
+        }
+    }
+
+    /// Take ownership of the current value, consuming the cell.
+    pub fn into_inner(self) -> Arc<T> {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each store. See the
+    /// `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        self.data.subscribe(callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        self.data.unsubscribe(subscription)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutArcCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MutArcCell").field(&self.load()).finish()
+    }
+}
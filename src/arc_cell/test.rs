@@ -0,0 +1,44 @@
+#![cfg(test)]
+
+use super::*;
+use std::cell::Cell;
+
+#[test]
+fn load_store() {
+    let cell = MutArcCell::new(vec![1, 2]);
+    let before = cell.load();
+    cell.store(Arc::new(vec![3]));
+    assert_eq!(*before, vec![1, 2]);
+    assert_eq!(*cell.load(), vec![3]);
+    assert_eq!(*cell.swap(Arc::new(vec![])), vec![3]);
+}
+
+#[test]
+fn rcu() {
+    let cell = MutArcCell::new(vec![1, 2]);
+    let reader = cell.load();
+    let new = cell.rcu(|v| {
+        let mut v = v.clone();
+        v.push(3);
+        v
+    });
+    assert!(Arc::ptr_eq(&new, &cell.load()));
+    assert_eq!(*reader, vec![1, 2]);
+    assert_eq!(*cell.load(), vec![1, 2, 3]);
+}
+
+#[test]
+fn rcu_reentrant() {
+    let cell = MutArcCell::new(1);
+    let calls = Cell::new(0);
+    cell.rcu(|n| {
+        calls.set(calls.get() + 1);
+        if calls.get() == 1 {
+            // Can read and even store while computing the update.
+            cell.store(Arc::new(*cell.load() * 10));
+        }
+        n + 1
+    });
+    assert_eq!(calls.get(), 2);
+    assert_eq!(*cell.load(), 11);
+}
@@ -0,0 +1,141 @@
+use crate::iter_check::MutationCheck;
+use crate::mcell::MCell;
+use std::fmt;
+
+mod test;
+
+/// A fixed-size array of `N` values, stored inline (with no heap
+/// allocation of its own).
+pub struct MutArray<T, const N: usize> {
+    data: MCell<[T; N]>,
+}
+
+impl<T, const N: usize> MutArray<T, N> {
+    #[track_caller]
+    pub fn new(values: [T; N]) -> Self {
+        MutArray {
+            data: MCell::new(values),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Load the value at `index`, returning `None` if it is out of
+    /// bounds.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().get(index).cloned()
+    }
+
+    /// Store `value` at `index`, returning the old value. Panics if
+    /// `index` is out of bounds.
+    pub fn replace(&self, index: usize, value: T) -> T {
+        assert!(
+            index < N,
+            "index {} is out of bounds for length {}",
+            index,
+            N
+        );
+        std::mem::replace(&mut self.data.borrow_mut()[index], value)
+    }
+
+    /// Store `value` at `index`. Panics if `index` is out of bounds.
+    pub fn set(&self, index: usize, value: T) {
+        self.replace(index, value);
+    }
+
+    /// Swap the values at `i` and `j`. Panics if either is out of
+    /// bounds.
+    pub fn swap(&self, i: usize, j: usize) {
+        assert!(
+            i < N && j < N,
+            "({}, {}) is out of bounds for length {}",
+            i,
+            j,
+            N
+        );
+        self.data.borrow_mut().swap(i, j);
+    }
+
+    /// Runs `op` with a reference to the values. All mut-cells are
+    /// read-only while `op` runs.
+    #[track_caller]
+    pub fn with<R>(&self, op: impl FnOnce(&[T; N]) -> R) -> R {
+        op(&self.data.borrow())
+    }
+
+    pub fn into_inner(self) -> [T; N] {
+        self.data.into_inner()
+    }
+
+    /// Iterate over the values, cloning them as we go. The array is
+    /// borrowed only for each step, so it may be modified during the
+    /// iteration; in debug builds, doing so logs a warning.
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_, T, N>
+    where
+        T: Clone,
+    {
+        Iter {
+            array: self,
+            index: 0,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+}
+
+impl<T: Default, const N: usize> Default for MutArray<T, N> {
+    #[track_caller]
+    fn default() -> Self {
+        MutArray::new(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for MutArray<T, N> {
+    #[track_caller]
+    fn from(values: [T; N]) -> Self {
+        MutArray::new(values)
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for MutArray<T, N> {
+    fn clone(&self) -> Self {
+        MutArray::new(self.data.borrow().clone())
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for MutArray<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+pub struct Iter<'iter, T, const N: usize> {
+    array: &'iter MutArray<T, N>,
+    index: usize,
+    check: MutationCheck,
+}
+
+impl<'iter, T: Clone, const N: usize> Iterator for Iter<'iter, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.check.check();
+        let value = self.array.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = N - self.index;
+        (remaining, Some(remaining))
+    }
+}
@@ -0,0 +1,24 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn get_set_swap() {
+    let a = MutArray::from([1, 2, 3]);
+    assert_eq!(a.len(), 3);
+    a.set(0, 10);
+    assert_eq!(a.replace(2, 30), 3);
+    a.swap(0, 2);
+    assert_eq!(a.get(0), Some(30));
+    assert_eq!(a.get(3), None);
+    assert_eq!(a.iter().collect::<Vec<_>>(), vec![30, 2, 10]);
+    assert_eq!(format!("{:?}", a), "[30, 2, 10]");
+    assert_eq!(a.into_inner(), [30, 2, 10]);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn swap_out_of_bounds() {
+    let a: MutArray<u8, 2> = MutArray::default();
+    a.swap(0, 2);
+}
@@ -0,0 +1,144 @@
+use crate::mcell::MCell;
+use std::fmt;
+
+mod test;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of small integers (e.g., visited flags indexed by node),
+/// stored one bit each. The storage grows as bits are set; bits
+/// beyond it read as clear.
+pub struct MutBitSet {
+    data: MCell<Vec<u64>>,
+}
+
+impl MutBitSet {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a set with room for the indices `0..bits` without
+    /// reallocating.
+    #[track_caller]
+    pub fn with_capacity(bits: usize) -> Self {
+        MutBitSet {
+            data: MCell::new(vec![0; bits.div_ceil(BITS)]),
+        }
+    }
+
+    /// Set bit `index`, returning whether it was already set.
+    pub fn set(&self, index: usize) -> bool {
+        let (word, mask) = (index / BITS, 1 << (index % BITS));
+        let mut data = self.data.borrow_mut();
+        if word >= data.len() {
+            data.resize(word + 1, 0);
+        }
+        let was_set = data[word] & mask != 0;
+        data[word] |= mask;
+        was_set
+    }
+
+    /// Clear bit `index`, returning whether it was set.
+    pub fn clear(&self, index: usize) -> bool {
+        let (word, mask) = (index / BITS, 1 << (index % BITS));
+        let mut data = self.data.borrow_mut();
+        match data.get_mut(word) {
+            Some(bits) => {
+                let was_set = *bits & mask != 0;
+                *bits &= !mask;
+                was_set
+            }
+            None => false,
+        }
+    }
+
+    /// Clear every bit, keeping the storage.
+    pub fn clear_all(&self) {
+        self.data.borrow_mut().iter_mut().for_each(|bits| *bits = 0);
+    }
+
+    pub fn test(&self, index: usize) -> bool {
+        let data = self.data.borrow();
+        data.get(index / BITS)
+            .is_some_and(|bits| bits & (1 << (index % BITS)) != 0)
+    }
+
+    /// The number of bits set.
+    pub fn count_ones(&self) -> usize {
+        let data = self.data.borrow();
+        data.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// The first bit set at or after `index`, if any.
+    fn next_set(&self, index: usize) -> Option<usize> {
+        let data = self.data.borrow();
+        let mut word = index / BITS;
+        let mut bits = *data.get(word)? & (!0 << (index % BITS));
+        loop {
+            if bits != 0 {
+                return Some(word * BITS + bits.trailing_zeros() as usize);
+            }
+            word += 1;
+            bits = *data.get(word)?;
+        }
+    }
+
+    /// Iterate over the indices of the set bits, in increasing order.
+    /// The set is borrowed only for each step, so it may be modified
+    /// during the iteration: a bit set ahead of the current position
+    /// is visited.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            set: self,
+            index: 0,
+        }
+    }
+}
+
+impl Default for MutBitSet {
+    #[track_caller]
+    fn default() -> Self {
+        MutBitSet::with_capacity(0)
+    }
+}
+
+impl Clone for MutBitSet {
+    fn clone(&self) -> Self {
+        MutBitSet {
+            data: MCell::new(self.data.borrow().clone()),
+        }
+    }
+}
+
+impl std::iter::FromIterator<usize> for MutBitSet {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let set = MutBitSet::new();
+        for index in iter {
+            set.set(index);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for MutBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'iter> {
+    set: &'iter MutBitSet,
+    index: usize,
+}
+
+impl<'iter> Iterator for Iter<'iter> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let index = self.set.next_set(self.index)?;
+        self.index = index + 1;
+        Some(index)
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn set_clear_test() {
+    let bits = MutBitSet::new();
+    assert!(!bits.set(3));
+    assert!(bits.set(3));
+    assert!(!bits.set(200));
+    assert!(bits.test(3));
+    assert!(!bits.test(4));
+    assert!(!bits.test(10_000));
+    assert_eq!(bits.count_ones(), 2);
+
+    assert!(bits.clear(3));
+    assert!(!bits.clear(3));
+    assert!(!bits.clear(10_000));
+    assert_eq!(bits.count_ones(), 1);
+
+    bits.clear_all();
+    assert_eq!(bits.count_ones(), 0);
+}
+
+#[test]
+fn iter() {
+    let bits: MutBitSet = vec![0, 63, 64, 130].into_iter().collect();
+    assert_eq!(bits.iter().collect::<Vec<_>>(), vec![0, 63, 64, 130]);
+    assert_eq!(format!("{:?}", bits), "{0, 63, 64, 130}");
+
+    // A bit set ahead of the current position is visited.
+    let seen: Vec<usize> = bits
+        .iter()
+        .inspect(|&i| {
+            if i == 64 {
+                bits.set(100);
+            }
+        })
+        .collect();
+    assert_eq!(seen, vec![0, 63, 64, 100, 130]);
+}
+
+#[test]
+fn compact() {
+    let bits = MutBitSet::with_capacity(1000);
+    assert_eq!(bits.data.borrow().len(), 16);
+    assert_eq!(MutBitSet::new().iter().next(), None);
+}
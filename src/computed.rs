@@ -0,0 +1,113 @@
+use crate::mcell::track;
+use std::cell::Cell;
+
+mod test;
+
+/// A value derived from other cells, spreadsheet-style.
+///
+/// The first call to `get` runs the `compute` closure and records
+/// which cells it read. Later calls return the cached value unless
+/// one of those cells has been mutated in the meantime, in which case
+/// the value is recomputed. A `Computed` read from within another
+/// `Computed`'s closure passes its dependencies along, so derived
+/// values can be layered.
+///
+/// The `compute` closure should be pure: it may read any cells, but
+/// should not mutate them.
+pub struct Computed<T, F = fn() -> T> {
+    compute: F,
+    cache: Cell<Option<Cache<T>>>,
+    computing: Cell<bool>,
+}
+
+struct Cache<T> {
+    value: T,
+
+    /// Revision at which `value` was computed.
+    revision: u64,
+
+    /// Ids of the cells that were read to compute `value`.
+    dependencies: Vec<u64>,
+}
+
+impl<T> Cache<T> {
+    fn is_stale(&self) -> bool {
+        self.dependencies
+            .iter()
+            .any(|&id| track::changed_at(id) > self.revision)
+    }
+}
+
+impl<T, F> Computed<T, F>
+where
+    F: Fn() -> T,
+{
+    pub fn new(compute: F) -> Self {
+        Computed {
+            compute,
+            cache: Cell::new(None),
+            computing: Cell::new(false),
+        }
+    }
+
+    /// Load the value, recomputing it if any of its dependencies have
+    /// changed since it was last computed.
+    ///
+    /// Panics if invoked on `self` from within its own `compute`
+    /// closure.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        if let Some(cache) = self.cache.take() {
+            if !cache.is_stale() {
+                let value = cache.value.clone();
+                track::record_reads(&cache.dependencies);
+                self.cache.set(Some(cache));
+                return value;
+            }
+        }
+
+        if self.computing.replace(true) {
+            panic!("cycle detected: `Computed` value depends on itself");
+        }
+        let revision = track::current_revision();
+        let (value, dependencies) = {
+            let _reset = ResetOnDrop(&self.computing);
+            track::tracking(&self.compute)
+        };
+        self.cache.set(Some(Cache {
+            value: value.clone(),
+            revision,
+            dependencies,
+        }));
+        value
+    }
+
+    /// True if the next call to `get` will run the `compute` closure.
+    pub fn is_stale(&self) -> bool {
+        match self.cache.take() {
+            Some(cache) => {
+                let stale = cache.is_stale();
+                self.cache.set(Some(cache));
+                stale
+            }
+            None => true,
+        }
+    }
+
+    /// Discard the cached value, forcing it to be recomputed on the
+    /// next call to `get`.
+    pub fn invalidate(&self) {
+        self.cache.set(None);
+    }
+}
+
+/// Clears the `computing` flag even if the closure panics.
+struct ResetOnDrop<'me>(&'me Cell<bool>);
+
+impl<'me> Drop for ResetOnDrop<'me> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{Mut, MutOnce, MutVec};
+use std::rc::Rc;
+
+#[test]
+fn recompute_on_change() {
+    let runs = Cell::new(0);
+    let a = Mut::new(1);
+    let b = Mut::new(2);
+    let unrelated = Mut::new(0);
+
+    let sum = Computed::new(|| {
+        runs.set(runs.get() + 1);
+        a.get() + b.get()
+    });
+    assert_eq!(sum.get(), 3);
+    assert_eq!(sum.get(), 3);
+    assert_eq!(runs.get(), 1);
+
+    unrelated.set(1);
+    assert!(!sum.is_stale());
+
+    b.set(10);
+    assert!(sum.is_stale());
+    assert_eq!(sum.get(), 11);
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn layered() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let total = Computed::new(|| v.iter().sum::<i32>());
+    let doubled = Computed::new(|| total.get() * 2);
+
+    assert_eq!(doubled.get(), 12);
+    v.push(4);
+
+    // `doubled` never read `v` directly, but inherits the dependency
+    // from `total`.
+    assert!(doubled.is_stale());
+    assert_eq!(doubled.get(), 20);
+}
+
+#[test]
+#[should_panic(expected = "cycle detected")]
+fn cycle() {
+    type Dyn = Computed<u32, Box<dyn Fn() -> u32>>;
+    let slot: Rc<MutOnce<Rc<Dyn>>> = Rc::new(MutOnce::new());
+    let c: Rc<Dyn> = Rc::new(Computed::new(Box::new({
+        let slot = slot.clone();
+        move || slot.get().unwrap().get() + 1
+    })));
+    assert!(slot.set(c.clone()).is_ok());
+    c.get();
+}
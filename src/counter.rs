@@ -0,0 +1,88 @@
+use crate::map::MutMap;
+use std::hash::Hash;
+
+mod test;
+
+/// Counts occurrences of keys. Each update to a count happens in a
+/// single locked step, unlike a `get` followed by an `insert` on a
+/// plain `MutMap`.
+pub struct MutCounter<K> {
+    counts: MutMap<K, usize>,
+}
+
+impl<K> MutCounter<K>
+where
+    K: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Adds `n` to the count for `key`, returning the new count.
+    pub fn add(&self, key: K, n: usize) -> usize {
+        self.counts.with_checked_out(|counts| {
+            let count = counts.entry(key).or_insert(0);
+            *count += n;
+            *count
+        })
+    }
+
+    /// The current count for `key` (zero if it was never added).
+    pub fn count(&self, key: &K) -> usize {
+        self.counts.get(key).unwrap_or(0)
+    }
+
+    /// Sum of all counts.
+    pub fn total(&self) -> usize {
+        self.counts.with_borrowed(|counts| counts.values().sum())
+    }
+
+    /// The `n` keys with the highest counts, most common first. Keys
+    /// with equal counts are returned in the order they were first
+    /// added.
+    pub fn most_common(&self, n: usize) -> Vec<(K, usize)>
+    where
+        K: Clone,
+    {
+        let mut counts: Vec<(K, usize)> = self.counts.iter().collect();
+        counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        counts.truncate(n);
+        counts
+    }
+}
+
+impl<K> Default for MutCounter<K>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        MutCounter {
+            counts: MutMap::default(),
+        }
+    }
+}
+
+impl<K> std::iter::FromIterator<K> for MutCounter<K>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = K>,
+    {
+        let counter = MutCounter::new();
+        for key in iter {
+            counter.add(key, 1);
+        }
+        counter
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn add_and_count() {
+    let c = MutCounter::new();
+    assert_eq!(c.add("a", 1), 1);
+    assert_eq!(c.add("b", 3), 3);
+    assert_eq!(c.add("a", 2), 3);
+    assert_eq!(c.count(&"a"), 3);
+    assert_eq!(c.count(&"z"), 0);
+    assert_eq!(c.total(), 6);
+    assert_eq!(c.len(), 2);
+}
+
+#[test]
+fn most_common() {
+    let c: MutCounter<char> = "abracadabra".chars().collect();
+    assert_eq!(c.most_common(3), vec![('a', 5), ('b', 2), ('r', 2)]);
+    assert_eq!(c.most_common(0), vec![]);
+    assert_eq!(c.most_common(10).len(), 5);
+}
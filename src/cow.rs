@@ -0,0 +1,287 @@
+//! Copy-on-write collections. These store their contents behind an
+//! `Arc`, so `snapshot` is O(1); the first mutation after a snapshot
+//! clones the contents (once), leaving the snapshot untouched.
+
+use crate::mcell::MCell;
+use indexmap::Equivalent;
+use indexmap::IndexMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+mod test;
+
+pub struct MutCowVec<T> {
+    data: MCell<Arc<Vec<T>>>,
+}
+
+impl<T: Clone> MutCowVec<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// The equivalent of `self[index]` -- load the element at the
+    /// given index, panicking if there is no such element.
+    pub fn at(&self, index: usize) -> T {
+        self.get(index).unwrap()
+    }
+
+    /// Attempt to get the element at the given `index`, returning
+    /// `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let data = self.data.borrow();
+        Some(data.get(index)?.clone())
+    }
+
+    /// A frozen view of the current contents, in O(1).
+    pub fn snapshot(&self) -> Arc<Vec<T>> {
+        self.data.borrow().clone()
+    }
+
+    /// Ensure no snapshot shares our contents, cloning them if
+    /// needed. The clone happens under a read lock, so `T::clone` may
+    /// read other cells.
+    fn make_unique(&self) {
+        let copy = {
+            let data = self.data.borrow();
+            if Arc::strong_count(&data) == 1 && Arc::weak_count(&data) == 0 {
+                return;
+            }
+            Vec::clone(&data)
+        };
+        self.data.set(Arc::new(copy));
+    }
+
+    /// Push `value` onto the end of the vector.
+    pub fn push(&self, value: T) {
+        self.make_unique();
+        let mut data = self.data.borrow_mut();
+        Arc::get_mut(&mut data).unwrap().push(value);
+    }
+
+    /// Pop a value from the end of the vector, if any.
+    pub fn pop(&self) -> Option<T> {
+        self.make_unique();
+        let mut data = self.data.borrow_mut();
+        Arc::get_mut(&mut data).unwrap().pop()
+    }
+
+    /// Replace the contents with `v`, returning the old contents
+    /// (which may be shared with outstanding snapshots).
+    pub fn replace(&self, v: Vec<T>) -> Arc<Vec<T>> {
+        self.data.replace(Arc::new(v))
+    }
+
+    /// Iterate over the elements in `self`, cloning them as we go.
+    ///
+    /// As with `MutVec::iter`, mutating `self` during this iteration
+    /// may lead to surprising results but is not undefined behavior.
+    /// To iterate over a stable view, iterate over a `snapshot`.
+    pub fn iter(&self) -> VecIter<'_, T> {
+        VecIter {
+            vec: self,
+            index: 0,
+        }
+    }
+}
+
+impl<T: Clone> Clone for MutCowVec<T> {
+    /// Cloning is O(1): the clone shares our contents until one of
+    /// the two is mutated.
+    fn clone(&self) -> Self {
+        MutCowVec {
+            data: MCell::new(self.snapshot()),
+        }
+    }
+}
+
+impl<T> Default for MutCowVec<T> {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl<T> From<Vec<T>> for MutCowVec<T> {
+    fn from(v: Vec<T>) -> Self {
+        MutCowVec {
+            data: MCell::new(Arc::new(v)),
+        }
+    }
+}
+
+impl<A> std::iter::FromIterator<A> for MutCowVec<A> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A>,
+    {
+        let v: Vec<A> = iter.into_iter().collect();
+        MutCowVec::from(v)
+    }
+}
+
+pub struct VecIter<'iter, T>
+where
+    T: Clone,
+{
+    vec: &'iter MutCowVec<T>,
+    index: usize,
+}
+
+impl<'iter, T> Iterator for VecIter<'iter, T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.vec.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
+
+pub struct MutCowMap<K, V> {
+    data: MCell<Arc<IndexMap<K, V>>>,
+}
+
+impl<K, V> MutCowMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// A frozen view of the current contents, in O(1).
+    pub fn snapshot(&self) -> Arc<IndexMap<K, V>> {
+        self.data.borrow().clone()
+    }
+
+    /// Inserts `(key, value)` into the map, first cloning the
+    /// contents if they are shared with a snapshot. As with
+    /// `MutMap::insert`, all mut-cells are read-only during the
+    /// operation and *this* map will appear empty.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        Arc::make_mut(&mut self.data.check_out()).insert(key, value)
+    }
+
+    /// Removes `key` from the map, first cloning the contents if they
+    /// are shared with a snapshot.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+        Arc::make_mut(&mut self.data.check_out()).remove(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.data.borrow().contains_key(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.data.borrow().get(key).cloned()
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(K, V)> {
+        let data = self.data.borrow();
+        let (k, v) = data.get_index(index)?;
+        Some((k.clone(), v.clone()))
+    }
+
+    /// Iterate over the entries in `self`, cloning them as we go.
+    ///
+    /// As with `MutMap::iter`, mutating `self` during this iteration
+    /// may lead to surprising results but is not undefined behavior.
+    /// To iterate over a stable view, iterate over a `snapshot`.
+    pub fn iter(&self) -> MapIter<'_, K, V> {
+        MapIter {
+            map: self,
+            index: 0,
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for MutCowMap<K, V> {
+    /// Cloning is O(1): the clone shares our contents until one of
+    /// the two is mutated.
+    fn clone(&self) -> Self {
+        MutCowMap {
+            data: MCell::new(self.data.borrow().clone()),
+        }
+    }
+}
+
+impl<K, V> Default for MutCowMap<K, V> {
+    fn default() -> Self {
+        Self::from(IndexMap::new())
+    }
+}
+
+impl<K, V> From<IndexMap<K, V>> for MutCowMap<K, V> {
+    fn from(map: IndexMap<K, V>) -> Self {
+        MutCowMap {
+            data: MCell::new(Arc::new(map)),
+        }
+    }
+}
+
+impl<K, V> std::iter::FromIterator<(K, V)> for MutCowMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let map: IndexMap<K, V> = iter.into_iter().collect();
+        MutCowMap::from(map)
+    }
+}
+
+pub struct MapIter<'iter, K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    map: &'iter MutCowMap<K, V>,
+    index: usize,
+}
+
+impl<'iter, K, V> Iterator for MapIter<'iter, K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let entry = self.map.get_index(self.index)?;
+        self.index += 1;
+        Some(entry)
+    }
+}
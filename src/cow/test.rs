@@ -0,0 +1,34 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn vec_snapshot_is_frozen() {
+    let v = MutCowVec::from(vec![1, 2]);
+    let snap = v.snapshot();
+    v.push(3);
+    let snap2 = v.snapshot();
+    v.pop();
+    assert_eq!(*snap, vec![1, 2]);
+    assert_eq!(*snap2, vec![1, 2, 3]);
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 2]);
+
+    // Without outstanding snapshots, mutation happens in place.
+    drop((snap, snap2));
+    let before = Arc::as_ptr(&v.snapshot());
+    v.push(4);
+    assert_eq!(Arc::as_ptr(&v.snapshot()), before);
+}
+
+#[test]
+fn map_snapshot_is_frozen() {
+    let m: MutCowMap<_, _> = vec![("a", 1)].into_iter().collect();
+    let copy = m.clone();
+    let snap = m.snapshot();
+    m.insert("b", 2);
+    assert_eq!(m.remove("a"), Some(1));
+    assert_eq!(m.remove("z"), None);
+    assert_eq!(snap.len(), 1);
+    assert_eq!(copy.get("a"), Some(1));
+    assert_eq!(m.iter().collect::<Vec<_>>(), vec![("b", 2)]);
+}
@@ -0,0 +1,107 @@
+//! An inventory of the live cells on this thread (enabled with the
+//! `debug` feature), for tracking down which shared structure is
+//! ballooning or was left locked.
+//!
+//! Every cell registers itself when created, recording its type and
+//! where it was created. For `Mut`, `MutVec`, and `MutMap`, that is
+//! the location of the constructor call; for the other types, it may
+//! be a location inside this crate. Collections also report their
+//! current size.
+
+use crate::mcell::lock;
+use crate::memory::MemoryUsage;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::panic::Location;
+
+mod test;
+
+/// A live cell, as reported by `dump`.
+#[derive(Clone, Debug)]
+pub struct CellInfo {
+    /// The cell's id (see `revision::CellId`).
+    pub id: u64,
+
+    /// The type of the data in the cell.
+    pub type_name: &'static str,
+
+    /// Where the cell was created.
+    pub location: &'static Location<'static>,
+
+    /// The number of elements, for collections.
+    pub size: Option<usize>,
+
+    /// The footprint, for collections.
+    pub usage: Option<MemoryUsage>,
+}
+
+thread_local! {
+    static CELLS: RefCell<BTreeMap<u64, CellInfo>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub(crate) fn register(id: u64, type_name: &'static str, location: &'static Location<'static>) {
+    CELLS.with(|cells| {
+        cells.borrow_mut().insert(
+            id,
+            CellInfo {
+                id,
+                type_name,
+                location,
+                size: None,
+                usage: None,
+            },
+        )
+    });
+}
+
+pub(crate) fn update_size(id: u64, usage: MemoryUsage) {
+    CELLS.with(|cells| {
+        if let Some(info) = cells.borrow_mut().get_mut(&id) {
+            info.size = Some(usage.len);
+            info.usage = Some(usage);
+        }
+    });
+}
+
+pub(crate) fn forget(id: u64) {
+    // The registry may already be gone during thread teardown.
+    let _ = CELLS.try_with(|cells| cells.borrow_mut().remove(&id));
+}
+
+/// The live cells on this thread, oldest first.
+pub fn dump() -> Vec<CellInfo> {
+    CELLS.with(|cells| cells.borrow().values().cloned().collect())
+}
+
+/// The total `heap_bytes` of the live collections on this thread.
+pub fn total_heap_bytes() -> usize {
+    CELLS.with(|cells| {
+        cells
+            .borrow()
+            .values()
+            .filter_map(|info| info.usage)
+            .map(|usage| usage.heap_bytes)
+            .sum()
+    })
+}
+
+/// A human-readable rendering of `dump`, with one cell per line,
+/// preceded by whether the thread currently holds a lock.
+pub fn report() -> String {
+    let mut out = String::new();
+    let locked = if lock::is_locked() {
+        "locked"
+    } else {
+        "unlocked"
+    };
+    writeln!(out, "thread lock: {}", locked).unwrap();
+    for info in dump() {
+        write!(out, "#{} {} at {}", info.id, info.type_name, info.location).unwrap();
+        if let Some(usage) = info.usage {
+            write!(out, " (size {}, {} bytes)", usage.len, usage.heap_bytes).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
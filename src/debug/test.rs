@@ -0,0 +1,35 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{Mut, MutMap, MutVec};
+
+fn find(id: u64) -> Option<CellInfo> {
+    dump().into_iter().find(|info| info.id == id)
+}
+
+#[test]
+fn inventory() {
+    let line = line!() + 1;
+    let v = MutVec::new();
+    let id = v.data.existing_id().unwrap();
+    let info = find(id).unwrap();
+    assert_eq!(info.location.line(), line);
+    assert!(info.type_name.contains("Vec<u32>"));
+    assert_eq!(info.size, Some(0));
+
+    v.push(1u32);
+    v.push(2);
+    assert_eq!(find(id).unwrap().size, Some(2));
+
+    let m: MutMap<u32, u32> = (0..5).map(|i| (i, i)).collect();
+    assert_eq!(find(m.data.existing_id().unwrap()).unwrap().size, Some(5));
+
+    let c = Mut::new(3);
+    assert_eq!(find(c.data.existing_id().unwrap()).unwrap().size, None);
+    assert!(report().contains(&format!("#{} alloc::vec::Vec<u32> at ", id)));
+
+    assert!(total_heap_bytes() >= v.memory_usage().heap_bytes + m.memory_usage().heap_bytes);
+
+    drop(v);
+    assert!(find(id).is_none());
+}
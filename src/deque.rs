@@ -0,0 +1,168 @@
+use crate::iter_check::MutationCheck;
+use crate::mcell::MCell;
+use crate::observe;
+use crate::observe::SubscriptionId;
+use std::collections::VecDeque;
+use std::fmt;
+
+mod test;
+
+/// A double-ended queue of mutable data, with O(1) pushes and pops at
+/// both ends.
+pub struct MutDeque<T> {
+    pub(crate) data: MCell<VecDeque<T>>,
+}
+
+impl<T> MutDeque<T> {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Push `value` onto the front of the deque.
+    pub fn push_front(&self, value: T) {
+        self.data.borrow_mut().push_front(value);
+    }
+
+    /// Push `value` onto the back of the deque.
+    pub fn push_back(&self, value: T) {
+        self.data.borrow_mut().push_back(value);
+    }
+
+    /// Pop a value from the front of the deque, if any.
+    pub fn pop_front(&self) -> Option<T> {
+        self.data.borrow_mut().pop_front()
+    }
+
+    /// Pop a value from the back of the deque, if any.
+    pub fn pop_back(&self) -> Option<T> {
+        self.data.borrow_mut().pop_back()
+    }
+
+    /// Get the element at `index`, where index 0 is the front.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().get(index).cloned()
+    }
+
+    pub fn front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().front().cloned()
+    }
+
+    pub fn back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().back().cloned()
+    }
+
+    /// Iterate over the elements from front to back, cloning them as
+    /// we go.
+    ///
+    /// As with `MutVec::iter`, `self` may be mutated during the
+    /// iteration, which may lead to surprising results (a `pop_front`
+    /// shifts the remaining elements) but is not undefined behavior.
+    /// In debug builds, such a mutation logs a warning.
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            deque: self,
+            index: 0,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+
+    /// Take ownership of our internal deque, replacing it with an
+    /// empty one.
+    pub fn take(&self) -> VecDeque<T> {
+        self.data.take()
+    }
+
+    /// Take ownership of our internal deque, consuming `self`.
+    pub fn into_inner(self) -> VecDeque<T> {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+}
+
+impl<T: Clone> Clone for MutDeque<T> {
+    fn clone(&self) -> Self {
+        MutDeque::from(self.data.borrow().clone())
+    }
+}
+
+impl<T> Default for MutDeque<T> {
+    #[track_caller]
+    fn default() -> Self {
+        MutDeque::from(VecDeque::new())
+    }
+}
+
+impl<A> std::iter::FromIterator<A> for MutDeque<A> {
+    #[track_caller]
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A>,
+    {
+        MutDeque::from(iter.into_iter().collect::<VecDeque<A>>())
+    }
+}
+
+impl<T> From<VecDeque<T>> for MutDeque<T> {
+    #[track_caller]
+    fn from(deque: VecDeque<T>) -> Self {
+        MutDeque {
+            data: MCell::new(deque),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+pub struct Iter<'iter, T> {
+    deque: &'iter MutDeque<T>,
+    index: usize,
+    check: MutationCheck,
+}
+
+impl<'iter, T: Clone> Iterator for Iter<'iter, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.check.check();
+        let value = self.deque.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
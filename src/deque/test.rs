@@ -0,0 +1,42 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn both_ends() {
+    let d = MutDeque::new();
+    d.push_back(2);
+    d.push_back(3);
+    d.push_front(1);
+    assert_eq!(d.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(d.front(), Some(1));
+    assert_eq!(d.back(), Some(3));
+    assert_eq!(d.pop_front(), Some(1));
+    assert_eq!(d.pop_back(), Some(3));
+    assert_eq!(d.get(0), Some(2));
+    assert_eq!(d.len(), 1);
+    assert_eq!(format!("{:?}", d), "[2]");
+}
+
+#[test]
+fn as_work_queue() {
+    // Items pushed while draining are processed in the same loop.
+    let d: MutDeque<u32> = vec![3].into_iter().collect();
+    let mut seen = vec![];
+    while let Some(n) = d.pop_front() {
+        seen.push(n);
+        if n > 0 {
+            d.push_back(n - 1);
+        }
+    }
+    assert_eq!(seen, vec![3, 2, 1, 0]);
+    assert!(d.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "lock already held")]
+fn push_while_borrowed() {
+    let d = MutDeque::from(VecDeque::from(vec![1]));
+    let _data = d.data.borrow();
+    d.push_back(2);
+}
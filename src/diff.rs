@@ -0,0 +1,91 @@
+//! Structured differences between the current contents of a
+//! collection and an older snapshot of it.
+
+use indexmap::IndexMap;
+use std::hash::Hash;
+
+mod test;
+
+/// The changes that turn an older snapshot of a map into its current
+/// contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapDiff<K, V> {
+    /// Entries present now but not in the snapshot.
+    pub added: Vec<(K, V)>,
+
+    /// Keys present in the snapshot but not now.
+    pub removed: Vec<K>,
+
+    /// Entries present in both whose values differ, with the current
+    /// value.
+    pub changed: Vec<(K, V)>,
+}
+
+impl<K, V> MapDiff<K, V> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub(crate) fn diff_maps<K, V>(current: &IndexMap<K, V>, older: &IndexMap<K, V>) -> MapDiff<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: PartialEq + Clone,
+{
+    let mut diff = MapDiff {
+        added: vec![],
+        removed: vec![],
+        changed: vec![],
+    };
+    for (key, value) in current {
+        match older.get(key) {
+            None => diff.added.push((key.clone(), value.clone())),
+            Some(old) if old != value => diff.changed.push((key.clone(), value.clone())),
+            Some(_) => {}
+        }
+    }
+    for key in older.keys() {
+        if !current.contains_key(key) {
+            diff.removed.push(key.clone());
+        }
+    }
+    diff
+}
+
+/// The changes that turn an older snapshot of a vector into its
+/// current contents, compared position by position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VecDiff<T> {
+    /// Elements at indices beyond the end of the snapshot.
+    pub added: Vec<(usize, T)>,
+
+    /// Indices present in the snapshot but beyond the current end.
+    pub removed: Vec<usize>,
+
+    /// Indices present in both whose elements differ, with the
+    /// current element.
+    pub changed: Vec<(usize, T)>,
+}
+
+impl<T> VecDiff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub(crate) fn diff_slices<T>(current: &[T], older: &[T]) -> VecDiff<T>
+where
+    T: PartialEq + Clone,
+{
+    let common = current.len().min(older.len());
+    VecDiff {
+        added: (common..current.len())
+            .map(|i| (i, current[i].clone()))
+            .collect(),
+        removed: (common..older.len()).collect(),
+        changed: (0..common)
+            .filter(|&i| current[i] != older[i])
+            .map(|i| (i, current[i].clone()))
+            .collect(),
+    }
+}
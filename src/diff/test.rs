@@ -0,0 +1,32 @@
+#![cfg(test)]
+
+use crate::{MutMap, MutVec};
+use indexmap::IndexMap;
+
+#[test]
+fn map_diff() {
+    let older: IndexMap<_, _> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    let mut m = MutMap::from(older.clone());
+    assert!(m.diff(&older).is_empty());
+
+    m.insert("b", 20);
+    m.insert("d", 4);
+    m.remove("a");
+    let diff = m.diff(&older);
+    assert_eq!(diff.added, vec![("d", 4)]);
+    assert_eq!(diff.removed, vec!["a"]);
+    assert_eq!(diff.changed, vec![("b", 20)]);
+}
+
+#[test]
+fn vec_diff() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let diff = v.diff(&[1, 5]);
+    assert_eq!(diff.added, vec![(2, 3)]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.changed, vec![(1, 2)]);
+
+    let diff = v.diff(&[1, 2, 3, 4, 5]);
+    assert_eq!(diff.removed, vec![3, 4]);
+    assert!(diff.added.is_empty() && diff.changed.is_empty());
+}
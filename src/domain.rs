@@ -0,0 +1,97 @@
+//! Lock domains. Normally, all mut-cells on a thread share one thread
+//! lock: while a `MutVec` is being iterated, no other cell can be
+//! modified, and while one is being modified, no other can be read.
+//! That is what makes handing out references safe, as the cell being
+//! iterated may be reachable from the one being modified.
+//!
+//! A `LockDomain` has a lock of its own. Cells created inside
+//! `LockDomain::enter` belong to the domain (for their whole life),
+//! and locking them does not lock the cells of other domains. This
+//! lets unrelated subsystems -- each with its own domain -- access
+//! their data independently:
+//!
+//! ```
+//! use mutable::{LockDomain, MutVec};
+//!
+//! let log: MutVec<String> = LockDomain::new().enter(MutVec::new);
+//! let files = MutVec::from(vec!["a.txt"]);
+//!
+//! // Without the domain, pushing while `files` is borrowed would panic.
+//! files.with_element(0, |file| log.push(format!("opened {}", file)));
+//! assert_eq!(log.into_inner(), vec!["opened a.txt"]);
+//! ```
+//!
+//! Each access still checks the lock of the cell's own domain, so
+//! every cell is protected as before. What a domain gives up is the
+//! guarantee that *nothing else* changes during a borrow: while a
+//! cell of one domain is borrowed, the cells of other domains can be
+//! modified, even if they are reachable from it (say, an `Rc<Mut<T>>`
+//! shared between the data of two domains). Code that relies on a
+//! group of cells staying consistent during a borrow (e.g., a
+//! `MutVec<Mut<T>>` and its elements) should keep them in the same
+//! domain.
+//!
+//! Domains are meant to be few and long-lived (say, one per
+//! subsystem): each one takes up a slot of every thread's lock state
+//! for the life of the program.
+
+use crate::mcell::lock::{self, Domain};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+mod test;
+
+/// An independently locked group of cells; see the `domain` module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LockDomain {
+    domain: Domain,
+}
+
+/// The next domain to hand out; 0 is the default domain.
+static NEXT: AtomicU32 = AtomicU32::new(1);
+
+impl LockDomain {
+    /// Create a new domain, with a lock of its own.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The domain of cells created outside of any `enter`.
+    pub fn global() -> Self {
+        LockDomain {
+            domain: Domain::DEFAULT,
+        }
+    }
+
+    /// The domain that cells created on this thread now will belong
+    /// to.
+    pub fn current() -> Self {
+        LockDomain {
+            domain: lock::current_domain(),
+        }
+    }
+
+    /// Runs `op`, with the cells it creates belonging to this domain.
+    /// Cells it accesses are unaffected: they stay in the domain they
+    /// were created in.
+    pub fn enter<R>(&self, op: impl FnOnce() -> R) -> R {
+        let _restore = Restore(lock::set_current_domain(self.domain));
+        op()
+    }
+}
+
+impl Default for LockDomain {
+    fn default() -> Self {
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        assert!(n != 0, "too many lock domains");
+        LockDomain { domain: Domain(n) }
+    }
+}
+
+/// Restores the previous current domain, even on panic.
+struct Restore(Domain);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        lock::set_current_domain(self.0);
+    }
+}
@@ -0,0 +1,76 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{borrow_mut2, LockError, Mut, MutMap, MutVec};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn separate_domains_lock_independently() {
+    let log: MutVec<u32> = LockDomain::new().enter(MutVec::new);
+    let v = MutVec::from(vec![1, 2, 3]);
+    for x in v.iter() {
+        v.with_element(0, |_| log.push(x));
+    }
+    assert_eq!(log.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+fn same_domain_still_conflicts() {
+    let domain = LockDomain::new();
+    let (a, b) = domain.enter(|| (MutVec::from(vec![1]), MutVec::<u32>::new()));
+    let result = catch_unwind(AssertUnwindSafe(|| a.with_element(0, |x| b.push(*x))));
+    assert!(result.is_err());
+    assert!(b.is_empty());
+}
+
+#[test]
+fn read_in_other_domain_while_write_locked() {
+    let config: MutMap<&str, u32> = LockDomain::new().enter(|| {
+        let config = MutMap::new();
+        config.insert("n", 2);
+        config
+    });
+    let v = MutVec::from(vec![1]);
+    v.with_element_mut(0, |x| *x *= config.get("n").unwrap());
+    assert_eq!(v.into_inner(), vec![2]);
+}
+
+#[test]
+fn enter_sets_and_restores_current() {
+    let domain = LockDomain::new();
+    assert_ne!(domain, LockDomain::new());
+    assert_eq!(LockDomain::current(), LockDomain::global());
+    domain.enter(|| {
+        assert_eq!(LockDomain::current(), domain);
+        LockDomain::global().enter(|| assert_eq!(LockDomain::current(), LockDomain::global()));
+        assert_eq!(LockDomain::current(), domain);
+    });
+    let result = catch_unwind(AssertUnwindSafe(|| domain.enter(|| panic!("inside"))));
+    assert!(result.is_err());
+    assert_eq!(LockDomain::current(), LockDomain::global());
+}
+
+#[test]
+fn borrow_mut2_across_domains() {
+    let a = Mut::new(1);
+    let b = LockDomain::new().enter(|| Mut::new(2));
+    borrow_mut2(&a, &b, std::mem::swap);
+    assert_eq!((a.get(), b.get()), (2, 1));
+
+    // Both domains are locked while `op` runs.
+    let c: Mut<u32> = Mut::new(0);
+    let result = catch_unwind(AssertUnwindSafe(|| borrow_mut2(&a, &b, |_, _| c.get())));
+    assert!(result.is_err());
+    assert_eq!(crate::mcell::lock::state(), vec![]);
+}
+
+#[test]
+fn try_methods_check_their_own_domain() {
+    let other = LockDomain::new().enter(|| Mut::new(1));
+    let v = MutVec::from(vec![1]);
+    v.with_element(0, |_| {
+        assert_eq!(other.set_locked(2), Ok(()));
+        assert_eq!(crate::try_modify(|| ()), Err(LockError::ReadLocked));
+    });
+    assert_eq!(other.get(), 2);
+}
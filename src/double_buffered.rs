@@ -0,0 +1,75 @@
+//! Double buffering for frame-based simulations: the state of the
+//! previous frame can be read while the next frame is being written.
+
+use crate::mcell::MCell;
+use std::fmt;
+use std::rc::Rc;
+
+mod test;
+
+/// Holds two buffers: the *front* buffer, with the previous frame,
+/// is read-only; the *back* buffer, with the next frame, is written.
+/// `flip` publishes the back buffer as the new front and recycles
+/// the old front buffer as the new back.
+///
+/// Reading hands out an `Rc` of the front buffer, so no lock is held
+/// while the reader looks at it -- in particular, the previous frame
+/// can be read from inside `write`.
+pub struct DoubleBufferedMut<T> {
+    front: MCell<Rc<T>>,
+    back: MCell<T>,
+}
+
+impl<T: Default> DoubleBufferedMut<T> {
+    /// Create double buffers that both start out as `value`.
+    pub fn new(value: T) -> Self
+    where
+        T: Clone,
+    {
+        DoubleBufferedMut {
+            front: MCell::new(Rc::new(value.clone())),
+            back: MCell::new(value),
+        }
+    }
+
+    /// The previous frame. This is O(1), and the result is unaffected
+    /// by later flips.
+    pub fn read(&self) -> Rc<T> {
+        self.front.borrow().clone()
+    }
+
+    /// Modify the next frame. While `op` runs, all mut-cells are
+    /// read-only, but it may `read` the previous frame.
+    #[track_caller]
+    pub fn write<R>(&self, op: impl FnOnce(&mut T) -> R) -> R {
+        op(&mut self.back.check_out())
+    }
+
+    /// Make the next frame the previous one. The old front buffer
+    /// becomes the back buffer (so it holds the state of two frames
+    /// ago); it is cloned only if some reader still holds it.
+    pub fn flip(&self)
+    where
+        T: Clone,
+    {
+        let next = Rc::new(self.back.take());
+        let old = self.front.replace(next);
+        self.back
+            .set(Rc::try_unwrap(old).unwrap_or_else(|rc| T::clone(&rc)));
+    }
+}
+
+impl<T: Default + Clone> Default for DoubleBufferedMut<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoubleBufferedMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DoubleBufferedMut")
+            .field("front", &*self.front.borrow())
+            .field("back", &*self.back.borrow())
+            .finish()
+    }
+}
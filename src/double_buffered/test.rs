@@ -0,0 +1,37 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn frames() {
+    let cells = DoubleBufferedMut::new(vec![1, 2, 3]);
+    for frame in 0..2 {
+        let prev = cells.read();
+        cells.write(|next| {
+            for (n, p) in next.iter_mut().zip(cells.read().iter()) {
+                *n = p * 2;
+            }
+        });
+        assert!(Rc::ptr_eq(&prev, &cells.read()));
+        assert_eq!(prev[0], 1 << frame);
+        cells.flip();
+    }
+    assert_eq!(*cells.read(), vec![4, 8, 12]);
+}
+
+#[test]
+fn flip_with_outstanding_reader() {
+    let cells = DoubleBufferedMut::new(0);
+    cells.write(|n| *n = 1);
+    let old = cells.read();
+    cells.flip();
+    assert_eq!(*old, 0);
+    assert_eq!(*cells.read(), 1);
+    cells.write(|n| *n += 10);
+    cells.flip();
+    assert_eq!(*cells.read(), 10);
+    assert_eq!(
+        format!("{:?}", cells),
+        "DoubleBufferedMut { front: 10, back: 1 }"
+    );
+}
@@ -0,0 +1,139 @@
+use crate::mcell::MCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+mod test;
+
+/// A fieldless enum that can key a `MutEnumMap`: its variants are
+/// numbered densely from 0. With the `derive` feature, this can be
+/// derived with `#[derive(EnumKey)]`.
+pub trait EnumKey: Sized {
+    /// The number of variants.
+    const COUNT: usize;
+
+    /// The number of this variant, in `0..COUNT`.
+    fn index(&self) -> usize;
+
+    /// The variant numbered `index`. Panics if `index` is not less
+    /// than `COUNT`.
+    fn from_index(index: usize) -> Self;
+}
+
+/// A map with a value for every variant of the enum `K`, stored
+/// densely (indexed by variant), for per-category counters and
+/// settings.
+pub struct MutEnumMap<K, V> {
+    data: MCell<Vec<V>>,
+    keys: PhantomData<K>,
+}
+
+impl<K: EnumKey, V> MutEnumMap<K, V> {
+    /// A map with the default value for every key.
+    pub fn new() -> Self
+    where
+        V: Default,
+    {
+        Self::default()
+    }
+
+    /// A map with the value `op(key)` for every key.
+    pub fn from_fn(op: impl FnMut(K) -> V) -> Self {
+        MutEnumMap {
+            data: MCell::new((0..K::COUNT).map(K::from_index).map(op).collect()),
+            keys: PhantomData,
+        }
+    }
+
+    /// The number of keys (which is always `K::COUNT`).
+    pub fn len(&self) -> usize {
+        K::COUNT
+    }
+
+    pub fn is_empty(&self) -> bool {
+        K::COUNT == 0
+    }
+
+    pub fn get(&self, key: &K) -> V
+    where
+        V: Clone,
+    {
+        self.data.borrow()[key.index()].clone()
+    }
+
+    pub fn replace(&self, key: &K, value: V) -> V {
+        let mut data = self.data.borrow_mut();
+        std::mem::replace(&mut data[key.index()], value)
+    }
+
+    pub fn set(&self, key: &K, value: V) {
+        self.replace(key, value);
+    }
+
+    /// Modify the value for `key` in place. While `op` runs, all
+    /// mut-cells are read-only, and *this* map is checked out: reading
+    /// it finds no values, so `get` and iteration panic (with an index
+    /// out of bounds) and `Debug` shows an empty map.
+    #[track_caller]
+    pub fn update<R>(&self, key: &K, op: impl FnOnce(&mut V) -> R) -> R {
+        op(&mut self.data.check_out()[key.index()])
+    }
+
+    /// Iterate over every key and its value, in variant order,
+    /// cloning the values as we go.
+    pub fn iter(&self) -> Iter<'_, K, V>
+    where
+        V: Clone,
+    {
+        Iter {
+            map: self,
+            index: 0,
+        }
+    }
+}
+
+impl<K: EnumKey, V: Default> Default for MutEnumMap<K, V> {
+    fn default() -> Self {
+        Self::from_fn(|_| V::default())
+    }
+}
+
+impl<K, V: Clone> Clone for MutEnumMap<K, V> {
+    fn clone(&self) -> Self {
+        MutEnumMap {
+            data: MCell::new(self.data.borrow().clone()),
+            keys: PhantomData,
+        }
+    }
+}
+
+impl<K: EnumKey + fmt::Debug, V: fmt::Debug> fmt::Debug for MutEnumMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_map()
+            .entries(data.iter().enumerate().map(|(i, v)| (K::from_index(i), v)))
+            .finish()
+    }
+}
+
+pub struct Iter<'iter, K, V> {
+    map: &'iter MutEnumMap<K, V>,
+    index: usize,
+}
+
+impl<'iter, K, V> Iterator for Iter<'iter, K, V>
+where
+    K: EnumKey,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        if self.index == K::COUNT {
+            return None;
+        }
+        let key = K::from_index(self.index);
+        self.index += 1;
+        let value = self.map.get(&key);
+        Some((key, value))
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(test)]
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Level {
+    Debug,
+    Info,
+    Error,
+}
+
+impl EnumKey for Level {
+    const COUNT: usize = 3;
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        [Level::Debug, Level::Info, Level::Error][index]
+    }
+}
+
+#[test]
+fn counters() {
+    let counts: MutEnumMap<Level, u32> = MutEnumMap::new();
+    counts.update(&Level::Info, |c| *c += 1);
+    counts.update(&Level::Info, |c| *c += 1);
+    counts.set(&Level::Error, 5);
+    assert_eq!(counts.get(&Level::Info), 2);
+    assert_eq!(counts.replace(&Level::Error, 0), 5);
+    assert_eq!(counts.len(), 3);
+    assert_eq!(
+        counts.iter().collect::<Vec<_>>(),
+        vec![(Level::Debug, 0), (Level::Info, 2), (Level::Error, 0)]
+    );
+    assert_eq!(format!("{:?}", counts), "{Debug: 0, Info: 2, Error: 0}");
+}
+
+#[test]
+fn from_fn() {
+    let names = MutEnumMap::from_fn(|level: Level| format!("{:?}", level).to_lowercase());
+    assert_eq!(names.get(&Level::Error), "error");
+    assert_eq!(names.clone().get(&Level::Debug), "debug");
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn read_during_update() {
+    let counts: MutEnumMap<Level, u32> = MutEnumMap::new();
+    counts.update(&Level::Info, |_| {
+        assert_eq!(format!("{:?}", counts), "{}");
+        counts.get(&Level::Debug)
+    });
+}
@@ -0,0 +1,151 @@
+//! Errors for the `try_` variants of the collections' operations.
+
+use std::fmt;
+
+mod test;
+
+/// An index was out of bounds. See `MutVec::try_at`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IndexError {
+    /// The index that was requested.
+    pub index: usize,
+
+    /// The length of the collection at the time.
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} out of bounds for length {}",
+            self.index, self.len
+        )
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+/// A key was not present. See `MutMap::try_get`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyError<K> {
+    /// The key that was requested.
+    pub key: K,
+}
+
+impl<K: fmt::Debug> fmt::Display for KeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key {:?} not found", self.key)
+    }
+}
+
+impl<K: fmt::Debug> std::error::Error for KeyError<K> {}
+
+/// An insertion would have exceeded a collection's maximum length.
+/// See `MutVec::try_push` and `MutMap::try_insert`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapacityError<T> {
+    /// The rejected element, returned to the caller.
+    pub value: T,
+
+    /// The maximum length of the collection.
+    pub max_len: usize,
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "capacity limit of {} exceeded", self.max_len)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for CapacityError<T> {}
+
+/// The thread lock was not available for an operation. See the
+/// `try_lock` module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LockError {
+    /// The thread holds a read lock (e.g., a borrow is active or a
+    /// collection is checked out), so nothing can be modified.
+    ReadLocked,
+
+    /// The thread holds the write lock (e.g., inside `borrow_mut2`),
+    /// so nothing can be read or modified.
+    WriteLocked,
+
+    /// The thread holds the maximum number of read locks, so no more
+    /// can be acquired.
+    TooManyReaders,
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::ReadLocked => write!(f, "mutable data is locked for reading"),
+            LockError::WriteLocked => write!(f, "mutable data is locked for writing"),
+            LockError::TooManyReaders => write!(f, "too many read locks on mutable data"),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+/// A lock-checked insertion (`MutVec::push_locked` or
+/// `MutMap::insert_locked`) failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InsertError<T> {
+    /// The thread lock was not available; the element was dropped.
+    Locked(LockError),
+
+    /// The insertion would have exceeded the collection's maximum
+    /// length, and the policy is not `EvictOldest`.
+    Capacity(CapacityError<T>),
+}
+
+impl<T> From<LockError> for InsertError<T> {
+    fn from(err: LockError) -> Self {
+        InsertError::Locked(err)
+    }
+}
+
+impl<T> From<CapacityError<T>> for InsertError<T> {
+    fn from(err: CapacityError<T>) -> Self {
+        InsertError::Capacity(err)
+    }
+}
+
+impl<T> fmt::Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::Locked(err) => err.fmt(f),
+            InsertError::Capacity(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for InsertError<T> {}
+
+/// A cell with poisoning enabled was accessed after a panic left it
+/// half-modified. See the `poison` module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Poisoned;
+
+impl fmt::Display for Poisoned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mutable data poisoned by a panic during modification")
+    }
+}
+
+impl std::error::Error for Poisoned {}
+
+/// A lock guard outlived the closure passed to
+/// `catch_unwind_restore`, which returns this as its error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GuardOutlived;
+
+impl fmt::Display for GuardOutlived {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a lock guard outlived `catch_unwind_restore`")
+    }
+}
+
+impl std::error::Error for GuardOutlived {}
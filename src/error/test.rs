@@ -0,0 +1,31 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{MutMap, MutVec};
+
+#[test]
+fn try_at() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    assert_eq!(v.try_at(1), Ok(2));
+    let err = v.try_at(5).unwrap_err();
+    assert_eq!(err, IndexError { index: 5, len: 3 });
+    assert_eq!(err.to_string(), "index 5 out of bounds for length 3");
+}
+
+#[test]
+fn try_get() {
+    let m: MutMap<String, u32> = vec![("a".to_string(), 1)].into_iter().collect();
+    assert_eq!(m.try_get("a"), Ok(1));
+    let err = m.try_get("b").unwrap_err();
+    assert_eq!(err.key, "b");
+    assert_eq!(err.to_string(), r#"key "b" not found"#);
+}
+
+#[test]
+fn through_question_mark() {
+    fn total(v: &MutVec<u32>) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(v.try_at(0)? + v.try_at(1)?)
+    }
+    assert_eq!(total(&MutVec::from(vec![1, 2])).unwrap(), 3);
+    assert!(total(&MutVec::from(vec![1])).is_err());
+}
@@ -0,0 +1,192 @@
+//! The rolling content hash behind `MutMap::fingerprint`.
+//!
+//! The fingerprint of a map is the (wrapping) sum of the hashes of
+//! its entries, so it does not depend on their order and can be
+//! updated as entries come and go. The map's own insert and remove
+//! operations do so; any other mutation (e.g., `update_make_mut`)
+//! leaves the fingerprint stale, to be recomputed from scratch the
+//! next time it is requested. Staleness is detected with the cell's
+//! change revision (see `track`), which every mutation bumps.
+
+use crate::mcell::track;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic::RefUnwindSafe;
+
+mod test;
+
+pub(crate) struct Fingerprint<K, V, S> {
+    state: Cell<Option<State<K, V, S>>>,
+}
+
+/// The fingerprint is a plain value that is replaced wholesale, so a
+/// panic cannot leave it half-updated.
+impl<K, V, S> RefUnwindSafe for Fingerprint<K, V, S> {}
+
+struct State<K, V, S> {
+    hash_key: fn(&K) -> u64,
+    hash_value: fn(&V) -> u64,
+    sum_entries: fn(&S) -> u64,
+
+    /// The id of the cell when `sum` was computed (ids change when a
+    /// cell moves to another thread).
+    id: u64,
+
+    /// The change revision of the cell when `sum` was computed.
+    revision: u64,
+
+    sum: u64,
+}
+
+impl<K, V, S> Clone for State<K, V, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, S> Copy for State<K, V, S> {}
+
+/// A hash that is the same in every run of the program, so that
+/// fingerprints can serve as cache keys.
+pub(crate) fn stable_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The hash of an entry, from the hashes of its key and value.
+fn entry_hash(key_hash: u64, value_hash: u64) -> u64 {
+    stable_hash(&(key_hash, value_hash))
+}
+
+impl<K, V, S> Fingerprint<K, V, S> {
+    pub(crate) fn new() -> Self {
+        Fingerprint {
+            state: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.state.get().is_some()
+    }
+
+    /// Start maintaining the fingerprint of the cell `id`, whose
+    /// contents are `data`.
+    pub(crate) fn enable(&self, id: u64, data: &S)
+    where
+        K: Hash,
+        V: Hash,
+        S: EntrySum<K, V>,
+    {
+        let mut state = State {
+            hash_key: stable_hash::<K>,
+            hash_value: stable_hash::<V>,
+            sum_entries: S::sum_entries,
+            id,
+            revision: 0,
+            sum: 0,
+        };
+        state.sum = (state.sum_entries)(data);
+        state.revision = track::changed_at(id);
+        self.state.set(Some(state));
+    }
+
+    pub(crate) fn disable(&self) {
+        self.state.set(None);
+    }
+
+    /// The fingerprint of the cell `id`, whose contents are `data`;
+    /// recomputed if stale.
+    pub(crate) fn get(&self, id: u64, data: &S) -> Option<u64> {
+        let mut state = self.state.get()?;
+        if state.id != id || state.revision != track::changed_at(id) {
+            state.sum = (state.sum_entries)(data);
+            state.id = id;
+            state.revision = track::changed_at(id);
+            self.state.set(Some(state));
+        }
+        Some(state.sum)
+    }
+
+    /// True if the fingerprint is up to date without recomputation.
+    #[cfg(test)]
+    pub(crate) fn is_current(&self, id: u64) -> bool {
+        self.state
+            .get()
+            .is_some_and(|state| state.id == id && state.revision == track::changed_at(id))
+    }
+
+    /// Begin an update of the fingerprint of the cell `id`, just
+    /// before it is checked out; returns `None` if there is no
+    /// up-to-date fingerprint to update. Finish with `end` once the
+    /// check-out is over.
+    pub(crate) fn begin(&self, id: Option<u64>) -> Option<Delta<K, V, S>> {
+        let state = self.state.get()?;
+        if Some(state.id) != id || state.revision != track::changed_at(state.id) {
+            return None;
+        }
+        Some(Delta {
+            state,
+            // The revision that the check-out will stamp the cell
+            // with, as no other cell can change in the meantime.
+            expected: track::current_revision() + 1,
+        })
+    }
+
+    /// Record the update, unless the cell has (also) been changed in
+    /// some other way since `begin`.
+    pub(crate) fn end(&self, delta: Option<Delta<K, V, S>>) {
+        let delta = match delta {
+            Some(delta) => delta,
+            None => return,
+        };
+        if track::changed_at(delta.state.id) == delta.expected {
+            let state = State {
+                revision: delta.expected,
+                ..delta.state
+            };
+            self.state.set(Some(state));
+        }
+    }
+}
+
+pub(crate) struct Delta<K, V, S> {
+    state: State<K, V, S>,
+    expected: u64,
+}
+
+impl<K, V, S> Delta<K, V, S> {
+    pub(crate) fn hash_key(&self, key: &K) -> u64 {
+        (self.state.hash_key)(key)
+    }
+
+    /// Account for an entry with key hash `key_hash` being added.
+    pub(crate) fn add(&mut self, key_hash: u64, value: &V) {
+        let hash = entry_hash(key_hash, (self.state.hash_value)(value));
+        self.state.sum = self.state.sum.wrapping_add(hash);
+    }
+
+    /// Account for an entry with key hash `key_hash` being removed.
+    pub(crate) fn sub(&mut self, key_hash: u64, value: &V) {
+        let hash = entry_hash(key_hash, (self.state.hash_value)(value));
+        self.state.sum = self.state.sum.wrapping_sub(hash);
+    }
+
+    pub(crate) fn sub_entry(&mut self, key: &K, value: &V) {
+        self.sub(self.hash_key(key), value);
+    }
+}
+
+/// A store whose entries can be summed, for `Fingerprint::enable`.
+pub(crate) trait EntrySum<K, V> {
+    fn sum_entries(&self) -> u64;
+}
+
+impl<K: Hash, V: Hash> EntrySum<K, V> for indexmap::IndexMap<K, V> {
+    fn sum_entries(&self) -> u64 {
+        self.iter().fold(0u64, |sum, (k, v)| {
+            sum.wrapping_add(entry_hash(stable_hash(k), stable_hash(v)))
+        })
+    }
+}
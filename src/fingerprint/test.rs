@@ -0,0 +1,83 @@
+#![cfg(test)]
+
+use super::*;
+use crate::diff::MapDiff;
+use crate::MutMap;
+use std::sync::Arc;
+
+#[test]
+fn disabled_by_default() {
+    let m: MutMap<u32, u32> = MutMap::new();
+    assert_eq!(m.fingerprint(), None);
+    m.enable_fingerprint();
+    assert!(m.fingerprint().is_some());
+    m.disable_fingerprint();
+    assert_eq!(m.fingerprint(), None);
+}
+
+#[test]
+fn independent_of_order() {
+    let a = MutMap::new();
+    a.enable_fingerprint();
+    a.insert("x", 1);
+    a.insert("y", 2);
+
+    let mut b = MutMap::new();
+    b.insert("z", 3);
+    b.insert("y", 2);
+    b.insert("x", 1);
+    b.enable_fingerprint();
+    assert_ne!(a.fingerprint(), b.fingerprint());
+    b.remove("z");
+    assert_eq!(a.fingerprint(), b.fingerprint());
+
+    // Swapping the values of two keys changes the fingerprint.
+    b.insert("x", 2);
+    b.insert("y", 1);
+    assert_ne!(a.fingerprint(), b.fingerprint());
+}
+
+#[test]
+fn incremental_matches_recomputed() {
+    let m = MutMap::new();
+    m.enable_fingerprint();
+    for i in 0..10 {
+        m.insert(i, i * 2);
+    }
+    m.insert(3, 0);
+    m.remove_index_range(0..2);
+    m.apply(MapDiff {
+        added: vec![(20, 1)],
+        removed: vec![4],
+        changed: vec![(5, 5)],
+    });
+
+    assert!(m.fingerprint.is_current(m.data.id()));
+
+    let fresh: MutMap<u32, u32> = m.clone();
+    fresh.enable_fingerprint();
+    assert_eq!(m.fingerprint(), fresh.fingerprint());
+}
+
+#[test]
+fn recomputed_after_other_mutations() {
+    let m = MutMap::new();
+    m.insert("a", Arc::new(vec![1]));
+    m.enable_fingerprint();
+    let before = m.fingerprint();
+
+    m.update_make_mut("a", |v| v.push(2));
+    assert!(!m.fingerprint.is_current(m.data.id()));
+    assert_ne!(m.fingerprint(), before);
+    assert!(m.fingerprint.is_current(m.data.id()));
+
+    let fresh = m.clone();
+    fresh.enable_fingerprint();
+    assert_eq!(m.fingerprint(), fresh.fingerprint());
+}
+
+#[test]
+fn stable_hash_is_deterministic() {
+    assert_eq!(stable_hash("abc"), stable_hash("abc"));
+    assert_ne!(stable_hash(&1u32), stable_hash(&2u32));
+}
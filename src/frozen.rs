@@ -0,0 +1,116 @@
+//! Immutable snapshots of the crate's collections. A common lifecycle
+//! is to build up shared state on one thread and then share it,
+//! read-only, across threads. `MutVec::freeze` and `MutMap::freeze`
+//! express that transition: they consume the collection (without
+//! copying its contents) and yield a `Send + Sync` value that needs
+//! no locking to read. Cloning a frozen collection is O(1).
+
+use crate::map::MapStore;
+use crate::{MutMap, MutVec};
+use indexmap::IndexMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
+
+mod test;
+
+pub struct FrozenVec<T> {
+    data: Arc<Vec<T>>,
+}
+
+impl<T> FrozenVec<T> {
+    /// Convert back into a `MutVec`. The contents are cloned only if
+    /// other copies of this `FrozenVec` are alive.
+    pub fn thaw(self) -> MutVec<T>
+    where
+        T: Clone,
+    {
+        MutVec::from(Arc::unwrap_or_clone(self.data))
+    }
+}
+
+impl<T> Deref for FrozenVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T> Clone for FrozenVec<T> {
+    fn clone(&self) -> Self {
+        FrozenVec {
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FrozenVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
+pub struct FrozenMap<K, V, S = IndexMap<K, V>> {
+    data: Arc<S>,
+    entries: PhantomData<(K, V)>,
+}
+
+impl<K, V, S> FrozenMap<K, V, S> {
+    /// Convert back into a `MutMap`. The contents are cloned only if
+    /// other copies of this `FrozenMap` are alive.
+    pub fn thaw(self) -> MutMap<K, V, S>
+    where
+        S: MapStore<K, V> + Clone,
+    {
+        MutMap::from(Arc::unwrap_or_clone(self.data))
+    }
+}
+
+impl<K, V, S> Deref for FrozenMap<K, V, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.data
+    }
+}
+
+impl<K, V, S> Clone for FrozenMap<K, V, S> {
+    fn clone(&self) -> Self {
+        FrozenMap {
+            data: self.data.clone(),
+            entries: PhantomData,
+        }
+    }
+}
+
+impl<K, V, S: fmt::Debug> fmt::Debug for FrozenMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
+impl<T> MutVec<T> {
+    /// Consume `self`, producing an immutable `FrozenVec` that can be
+    /// shared across threads.
+    pub fn freeze(self) -> FrozenVec<T> {
+        FrozenVec {
+            data: Arc::new(self.into_inner()),
+        }
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    /// Consume `self`, producing an immutable `FrozenMap` that can be
+    /// shared across threads.
+    pub fn freeze(self) -> FrozenMap<K, V, S> {
+        FrozenMap {
+            data: Arc::new(self.into_inner()),
+            entries: PhantomData,
+        }
+    }
+}
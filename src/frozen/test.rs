@@ -0,0 +1,32 @@
+#![cfg(test)]
+
+use super::*;
+use std::thread;
+
+#[test]
+fn share_across_threads() {
+    let v: MutVec<u32> = (0..10).collect();
+    let frozen = v.freeze();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let frozen = frozen.clone();
+            thread::spawn(move || frozen.iter().sum::<u32>())
+        })
+        .collect();
+    for h in handles {
+        assert_eq!(h.join().unwrap(), 45);
+    }
+    let v = frozen.thaw();
+    v.push(10);
+    assert_eq!(v.len(), 11);
+}
+
+#[test]
+fn freeze_map() {
+    let m: MutMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    let frozen = m.freeze();
+    let copy = frozen.clone();
+    assert_eq!(thread::spawn(move || copy["b"]).join().unwrap(), 2);
+    assert_eq!(frozen.get_index(0), Some((&"a", &1)));
+    assert_eq!(frozen.thaw().get("a"), Some(1));
+}
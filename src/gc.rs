@@ -0,0 +1,173 @@
+//! Cycle collection for graphs of reference-counted cells. Nodes
+//! allocated from a `GcHeap` are `MutRc`-based, so cycles among them
+//! would normally leak; `GcHeap::collect` finds the nodes that are
+//! only reachable from other nodes of the heap (never from outside
+//! it) and breaks their cycles by dropping their values.
+//!
+//! Collection uses trial deletion: for each node, the references held
+//! by other nodes (found via `Trace`) are subtracted from its strong
+//! count. Nodes with references left over are reachable from outside
+//! the heap; everything they reach is live, and the rest is garbage.
+
+use crate::mcell::MCell;
+use crate::mutbl::Mut;
+use crate::rc::MutRc;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+mod test;
+
+/// Types that can report the `GcRc` references they hold.
+pub trait Trace: Sized {
+    /// Invoke `visit` on each `GcRc` directly held by `self`.
+    fn trace(&self, visit: &mut dyn FnMut(&GcRc<Self>));
+}
+
+/// A node of a `GcHeap`.
+pub struct GcRc<T> {
+    node: MutRc<Option<T>>,
+}
+
+/// Tracks the nodes allocated from it, so that they can be collected.
+pub struct GcHeap<T> {
+    nodes: MCell<Vec<Weak<Mut<Option<T>>>>>,
+}
+
+impl<T> GcRc<T> {
+    /// Runs `op` with a reference to the value of this node. All
+    /// mut-cells are read-only while `op` runs.
+    #[track_caller]
+    pub fn with<R>(&self, op: impl FnOnce(&T) -> R) -> R {
+        let data = self.node.rc.data.borrow();
+        op(data.as_ref().expect("collected node"))
+    }
+
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    pub fn set(&self, new_value: T) {
+        self.node.set(Some(new_value))
+    }
+
+    pub fn replace(&self, new_value: T) -> T {
+        self.node.replace(Some(new_value)).expect("collected node")
+    }
+
+    /// True if `a` and `b` refer to the same node.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        MutRc::ptr_eq(&a.node, &b.node)
+    }
+
+    fn addr(&self) -> usize {
+        Rc::as_ptr(&self.node.rc) as usize
+    }
+}
+
+impl<T: Trace> GcHeap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate a new node holding `value`.
+    pub fn alloc(&self, value: T) -> GcRc<T> {
+        let node = MutRc::new(Some(value));
+        self.nodes.check_out().push(Rc::downgrade(&node.rc));
+        GcRc { node }
+    }
+
+    /// The number of nodes allocated from this heap that are still
+    /// alive.
+    pub fn len(&self) -> usize {
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|w| w.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Collect the nodes that are unreachable from outside the heap,
+    /// returning how many there were.
+    pub fn collect(&self) -> usize {
+        let nodes: Vec<GcRc<T>> = {
+            let mut weaks = self.nodes.check_out();
+            weaks.retain(|w| w.strong_count() > 0);
+            weaks
+                .iter()
+                .filter_map(|w| {
+                    let rc = w.upgrade()?;
+                    Some(GcRc { node: MutRc { rc } })
+                })
+                .collect()
+        };
+
+        // Count the references that nodes hold to one another.
+        let mut internal: HashMap<usize, usize> = HashMap::new();
+        for node in &nodes {
+            let data = node.node.rc.data.borrow();
+            if let Some(value) = &*data {
+                value.trace(&mut |child| *internal.entry(child.addr()).or_insert(0) += 1);
+            }
+        }
+
+        // Nodes with other references (beyond the one in `nodes`) are
+        // roots; mark everything reachable from them.
+        let mut live = HashSet::new();
+        let mut stack: Vec<GcRc<T>> = nodes
+            .iter()
+            .filter(|n| {
+                let internal = internal.get(&n.addr()).copied().unwrap_or(0);
+                MutRc::strong_count(&n.node) - 1 > internal
+            })
+            .cloned()
+            .collect();
+        while let Some(node) = stack.pop() {
+            if !live.insert(node.addr()) {
+                continue;
+            }
+            let data = node.node.rc.data.borrow();
+            if let Some(value) = &*data {
+                value.trace(&mut |child| stack.push(child.clone()));
+            }
+        }
+
+        // Break the cycles among the rest. The values are dropped only
+        // once all of them are taken, outside of any lock.
+        let garbage: Vec<Option<T>> = nodes
+            .iter()
+            .filter(|n| !live.contains(&n.addr()))
+            .map(|n| n.node.replace(None))
+            .collect();
+        garbage.len()
+    }
+}
+
+impl<T> Clone for GcRc<T> {
+    fn clone(&self) -> Self {
+        GcRc {
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<T> Default for GcHeap<T> {
+    fn default() -> Self {
+        GcHeap {
+            nodes: MCell::new(vec![]),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for GcRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|value| f.debug_tuple("GcRc").field(value).finish())
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use super::*;
+
+struct Node {
+    name: &'static str,
+    edges: Vec<GcRc<Node>>,
+}
+
+impl Trace for Node {
+    fn trace(&self, visit: &mut dyn FnMut(&GcRc<Node>)) {
+        self.edges.iter().for_each(visit);
+    }
+}
+
+fn node(heap: &GcHeap<Node>, name: &'static str) -> GcRc<Node> {
+    heap.alloc(Node {
+        name,
+        edges: vec![],
+    })
+}
+
+fn link(from: &GcRc<Node>, to: &GcRc<Node>) {
+    let mut value = from.replace(Node {
+        name: "",
+        edges: vec![],
+    });
+    value.edges.push(to.clone());
+    from.set(value);
+}
+
+#[test]
+fn collects_cycle() {
+    let heap = GcHeap::new();
+    let a = node(&heap, "a");
+    let b = node(&heap, "b");
+    link(&a, &b);
+    link(&b, &a);
+    link(&a, &a);
+
+    assert_eq!(heap.collect(), 0);
+    drop(b);
+    assert_eq!(heap.collect(), 0);
+    assert_eq!(heap.len(), 2);
+
+    drop(a);
+    assert_eq!(heap.len(), 2);
+    assert_eq!(heap.collect(), 2);
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn keeps_reachable() {
+    let heap = GcHeap::new();
+    let a = node(&heap, "a");
+    let b = node(&heap, "b");
+    let c = node(&heap, "c");
+    link(&a, &b);
+    link(&b, &c);
+    link(&c, &b);
+    drop((b, c));
+
+    assert_eq!(heap.collect(), 0);
+    a.with(|a| a.edges[0].with(|b| assert_eq!(b.edges[0].with(|c| c.name), "c")));
+
+    a.set(Node {
+        name: "a",
+        edges: vec![],
+    });
+    assert_eq!(heap.collect(), 2);
+    assert_eq!(heap.len(), 1);
+}
@@ -0,0 +1,141 @@
+//! Branded cells, checked at compile time. An alternative to the
+//! dynamic thread lock for code that can pass a token around: a
+//! `GhostCell` can only be accessed through the `GhostToken` of its
+//! brand, shared (`&GhostToken`) to read and unique (`&mut
+//! GhostToken`) to write. So the borrow checker, rather than the
+//! thread lock, rules out modifying data while it is being read, and
+//! accesses cost nothing at runtime.
+//!
+//! ```
+//! use mutable::ghost::{GhostCell, GhostToken};
+//!
+//! GhostToken::scope(|mut token| {
+//!     let names = GhostCell::new(vec![String::from("a")]);
+//!     let lengths = GhostCell::new(Vec::new());
+//!     for name in names.borrow(&token).clone() {
+//!         lengths.borrow_mut(&mut token).push(name.len());
+//!     }
+//!     assert_eq!(lengths.into_inner(), vec![1]);
+//! });
+//! ```
+//!
+//! Each token has a brand (a lifetime) of its own, so the cells of
+//! one token cannot be accessed with another:
+//!
+//! ```compile_fail
+//! use mutable::ghost::{GhostCell, GhostToken};
+//!
+//! GhostToken::scope(|token1| {
+//!     GhostToken::scope(|token2| {
+//!         let cell = GhostCell::new(0);
+//!         let _ = cell.borrow(&token1);
+//!         let _ = cell.borrow(&token2);
+//!     });
+//! });
+//! ```
+//!
+//! The cells are not tracked otherwise either: there is no
+//! `subscribe`, dirty tracking, or poisoning.
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+mod test;
+
+/// An invariant lifetime, identifying a brand.
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/// The key to the `GhostCell`s of brand `'brand`. There is only one
+/// token per brand.
+pub struct GhostToken<'brand> {
+    brand: Brand<'brand>,
+}
+
+impl GhostToken<'_> {
+    /// Runs `op` with the token of a new brand. The brand cannot
+    /// escape `op`, and neither can the cells created with it.
+    pub fn scope<R>(op: impl for<'brand> FnOnce(GhostToken<'brand>) -> R) -> R {
+        op(GhostToken { brand: PhantomData })
+    }
+}
+
+impl fmt::Debug for GhostToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GhostToken")
+    }
+}
+
+/// A cell of brand `'brand`, accessed through the `GhostToken` of
+/// that brand.
+pub struct GhostCell<'brand, T: ?Sized> {
+    brand: Brand<'brand>,
+    value: UnsafeCell<T>,
+}
+
+// Safe: the cell is accessed only through the token, which is the
+// one thing that must be shared (or sent) between threads to access
+// it. So, as with `RwLock`, the cell can be shared if `T` can be sent
+// (for writing) and shared (for reading).
+unsafe impl<T: ?Sized + Send + Sync> Sync for GhostCell<'_, T> {}
+
+impl<'brand, T> GhostCell<'brand, T> {
+    pub fn new(value: T) -> Self {
+        GhostCell {
+            brand: PhantomData,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Take ownership of the value, consuming the cell.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn replace(&self, token: &mut GhostToken<'brand>, value: T) -> T {
+        std::mem::replace(self.borrow_mut(token), value)
+    }
+}
+
+impl<'brand, T: ?Sized> GhostCell<'brand, T> {
+    /// Shared access to the value, for as long as the token is
+    /// borrowed.
+    pub fn borrow<'a>(&'a self, token: &'a GhostToken<'brand>) -> &'a T {
+        let _ = token;
+        // Safe: mutable references are only handed out while the
+        // token is borrowed mutably, which cannot overlap with the
+        // borrow of `token`.
+        unsafe { &*self.value.get() }
+    }
+
+    /// Unique access to the value, for as long as the token is
+    /// borrowed.
+    pub fn borrow_mut<'a>(&'a self, token: &'a mut GhostToken<'brand>) -> &'a mut T {
+        let _ = token;
+        // Safe: the token is borrowed mutably for `'a`, so no other
+        // reference to the value of any cell of the brand can be
+        // handed out in the meantime.
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Unique access to the value, through a unique reference to the
+    /// cell; no token is needed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for GhostCell<'_, T> {
+    fn default() -> Self {
+        GhostCell::new(T::default())
+    }
+}
+
+/// The value can only be shown with the token, so this shows the
+/// type alone.
+impl<T: ?Sized> fmt::Debug for GhostCell<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GhostCell").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,51 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn read_and_write() {
+    GhostToken::scope(|mut token| {
+        let v = GhostCell::new(vec![1, 2]);
+        let total = GhostCell::new(0);
+        for x in v.borrow(&token).clone() {
+            *total.borrow_mut(&mut token) += x;
+        }
+        v.borrow_mut(&mut token).push(3);
+        assert_eq!(v.borrow(&token).len(), 3);
+        assert_eq!(total.replace(&mut token, 10), 3);
+        assert_eq!(total.into_inner(), 10);
+    });
+}
+
+#[test]
+fn shared_cells() {
+    use std::rc::Rc;
+
+    GhostToken::scope(|mut token| {
+        let a = Rc::new(GhostCell::new(String::from("a")));
+        let b = a.clone();
+        b.borrow_mut(&mut token).push('b');
+        assert_eq!(a.borrow(&token), "ab");
+    });
+}
+
+#[test]
+fn get_mut_needs_no_token() {
+    let mut cell: GhostCell<'_, u32> = GhostCell::default();
+    *cell.get_mut() = 5;
+    assert_eq!(cell.into_inner(), 5);
+    assert_eq!(format!("{:?}", GhostCell::new(1)), "GhostCell { .. }");
+}
+
+#[test]
+fn threads_share_the_cells() {
+    GhostToken::scope(|mut token| {
+        let cell = GhostCell::new(vec![1]);
+        cell.borrow_mut(&mut token).push(2);
+        std::thread::scope(|scope| {
+            let (cell, token) = (&cell, &token);
+            scope.spawn(move || assert_eq!(cell.borrow(token), &[1, 2]));
+            scope.spawn(move || assert_eq!(cell.borrow(token).len(), 2));
+        });
+    });
+}
@@ -0,0 +1,226 @@
+//! A directed graph of mutable data.
+
+use crate::mcell::MCell;
+use std::collections::VecDeque;
+use std::fmt;
+
+mod test;
+
+/// Identifies a node of a `MutGraph`. Nodes are never removed, so an
+/// index stays valid for the life of its graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIndex(usize);
+
+/// A directed graph whose nodes each hold an `N`. The node values and
+/// the adjacency lists live in separate cells, but both are covered by
+/// the same thread lock (see the `domain` module): as with any two
+/// cells, neither can be modified while the other is borrowed, so,
+/// e.g., calling `add_edge` from within `with` panics. The traversals
+/// (`bfs`, `dfs`) only borrow the graph for each step, so it can be
+/// edited while it is being walked.
+///
+/// The methods that take a `NodeIndex` panic if it does not refer to
+/// a node of the graph.
+pub struct MutGraph<N> {
+    nodes: MCell<Vec<N>>,
+
+    /// The successors of each node, in the order the edges were
+    /// added; indexed like `nodes`.
+    edges: MCell<Vec<Vec<NodeIndex>>>,
+}
+
+impl<N> MutGraph<N> {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.edges.borrow().len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.borrow().iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node_count() == 0
+    }
+
+    pub fn add_node(&self, value: N) -> NodeIndex {
+        self.nodes.borrow_mut().push(value);
+        let mut edges = self.edges.borrow_mut();
+        edges.push(vec![]);
+        NodeIndex(edges.len() - 1)
+    }
+
+    /// Add an edge from `from` to `to`. Parallel edges are allowed.
+    pub fn add_edge(&self, from: NodeIndex, to: NodeIndex) {
+        let mut edges = self.edges.borrow_mut();
+        assert!(to.0 < edges.len(), "invalid node index");
+        edges[from.0].push(to);
+    }
+
+    /// Remove one edge from `from` to `to`, returning whether there
+    /// was one.
+    pub fn remove_edge(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        let mut edges = self.edges.borrow_mut();
+        let successors = &mut edges[from.0];
+        match successors.iter().position(|&n| n == to) {
+            Some(position) => {
+                successors.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains_edge(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        self.edges.borrow()[from.0].contains(&to)
+    }
+
+    /// The successors of `node`, in the order the edges were added.
+    pub fn neighbors(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        self.edges.borrow()[node.0].clone()
+    }
+
+    /// Load (a clone of) the value of `node`.
+    pub fn get(&self, node: NodeIndex) -> N
+    where
+        N: Clone,
+    {
+        self.nodes.borrow()[node.0].clone()
+    }
+
+    /// Store `value` in `node`, returning the old value.
+    pub fn replace(&self, node: NodeIndex, value: N) -> N {
+        std::mem::replace(&mut self.nodes.borrow_mut()[node.0], value)
+    }
+
+    /// Runs `op` with a reference to the value of `node`. All
+    /// mut-cells are read-only while `op` runs.
+    #[track_caller]
+    pub fn with<R>(&self, node: NodeIndex, op: impl FnOnce(&N) -> R) -> R {
+        op(&self.nodes.borrow()[node.0])
+    }
+
+    /// Iterate over the nodes reachable from `start` (including
+    /// `start`), in breadth-first order. The successors of each node
+    /// are read when the iteration reaches it, so edges added during
+    /// the iteration are followed if their source has not been
+    /// visited yet.
+    pub fn bfs(&self, start: NodeIndex) -> Bfs<'_, N> {
+        let mut visited = Visited::default();
+        visited.insert(self.checked(start));
+        Bfs {
+            graph: self,
+            queue: VecDeque::from(vec![start]),
+            visited,
+        }
+    }
+
+    /// Iterate over the nodes reachable from `start` (including
+    /// `start`), in depth-first pre-order; see `bfs` for how edits
+    /// during the iteration are handled.
+    pub fn dfs(&self, start: NodeIndex) -> Dfs<'_, N> {
+        Dfs {
+            graph: self,
+            stack: vec![self.checked(start)],
+            visited: Visited::default(),
+        }
+    }
+
+    fn checked(&self, node: NodeIndex) -> NodeIndex {
+        assert!(node.0 < self.node_count(), "invalid node index");
+        node
+    }
+}
+
+impl<N> Default for MutGraph<N> {
+    #[track_caller]
+    fn default() -> Self {
+        MutGraph {
+            nodes: MCell::new(Vec::new()),
+            edges: MCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<N: fmt::Debug> fmt::Debug for MutGraph<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = self.nodes.borrow();
+        let edges = self.edges.borrow();
+        f.debug_map()
+            .entries(
+                nodes
+                    .iter()
+                    .zip(edges.iter())
+                    .enumerate()
+                    .map(|(i, (value, successors))| ((NodeIndex(i), value), successors)),
+            )
+            .finish()
+    }
+}
+
+/// The nodes a traversal has reached so far.
+#[derive(Default)]
+struct Visited(Vec<bool>);
+
+impl Visited {
+    /// Mark `node`, returning whether it was not marked already.
+    fn insert(&mut self, node: NodeIndex) -> bool {
+        if node.0 >= self.0.len() {
+            self.0.resize(node.0 + 1, false);
+        }
+        !std::mem::replace(&mut self.0[node.0], true)
+    }
+
+    fn contains(&self, node: NodeIndex) -> bool {
+        self.0.get(node.0).copied().unwrap_or(false)
+    }
+}
+
+pub struct Bfs<'iter, N> {
+    graph: &'iter MutGraph<N>,
+    queue: VecDeque<NodeIndex>,
+    visited: Visited,
+}
+
+impl<'iter, N> Iterator for Bfs<'iter, N> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<NodeIndex> {
+        let node = self.queue.pop_front()?;
+        let edges = self.graph.edges.borrow();
+        for &successor in &edges[node.0] {
+            if self.visited.insert(successor) {
+                self.queue.push_back(successor);
+            }
+        }
+        Some(node)
+    }
+}
+
+pub struct Dfs<'iter, N> {
+    graph: &'iter MutGraph<N>,
+    stack: Vec<NodeIndex>,
+    visited: Visited,
+}
+
+impl<'iter, N> Iterator for Dfs<'iter, N> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<NodeIndex> {
+        let edges = self.graph.edges.borrow();
+        while let Some(node) = self.stack.pop() {
+            if self.visited.insert(node) {
+                let visited = &self.visited;
+                let successors = edges[node.0].iter().rev();
+                self.stack
+                    .extend(successors.filter(|&&n| !visited.contains(n)));
+                return Some(node);
+            }
+        }
+        None
+    }
+}
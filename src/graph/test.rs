@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use super::*;
+
+/// a -> b -> d, a -> c -> d, d -> a
+fn sample() -> (MutGraph<char>, [NodeIndex; 4]) {
+    let graph = MutGraph::new();
+    let [a, b, c, d] = ['a', 'b', 'c', 'd'].map(|v| graph.add_node(v));
+    graph.add_edge(a, b);
+    graph.add_edge(a, c);
+    graph.add_edge(b, d);
+    graph.add_edge(c, d);
+    graph.add_edge(d, a);
+    (graph, [a, b, c, d])
+}
+
+#[test]
+fn structure() {
+    let (graph, [a, b, c, d]) = sample();
+    assert_eq!((graph.node_count(), graph.edge_count()), (4, 5));
+    assert_eq!(graph.neighbors(a), vec![b, c]);
+    assert!(graph.contains_edge(d, a));
+    assert!(graph.remove_edge(d, a));
+    assert!(!graph.remove_edge(d, a));
+    assert_eq!(graph.neighbors(d), vec![]);
+    assert_eq!(graph.replace(c, 'C'), 'c');
+    assert_eq!(graph.get(c), 'C');
+}
+
+#[test]
+fn traversals() {
+    let (graph, [a, b, c, d]) = sample();
+    assert_eq!(graph.bfs(a).collect::<Vec<_>>(), vec![a, b, c, d]);
+    assert_eq!(graph.dfs(a).collect::<Vec<_>>(), vec![a, b, d, c]);
+    assert_eq!(graph.dfs(c).collect::<Vec<_>>(), vec![c, d, a, b]);
+}
+
+#[test]
+fn edit_while_walking() {
+    let (graph, [a, b, _, _]) = sample();
+    let mut seen = vec![];
+    for node in graph.bfs(a) {
+        if node == b {
+            let e = graph.add_node('e');
+            graph.add_edge(b, e);
+        }
+        seen.push(graph.get(node));
+    }
+    // `b`'s successors were read before the edge was added.
+    assert_eq!(seen, vec!['a', 'b', 'c', 'd']);
+
+    let seen: Vec<char> = graph.dfs(a).map(|n| graph.get(n)).collect();
+    assert_eq!(seen, vec!['a', 'b', 'd', 'e', 'c']);
+}
+
+#[test]
+#[should_panic(expected = "lock already held")]
+fn add_edge_while_reading_a_node() {
+    let (graph, [a, b, _, _]) = sample();
+    graph.with(a, |_| graph.add_edge(a, b));
+}
@@ -0,0 +1,150 @@
+use crate::mcell::MCell;
+
+mod test;
+
+/// A fixed-size, two-dimensional grid of values stored in row-major
+/// order.
+pub struct MutGrid<T> {
+    data: MCell<Vec<T>>,
+    width: usize,
+    height: usize,
+}
+
+/// Offsets of the eight cells surrounding a given cell, in row-major
+/// order.
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T> MutGrid<T> {
+    /// Create a `width` x `height` grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        MutGrid {
+            data: MCell::new(vec![fill; width * height]),
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Load the value at `(x, y)`, returning `None` if it is out of
+    /// bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let offset = self.offset(x, y)?;
+        Some(self.data.borrow()[offset].clone())
+    }
+
+    /// The equivalent of `self[(x, y)]` -- load the value at the given
+    /// coordinates, panicking if they are out of bounds.
+    pub fn at(&self, x: usize, y: usize) -> T
+    where
+        T: Clone,
+    {
+        self.get(x, y).unwrap()
+    }
+
+    /// Store `value` at `(x, y)`, returning the old value. Panics if
+    /// the coordinates are out of bounds.
+    pub fn replace(&self, x: usize, y: usize, value: T) -> T {
+        let offset = match self.offset(x, y) {
+            Some(offset) => offset,
+            None => panic!(
+                "({}, {}) is out of bounds for a {}x{} grid",
+                x, y, self.width, self.height
+            ),
+        };
+        std::mem::replace(&mut self.data.borrow_mut()[offset], value)
+    }
+
+    /// Store `value` at `(x, y)`. Panics if the coordinates are out of
+    /// bounds.
+    pub fn set(&self, x: usize, y: usize, value: T) {
+        self.replace(x, y, value);
+    }
+
+    /// Clone out row `y`. Panics if `y` is out of bounds.
+    pub fn row(&self, y: usize) -> Vec<T>
+    where
+        T: Clone,
+    {
+        assert!(y < self.height, "row {} is out of bounds", y);
+        let start = y * self.width;
+        self.data.borrow()[start..start + self.width].to_vec()
+    }
+
+    /// Iterate over the (up to eight) in-bounds cells surrounding
+    /// `(x, y)`, yielding their coordinates and (cloned) values.
+    ///
+    /// Values are loaded as the iteration proceeds, so updates made
+    /// to the grid during iteration are visible to it. When computing
+    /// the next generation of a cellular automaton, write into a
+    /// separate grid instead.
+    pub fn neighbors(&self, x: usize, y: usize) -> Neighbors<'_, T>
+    where
+        T: Clone,
+    {
+        Neighbors {
+            grid: self,
+            x,
+            y,
+            index: 0,
+        }
+    }
+}
+
+pub struct Neighbors<'iter, T>
+where
+    T: Clone,
+{
+    grid: &'iter MutGrid<T>,
+    x: usize,
+    y: usize,
+    index: usize,
+}
+
+impl<'iter, T> Iterator for Neighbors<'iter, T>
+where
+    T: Clone,
+{
+    type Item = ((usize, usize), T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(dx, dy)) = NEIGHBOR_OFFSETS.get(self.index) {
+            self.index += 1;
+            let x = self.x.wrapping_add(dx as usize);
+            let y = self.y.wrapping_add(dy as usize);
+            if let Some(value) = self.grid.get(x, y) {
+                return Some(((x, y), value));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,30 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn get_set_row() {
+    let g = MutGrid::new(3, 2, 0);
+    g.set(2, 1, 5);
+    assert_eq!(g.replace(0, 0, 1), 0);
+    assert_eq!(g.get(2, 1), Some(5));
+    assert_eq!(g.get(3, 0), None);
+    assert_eq!(g.row(0), vec![1, 0, 0]);
+    assert_eq!(g.row(1), vec![0, 0, 5]);
+}
+
+#[test]
+fn neighbors() {
+    let g = MutGrid::new(3, 3, 0);
+    for y in 0..3 {
+        for x in 0..3 {
+            g.set(x, y, y * 3 + x);
+        }
+    }
+
+    let corner: Vec<_> = g.neighbors(0, 0).collect();
+    assert_eq!(corner, vec![((1, 0), 1), ((0, 1), 3), ((1, 1), 4)]);
+
+    let center: usize = g.neighbors(1, 1).map(|(_, v)| v).sum();
+    assert_eq!(center, (0..9).sum::<usize>() - 4);
+}
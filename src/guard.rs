@@ -0,0 +1,90 @@
+//! Guards giving references into the crate's collections.
+
+use crate::mcell::{lock, ShareGuard};
+use crate::multi::Lockable;
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+mod test;
+
+/// A reference to an element of a collection (e.g., from
+/// `MutVec::get_ref`), for reading it without cloning it. So long as
+/// the guard exists, the thread holds a read lock: all mut-cells are
+/// read-only.
+pub struct ElementGuard<'a, T> {
+    guard: ShareGuard<'a, T>,
+}
+
+impl<'a, T> ElementGuard<'a, T> {
+    pub(crate) fn new(guard: ShareGuard<'a, T>) -> Self {
+        ElementGuard { guard }
+    }
+}
+
+impl<T> Deref for ElementGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ElementGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for ElementGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+/// A read borrow of a shared cell that owns its `Rc`, so it has no
+/// lifetime and can be stored in a struct or returned from a function.
+/// It derefs to the contents (`T` for a `Mut<T>`, `Vec<T>` for a
+/// `MutVec<T>`, or the store of a `MutMap`). As with any borrow, all
+/// mut-cells are read-only so long as the guard exists.
+pub struct OwnedShareGuard<C: Lockable> {
+    owner: Rc<C>,
+}
+
+impl<C: Lockable> OwnedShareGuard<C> {
+    pub fn new(owner: Rc<C>) -> Self {
+        owner.mcell().0.acquire_shared();
+        OwnedShareGuard { owner }
+    }
+
+    /// The cell this guard borrows.
+    pub fn owner(this: &Self) -> &Rc<C> {
+        &this.owner
+    }
+}
+
+impl<C: Lockable> Deref for OwnedShareGuard<C> {
+    type Target = C::Target;
+
+    fn deref(&self) -> &C::Target {
+        // Safe: we hold the read lock until we are dropped, and the
+        // `Rc` keeps the cell alive. (`Rc` also keeps the guard from
+        // being sent to a thread where the lock is not held.)
+        unsafe { self.owner.mcell().0.shared_ref() }
+    }
+}
+
+impl<C: Lockable> Drop for OwnedShareGuard<C> {
+    fn drop(&mut self) {
+        lock::release_read_lock(self.owner.mcell().0.domain());
+    }
+}
+
+impl<C: Lockable> fmt::Debug for OwnedShareGuard<C>
+where
+    C::Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        C::Target::fmt(self, f)
+    }
+}
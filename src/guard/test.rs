@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+use crate::{MutMap, MutVec};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Not `Clone`.
+#[derive(Debug, PartialEq)]
+struct Payload(Vec<u8>);
+
+#[test]
+fn vec_get_ref() {
+    let v = MutVec::from(vec![Payload(vec![1]), Payload(vec![2, 3])]);
+    assert_eq!(v.get_ref(1).unwrap().0.len(), 2);
+    assert!(v.get_ref(2).is_none());
+
+    // The lock is held while the guard exists.
+    let guard = v.get_ref(0).unwrap();
+    assert!(catch_unwind(AssertUnwindSafe(|| v.push(Payload(vec![])))).is_err());
+    assert_eq!(*guard, Payload(vec![1]));
+    drop(guard);
+    v.push(Payload(vec![]));
+    assert_eq!(v.len(), 3);
+}
+
+#[test]
+fn map_get_ref() {
+    let m = MutMap::new();
+    m.insert("a".to_string(), Payload(vec![1]));
+    assert_eq!(format!("{:?}", m.get_ref("a").unwrap()), "Payload([1])");
+    assert!(m.get_ref("b").is_none());
+}
+
+#[test]
+fn owned_share_guard() {
+    use super::OwnedShareGuard;
+    use std::rc::Rc;
+
+    /// A view that stores its borrow.
+    struct Evens {
+        data: OwnedShareGuard<MutVec<u32>>,
+    }
+
+    impl Evens {
+        fn iter(&self) -> impl Iterator<Item = &u32> {
+            self.data.iter().filter(|n| *n % 2 == 0)
+        }
+    }
+
+    fn view(v: &Rc<MutVec<u32>>) -> Evens {
+        Evens {
+            data: OwnedShareGuard::new(v.clone()),
+        }
+    }
+
+    let v = Rc::new(MutVec::from(vec![1, 2, 3, 4]));
+    let evens = view(&v);
+    assert_eq!(evens.iter().collect::<Vec<_>>(), vec![&2, &4]);
+    assert!(Rc::ptr_eq(OwnedShareGuard::owner(&evens.data), &v));
+    assert!(catch_unwind(AssertUnwindSafe(|| v.push(5))).is_err());
+
+    drop(evens);
+    v.push(6);
+    assert_eq!(v.len(), 5);
+}
@@ -0,0 +1,513 @@
+//! Undo/redo support. Wrapping a collection in `Recorded` journals
+//! each mutation made through the wrapper as an invertible operation,
+//! so that it can later be undone (and redone).
+//!
+//! Mutations are grouped into steps: `checkpoint` closes the current
+//! step, and `undo` reverts one step at a time. Mutations made
+//! directly on the inner collection (bypassing the `Recorded`
+//! methods) are not journaled, and undoing past them may give
+//! surprising results.
+//!
+//! Where several collections are edited together (say, the documents
+//! and the selection of an editor), a `History` journals the
+//! mutations of all of them in one sequence of steps instead:
+//!
+//! ```
+//! use mutable::history::History;
+//! use mutable::{MutMap, MutVec};
+//!
+//! let lines = MutVec::new();
+//! let marks = MutMap::new();
+//! let history = History::new();
+//! history.push(&lines, "hello");
+//! history.insert(&marks, "start", 0);
+//! history.checkpoint();
+//! history.push(&lines, "world");
+//!
+//! assert!(history.undo());
+//! assert_eq!(lines.len(), 1);
+//! assert!(history.undo());
+//! assert!(lines.is_empty() && marks.is_empty());
+//! assert!(history.redo());
+//! assert_eq!(marks.get("start"), Some(0));
+//! ```
+
+use crate::map::MutMap;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::ops::Deref;
+
+mod test;
+
+/// A collection whose mutations can be journaled.
+pub trait Journaled {
+    /// A mutation of the collection.
+    type Op;
+
+    /// Apply `op`, returning the operation that reverses it (if `op`
+    /// turned out to be a no-op, `None`).
+    fn apply(&self, op: Self::Op) -> Option<Self::Op>;
+
+    /// True if the collection is poisoned (see the `poison` module),
+    /// so that its contents, and the inverses journaled for it, can
+    /// no longer be relied upon.
+    fn is_poisoned(&self) -> bool {
+        false
+    }
+}
+
+/// A `Mut<T>` is mutated by storing a new value into it; the inverse
+/// operation stores the old value.
+impl<T> Journaled for Mut<T> {
+    type Op = T;
+
+    fn apply(&self, value: T) -> Option<T> {
+        Some(self.replace(value))
+    }
+
+    fn is_poisoned(&self) -> bool {
+        Mut::is_poisoned(self)
+    }
+}
+
+/// A mutation of a `MutVec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VecOp<T> {
+    Push(T),
+    Pop,
+    Replace(Vec<T>),
+}
+
+impl<T> Journaled for MutVec<T> {
+    type Op = VecOp<T>;
+
+    fn apply(&self, op: VecOp<T>) -> Option<VecOp<T>> {
+        match op {
+            VecOp::Push(value) => {
+                self.push(value);
+                Some(VecOp::Pop)
+            }
+            VecOp::Pop => Some(VecOp::Push(self.pop()?)),
+            VecOp::Replace(v) => Some(VecOp::Replace(self.replace(v))),
+        }
+    }
+
+    fn is_poisoned(&self) -> bool {
+        MutVec::is_poisoned(self)
+    }
+}
+
+/// A mutation of a `MutMap`.
+///
+/// Undoing a removal re-inserts the entry at the end of the map, so
+/// undo restores the map's contents but not necessarily its order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapOp<K, V> {
+    Insert(K, V),
+    Remove(K),
+}
+
+impl<K, V> Journaled for MutMap<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    type Op = MapOp<K, V>;
+
+    fn apply(&self, op: MapOp<K, V>) -> Option<MapOp<K, V>> {
+        match op {
+            MapOp::Insert(key, value) => {
+                let inverse_key = key.clone();
+                match self.insert(key, value) {
+                    Some(old) => Some(MapOp::Insert(inverse_key, old)),
+                    None => Some(MapOp::Remove(inverse_key)),
+                }
+            }
+            MapOp::Remove(key) => {
+                let old = self.remove_shared(&key)?;
+                Some(MapOp::Insert(key, old))
+            }
+        }
+    }
+
+    fn is_poisoned(&self) -> bool {
+        MutMap::is_poisoned(self)
+    }
+}
+
+/// The undo and redo steps. Kept in a `RefCell` rather than an
+/// `MCell`: it is private bookkeeping, and journaling (or discarding
+/// the journal, e.g., when a transaction commits inside a borrow)
+/// must not conflict with the thread lock.
+struct Journal<Op> {
+    /// Inverses of the operations performed since the last
+    /// checkpoint, oldest first. Each step (here and in `undo` and
+    /// `redo`) is applied starting from its last operation.
+    current: Vec<Op>,
+    undo: Vec<Vec<Op>>,
+    redo: Vec<Vec<Op>>,
+}
+
+impl<Op> Default for Journal<Op> {
+    fn default() -> Self {
+        Journal {
+            current: vec![],
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+}
+
+impl<Op> Journal<Op> {
+    fn checkpoint(&mut self) {
+        if !self.current.is_empty() {
+            let step = std::mem::take(&mut self.current);
+            self.undo.push(step);
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        !self.current.is_empty() || !self.undo.is_empty()
+    }
+
+    /// Undo the most recent step of `journal` with `replay`, which
+    /// applies the operations of a step and returns their inverses.
+    /// The journal is not borrowed while `replay` runs.
+    fn undo(journal: &RefCell<Self>, replay: impl FnOnce(Vec<Op>) -> Vec<Op>) -> bool {
+        let step = {
+            let mut journal = journal.borrow_mut();
+            journal.checkpoint();
+            journal.undo.pop()
+        };
+        match step {
+            Some(step) => {
+                let inverse = replay(step);
+                journal.borrow_mut().redo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone step of `journal`; see `undo`.
+    fn redo(journal: &RefCell<Self>, replay: impl FnOnce(Vec<Op>) -> Vec<Op>) -> bool {
+        let step = journal.borrow_mut().redo.pop();
+        match step {
+            Some(step) => {
+                let inverse = replay(step);
+                journal.borrow_mut().undo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A collection whose mutations are journaled for undo/redo. Reads
+/// are available through `Deref`.
+pub struct Recorded<C: Journaled> {
+    inner: C,
+    journal: RefCell<Journal<C::Op>>,
+}
+
+impl<C: Journaled> Recorded<C> {
+    pub fn new(inner: C) -> Self {
+        Recorded {
+            inner,
+            journal: RefCell::new(Journal::default()),
+        }
+    }
+
+    /// Apply `op` to the inner collection and journal its inverse.
+    /// Any redo history is discarded.
+    pub fn record(&self, op: C::Op) {
+        if let Some(inverse) = self.inner.apply(op) {
+            let mut journal = self.journal.borrow_mut();
+            journal.current.push(inverse);
+            journal.redo.clear();
+        }
+    }
+
+    /// End the current step: a subsequent `undo` reverts the
+    /// mutations since the previous checkpoint as a unit.
+    pub fn checkpoint(&self) {
+        self.journal.borrow_mut().checkpoint();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.journal.borrow().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.journal.borrow().redo.is_empty()
+    }
+
+    /// Revert the most recent step (closing the current step first,
+    /// if it has any mutations). Returns false if there was nothing
+    /// to undo.
+    pub fn undo(&self) -> bool {
+        Journal::undo(&self.journal, |step| self.replay(step))
+    }
+
+    /// Re-apply the most recently undone step. Returns false if there
+    /// was nothing to redo.
+    pub fn redo(&self) -> bool {
+        Journal::redo(&self.journal, |step| self.replay(step))
+    }
+
+    /// Discard all undo and redo history.
+    pub fn clear_history(&self) {
+        drop(self.journal.take());
+    }
+
+    /// Take ownership of the inner collection, discarding the history.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Apply the operations in `step`, last first, returning their
+    /// inverses (which are, in turn, to be applied last first).
+    fn replay(&self, step: Vec<C::Op>) -> Vec<C::Op> {
+        step.into_iter()
+            .rev()
+            .filter_map(|op| self.inner.apply(op))
+            .collect()
+    }
+}
+
+impl<C: Journaled> Deref for Recorded<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<T> Recorded<Mut<T>> {
+    /// Store `value` into the cell.
+    pub fn set(&self, value: T) {
+        self.record(value);
+    }
+}
+
+impl<T> Recorded<MutVec<T>> {
+    /// Push `value` onto the end of the vector.
+    pub fn push(&self, value: T) {
+        self.record(VecOp::Push(value));
+    }
+
+    /// Pop a value from the end of the vector, if any.
+    pub fn pop(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let value = self.inner.get(self.inner.len().checked_sub(1)?);
+        self.record(VecOp::Pop);
+        value
+    }
+
+    /// Replace the contents of the vector with `v`.
+    pub fn replace(&self, v: Vec<T>) {
+        self.record(VecOp::Replace(v));
+    }
+}
+
+impl<K, V> Recorded<MutMap<K, V>>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Inserts `(key, value)` into the map.
+    pub fn insert(&self, key: K, value: V) {
+        self.record(MapOp::Insert(key, value));
+    }
+
+    /// Removes `key` from the map, returning its value.
+    pub fn remove(&self, key: K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let value = self.inner.get(&key);
+        self.record(MapOp::Remove(key));
+        value
+    }
+}
+
+/// An operation journaled by a `History`, on whichever collection it
+/// applies to.
+pub(crate) trait Replay<'a> {
+    /// Apply the operation, returning its inverse.
+    fn replay(self: Box<Self>) -> Option<Box<dyn Replay<'a> + 'a>>;
+
+    /// True if the collection is poisoned; see `Journaled::is_poisoned`.
+    fn is_poisoned(&self) -> bool;
+}
+
+struct Entry<'a, C: Journaled> {
+    collection: &'a C,
+    op: C::Op,
+}
+
+impl<'a, C> Replay<'a> for Entry<'a, C>
+where
+    C: Journaled,
+    C::Op: 'a,
+{
+    fn replay(self: Box<Self>) -> Option<Box<dyn Replay<'a> + 'a>> {
+        let inverse = self.collection.apply(self.op)?;
+        Some(Box::new(Entry {
+            collection: self.collection,
+            op: inverse,
+        }))
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.collection.is_poisoned()
+    }
+}
+
+/// An undo/redo journal shared by several collections: each mutation
+/// made through the `History` is journaled, in order, whichever
+/// collection it is made on. As with `Recorded`, mutations made
+/// directly on the collections are not journaled.
+pub struct History<'a> {
+    journal: RefCell<Journal<Box<dyn Replay<'a> + 'a>>>,
+}
+
+impl<'a> History<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `op` to `collection` and journal its inverse. Any redo
+    /// history is discarded.
+    pub fn record<C>(&self, collection: &'a C, op: C::Op)
+    where
+        C: Journaled,
+        C::Op: 'a,
+    {
+        if let Some(inverse) = collection.apply(op) {
+            self.journal(Box::new(Entry {
+                collection,
+                op: inverse,
+            }));
+        }
+    }
+
+    /// Journal `entry`, to be replayed on `undo`. Any redo history is
+    /// discarded.
+    pub(crate) fn journal(&self, entry: Box<dyn Replay<'a> + 'a>) {
+        let mut journal = self.journal.borrow_mut();
+        journal.current.push(entry);
+        journal.redo.clear();
+    }
+
+    /// Undo every step, discarding the history. Collections that are
+    /// poisoned are skipped (their operations are dropped), as this
+    /// may run while a panic unwinds.
+    pub(crate) fn rollback(&self) {
+        let mut journal = self.journal.take();
+        journal.checkpoint();
+        for step in journal.undo.into_iter().rev() {
+            for op in step.into_iter().rev() {
+                if !op.is_poisoned() {
+                    op.replay();
+                }
+            }
+        }
+    }
+
+    /// End the current step: a subsequent `undo` reverts the
+    /// mutations since the previous checkpoint as a unit.
+    pub fn checkpoint(&self) {
+        self.journal.borrow_mut().checkpoint();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.journal.borrow().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.journal.borrow().redo.is_empty()
+    }
+
+    /// Revert the most recent step (closing the current step first,
+    /// if it has any mutations). Returns false if there was nothing
+    /// to undo.
+    pub fn undo(&self) -> bool {
+        Journal::undo(&self.journal, Self::replay)
+    }
+
+    /// Re-apply the most recently undone step. Returns false if there
+    /// was nothing to redo.
+    pub fn redo(&self) -> bool {
+        Journal::redo(&self.journal, Self::replay)
+    }
+
+    /// Discard all undo and redo history.
+    pub fn clear_history(&self) {
+        drop(self.journal.take());
+    }
+
+    /// Apply the operations in `step`, last first, returning their
+    /// inverses (which are, in turn, to be applied last first).
+    fn replay(step: Vec<Box<dyn Replay<'a> + 'a>>) -> Vec<Box<dyn Replay<'a> + 'a>> {
+        step.into_iter()
+            .rev()
+            .filter_map(|op| op.replay())
+            .collect()
+    }
+
+    /// Store `value` into `cell`.
+    pub fn set<T: 'a>(&self, cell: &'a Mut<T>, value: T) {
+        self.record(cell, value);
+    }
+
+    /// Push `value` onto the end of `vec`.
+    pub fn push<T: 'a>(&self, vec: &'a MutVec<T>, value: T) {
+        self.record(vec, VecOp::Push(value));
+    }
+
+    /// Pop a value from the end of `vec`, if any.
+    pub fn pop<T>(&self, vec: &'a MutVec<T>) -> Option<T>
+    where
+        T: 'a + Clone,
+    {
+        let value = vec.get(vec.len().checked_sub(1)?);
+        self.record(vec, VecOp::Pop);
+        value
+    }
+
+    /// Replace the contents of `vec` with `v`.
+    pub fn replace<T: 'a>(&self, vec: &'a MutVec<T>, v: Vec<T>) {
+        self.record(vec, VecOp::Replace(v));
+    }
+
+    /// Inserts `(key, value)` into `map`.
+    pub fn insert<K, V>(&self, map: &'a MutMap<K, V>, key: K, value: V)
+    where
+        K: 'a + Eq + Hash + Clone,
+        V: 'a,
+    {
+        self.record(map, MapOp::Insert(key, value));
+    }
+
+    /// Removes `key` from `map`, returning its value.
+    pub fn remove<K, V>(&self, map: &'a MutMap<K, V>, key: K) -> Option<V>
+    where
+        K: 'a + Eq + Hash + Clone,
+        V: 'a + Clone,
+    {
+        let value = map.get(&key);
+        self.record(map, MapOp::Remove(key));
+        value
+    }
+}
+
+impl Default for History<'_> {
+    fn default() -> Self {
+        History {
+            journal: RefCell::new(Journal::default()),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn vec_undo_redo() {
+    let v = Recorded::new(MutVec::new());
+    v.push(1);
+    v.push(2);
+    v.checkpoint();
+    v.push(3);
+    assert_eq!(v.pop(), Some(3));
+    assert_eq!(v.pop(), Some(2));
+
+    assert!(v.undo());
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 2]);
+    assert!(v.undo());
+    assert!(v.is_empty());
+    assert!(!v.undo());
+
+    assert!(v.redo());
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 2]);
+
+    // A fresh mutation discards the redo history.
+    v.push(4);
+    assert!(!v.can_redo());
+    assert!(v.undo());
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn map_undo_redo() {
+    let m = Recorded::new(MutMap::new());
+    m.insert("a", 1);
+    m.checkpoint();
+    m.insert("a", 2);
+    m.insert("b", 3);
+    assert_eq!(m.remove("a"), Some(2));
+    m.checkpoint();
+
+    assert!(m.undo());
+    assert_eq!(m.get("a"), Some(1));
+    assert_eq!(m.get("b"), None);
+
+    assert!(m.redo());
+    assert_eq!(m.get("a"), None);
+    assert_eq!(m.get("b"), Some(3));
+
+    assert!(m.undo());
+    assert!(m.undo());
+    assert!(m.is_empty());
+}
+
+#[test]
+fn history_across_collections() {
+    let m = Mut::new(0);
+    let v = MutVec::new();
+    let map = MutMap::new();
+    let history = History::new();
+
+    history.set(&m, 1);
+    history.push(&v, "a");
+    history.checkpoint();
+    history.insert(&map, "a", 1);
+    history.push(&v, "b");
+    assert_eq!(history.remove(&map, "a"), Some(1));
+    assert_eq!(history.pop(&v), Some("b"));
+    history.replace(&v, vec!["c"]);
+
+    assert!(history.undo());
+    assert_eq!(
+        (m.get(), v.get(0), v.len(), map.len()),
+        (1, Some("a"), 1, 0)
+    );
+    assert!(history.undo());
+    assert_eq!((m.get(), v.len()), (0, 0));
+    assert!(!history.can_undo());
+
+    assert!(history.redo());
+    assert!(history.redo());
+    assert_eq!(
+        (m.get(), v.get(0), v.len(), map.len()),
+        (1, Some("c"), 1, 0)
+    );
+    assert!(!history.can_redo());
+
+    // A fresh mutation discards the redo history.
+    assert!(history.undo());
+    history.set(&m, 5);
+    assert!(!history.can_redo());
+    history.clear_history();
+    assert!(!history.can_undo());
+    assert_eq!(m.get(), 5);
+}
+
+#[test]
+fn map_removal_reports_to_hooks_and_watch() {
+    use crate::watch::MapEvent;
+
+    let m = MutMap::new();
+    let changes = m.watch();
+    let history = History::new();
+    history.insert(&m, "a", 1);
+    history.checkpoint();
+    history.insert(&m, "b", 2);
+    assert_eq!(history.remove(&m, "a"), Some(1));
+    assert_eq!(
+        changes.drain(),
+        vec![
+            MapEvent::Inserted("a", 1),
+            MapEvent::Inserted("b", 2),
+            MapEvent::Removed("a"),
+        ]
+    );
+
+    // Undoing the last step re-inserts `a` and removes `b`; undoing
+    // the first removes `a`.
+    assert!(history.undo());
+    assert!(history.undo());
+    assert!(m.is_empty());
+    assert_eq!(
+        changes.drain(),
+        vec![
+            MapEvent::Inserted("a", 1),
+            MapEvent::Removed("b"),
+            MapEvent::Removed("a"),
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+//! Fresh ids for arena-style data structures.
+
+use crate::map::{MapStore, MutMap};
+use crate::mcell::MCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+
+mod test;
+
+/// Types that can serve as ids: newtypes around an index.
+pub trait Id: Copy {
+    fn from_index(index: usize) -> Self;
+
+    fn index(self) -> usize;
+}
+
+impl Id for usize {
+    fn from_index(index: usize) -> Self {
+        index
+    }
+
+    fn index(self) -> usize {
+        self
+    }
+}
+
+impl Id for u32 {
+    fn from_index(index: usize) -> Self {
+        u32::try_from(index).expect("id overflow")
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Id for u64 {
+    fn from_index(index: usize) -> Self {
+        index as u64
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Generates ids of type `I`, counting up from zero. A generator made
+/// with `recycling` hands out released ids again (most recently
+/// released first) before making new ones.
+pub struct MutIdGen<I> {
+    data: MCell<IdState>,
+    ids: PhantomData<I>,
+}
+
+#[derive(Default)]
+struct IdState {
+    next: usize,
+    recycle: bool,
+    free: Vec<usize>,
+}
+
+impl<I: Id> MutIdGen<I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A generator that reuses the ids passed to `release`.
+    pub fn recycling() -> Self {
+        MutIdGen {
+            data: MCell::new(IdState {
+                recycle: true,
+                ..IdState::default()
+            }),
+            ids: PhantomData,
+        }
+    }
+
+    /// A fresh id.
+    pub fn fresh(&self) -> I {
+        let index = {
+            let mut data = self.data.borrow_mut();
+            data.free.pop().unwrap_or_else(|| {
+                data.next += 1;
+                data.next - 1
+            })
+        };
+        I::from_index(index)
+    }
+
+    /// Makes `id` available to be handed out again, if this is a
+    /// recycling generator; otherwise, does nothing. The caller must
+    /// not release an id twice, or one that is still in use.
+    pub fn release(&self, id: I) {
+        let index = id.index();
+        let mut data = self.data.borrow_mut();
+        if data.recycle {
+            debug_assert!(index < data.next && !data.free.contains(&index));
+            data.free.push(index);
+        }
+    }
+
+    /// The number of ids handed out so far, not counting reuses.
+    pub fn issued(&self) -> usize {
+        self.data.borrow().next
+    }
+
+    /// Allocates a fresh id and inserts the value `make(id)` under it
+    /// into `map`, returning the id. `make` runs before the id is
+    /// visible in `map`; the map is checked out only for the
+    /// insertion itself.
+    #[track_caller]
+    pub fn insert_fresh<V, S>(&self, map: &MutMap<I, V, S>, make: impl FnOnce(I) -> V) -> I
+    where
+        S: MapStore<I, V>,
+    {
+        let id = self.fresh();
+        let value = make(id);
+        map.insert(id, value);
+        id
+    }
+}
+
+impl<I> Default for MutIdGen<I> {
+    fn default() -> Self {
+        MutIdGen {
+            data: MCell::new(IdState::default()),
+            ids: PhantomData,
+        }
+    }
+}
+
+impl<I> fmt::Debug for MutIdGen<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_struct("MutIdGen")
+            .field("issued", &data.next)
+            .field("free", &data.free)
+            .finish()
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(test)]
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Node(u32);
+
+impl Id for Node {
+    fn from_index(index: usize) -> Self {
+        Node(index as u32)
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[test]
+fn fresh() {
+    let ids = MutIdGen::<Node>::new();
+    assert_eq!(ids.fresh(), Node(0));
+    assert_eq!(ids.fresh(), Node(1));
+    ids.release(Node(0));
+    assert_eq!(ids.fresh(), Node(2));
+    assert_eq!(ids.issued(), 3);
+}
+
+#[test]
+fn recycling() {
+    let ids = MutIdGen::<u32>::recycling();
+    let (a, b) = (ids.fresh(), ids.fresh());
+    ids.release(a);
+    ids.release(b);
+    assert_eq!(ids.fresh(), b);
+    assert_eq!(ids.fresh(), a);
+    assert_eq!(ids.fresh(), 2);
+}
+
+#[test]
+fn insert_fresh() {
+    let ids = MutIdGen::<Node>::new();
+    let nodes = MutMap::new();
+    let a = ids.insert_fresh(&nodes, |id| format!("node {}", id.0));
+    let b = ids.insert_fresh(&nodes, |id| format!("node {}", id.0));
+    assert_eq!(nodes.get(&b).unwrap(), "node 1");
+    assert_eq!(nodes.keys().collect::<Vec<_>>(), vec![a, b]);
+}
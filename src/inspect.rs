@@ -0,0 +1,62 @@
+//! Time-travel debugging for `Logged` collections. Since every
+//! mutation of a `Logged` collection is in its log (along with the
+//! location that made it), we can reconstruct the collection as of
+//! any step and answer questions like "who inserted this key?".
+//!
+//! Steps are positions in the current log; operations removed with
+//! `Logged::take_ops` are no longer visible here.
+
+use crate::history::{Journaled, MapOp, VecOp};
+use crate::map::MutMap;
+use crate::oplog::{self, LogEntry, Logged};
+use crate::vec::MutVec;
+use std::hash::Hash;
+
+mod test;
+
+/// Reconstruct the state of `logged` as it was before log entry
+/// `step` was applied (so `as_of(logged, 0)` is empty).
+pub fn as_of<C>(logged: &Logged<C>, step: usize) -> C
+where
+    C: Journaled + Default,
+    C::Op: Clone,
+{
+    oplog::replay(logged.ops().into_iter().take(step))
+}
+
+/// The most recent log entry whose operation satisfies `pred`.
+pub fn find_last<C>(logged: &Logged<C>, pred: impl Fn(&C::Op) -> bool) -> Option<LogEntry<C::Op>>
+where
+    C: Journaled,
+    C::Op: Clone,
+{
+    logged.entries().into_iter().rev().find(|e| pred(&e.op))
+}
+
+/// The operation that stored the current value for `key`, if any.
+pub fn who_inserted<K, V>(map: &Logged<MutMap<K, V>>, key: &K) -> Option<LogEntry<MapOp<K, V>>>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    let entry = find_last(map, |op| match op {
+        MapOp::Insert(k, _) | MapOp::Remove(k) => k == key,
+    })?;
+    match entry.op {
+        MapOp::Insert(..) => Some(entry),
+        MapOp::Remove(_) => None,
+    }
+}
+
+/// The most recent operation that put `value` into the vector (by
+/// pushing it or by replacing the whole vector).
+pub fn who_added<T>(vec: &Logged<MutVec<T>>, value: &T) -> Option<LogEntry<VecOp<T>>>
+where
+    T: PartialEq + Clone,
+{
+    find_last(vec, |op| match op {
+        VecOp::Push(v) => v == value,
+        VecOp::Replace(v) => v.contains(value),
+        VecOp::Pop => false,
+    })
+}
@@ -0,0 +1,36 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn time_travel() {
+    let m = Logged::new(MutMap::new());
+    m.insert("a", 1);
+    m.insert("b", 2);
+    let line = line!() + 1;
+    m.insert("a", 3);
+    m.remove("b");
+
+    let earlier = as_of(&m, 2);
+    assert_eq!(earlier.get("a"), Some(1));
+    assert_eq!(earlier.get("b"), Some(2));
+    assert!(as_of(&m, 0).is_empty());
+
+    let entry = who_inserted(&m, &"a").unwrap();
+    assert_eq!(entry.step, 2);
+    assert_eq!(entry.location.line(), line);
+    assert!(entry.location.file().ends_with("test.rs"));
+    assert!(who_inserted(&m, &"b").is_none());
+}
+
+#[test]
+fn who_added_value() {
+    let v = Logged::new(MutVec::new());
+    v.push(1);
+    v.replace(vec![2, 3]);
+    v.push(4);
+    assert_eq!(who_added(&v, &3).unwrap().step, 1);
+    assert_eq!(who_added(&v, &4).unwrap().step, 2);
+    assert!(who_added(&v, &1).is_some());
+    assert!(who_added(&v, &5).is_none());
+}
@@ -0,0 +1,94 @@
+//! A map from (possibly overlapping) ranges to values.
+
+use crate::mcell::MCell;
+use std::fmt;
+use std::ops::Range;
+
+mod test;
+
+/// A map from half-open ranges of keys to values, for lookups such as
+/// "which spans contain this offset?". Ranges may overlap, and the
+/// same range may be inserted more than once.
+pub struct MutIntervalMap<K, V> {
+    /// Sorted by the start of the range.
+    data: MCell<Vec<(Range<K>, V)>>,
+}
+
+impl<K: Ord, V> MutIntervalMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Map `range` to `value`. Empty ranges are stored, but never
+    /// found by a query. During the insertion operation, all mut-cells
+    /// are locked and read-only; attempts to read from *this* map will
+    /// find it empty.
+    pub fn insert(&self, range: Range<K>, value: V) {
+        let mut data = self.data.check_out();
+        let index = data.partition_point(|(r, _)| r.start <= range.start);
+        data.insert(index, (range, value));
+    }
+
+    /// The values whose ranges contain `key`, in order of the start of
+    /// their ranges.
+    pub fn query_point(&self, key: &K) -> Vec<V>
+    where
+        V: Clone,
+    {
+        let data = self.data.borrow();
+        let end = data.partition_point(|(r, _)| r.start <= *key);
+        data[..end]
+            .iter()
+            .filter(|(r, _)| *key < r.end)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    /// The entries whose ranges overlap `range`, in order of the start
+    /// of their ranges.
+    pub fn query_overlaps(&self, range: Range<K>) -> Vec<(Range<K>, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let data = self.data.borrow();
+        let end = data.partition_point(|(r, _)| r.start < range.end);
+        data[..end]
+            .iter()
+            .filter(|(r, _)| range.start < r.end && r.start < r.end)
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the entries for which `keep` returns false. While
+    /// `keep` runs, all mut-cells are locked and read-only.
+    #[track_caller]
+    pub fn retain(&self, mut keep: impl FnMut(&Range<K>, &V) -> bool) {
+        self.data.check_out().retain(|(r, v)| keep(r, v));
+    }
+}
+
+impl<K, V> Default for MutIntervalMap<K, V> {
+    fn default() -> Self {
+        MutIntervalMap {
+            data: MCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for MutIntervalMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_map()
+            .entries(data.iter().map(|(r, v)| (r, v)))
+            .finish()
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(test)]
+
+use super::*;
+
+fn spans() -> MutIntervalMap<u32, &'static str> {
+    let map = MutIntervalMap::new();
+    map.insert(10..20, "b");
+    map.insert(0..30, "a");
+    map.insert(15..16, "c");
+    map.insert(25..25, "empty");
+    map
+}
+
+#[test]
+fn point() {
+    let map = spans();
+    assert_eq!(map.query_point(&15), vec!["a", "b", "c"]);
+    assert_eq!(map.query_point(&20), vec!["a"]);
+    assert_eq!(map.query_point(&25), vec!["a"]);
+    assert!(map.query_point(&30).is_empty());
+}
+
+#[test]
+fn overlaps() {
+    let map = spans();
+    assert_eq!(
+        map.query_overlaps(16..22),
+        vec![(0..30, "a"), (10..20, "b")]
+    );
+    assert_eq!(map.query_overlaps(30..40), vec![]);
+    assert_eq!(map.query_overlaps(15..16).len(), 3);
+}
+
+#[test]
+fn retain() {
+    let map = spans();
+    map.retain(|r, _| r.start >= 10);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.query_point(&5), Vec::<&str>::new());
+    assert_eq!(
+        format!("{:?}", map),
+        r#"{10..20: "b", 15..16: "c", 25..25: "empty"}"#
+    );
+}
@@ -0,0 +1,156 @@
+//! In debug builds, detects collections that are mutated while being
+//! iterated. The iterators (`MutVec::iter` and the like) allow this,
+//! but it makes them skip or repeat elements, which is rarely what was
+//! intended; so the first time an iterator created at a given
+//! location observes a mutation, a warning is logged (with the `log`
+//! crate). Release builds do not check, and neither do builds without
+//! the `log` feature (which is on by default).
+//!
+//! An iterator can instead be created with another `IterMode`
+//! (`MutVec::iter_with` and the like), which detects mutations with
+//! the cell's version, in all builds.
+
+use crate::mcell::MCell;
+
+mod test;
+
+/// How an iterator behaves if its collection is mutated while it is
+/// being iterated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IterMode {
+    /// Read the collection as it is at each step, so a mutation may
+    /// make the iterator skip or repeat elements (and, in debug
+    /// builds, logs a warning). This is the mode of `iter`.
+    #[default]
+    Live,
+
+    /// Panic at the first step after a mutation.
+    PanicOnMutation,
+
+    /// Iterate over a copy of the contents taken when the iterator
+    /// was created, unaffected by later mutations.
+    SnapshotAtStart,
+}
+
+/// The state that an iterator keeps for its `IterMode`, with items of
+/// type `I`.
+pub(crate) enum ModeState<I> {
+    Live,
+    PanicOnMutation { version: u64 },
+    Snapshot(std::vec::IntoIter<I>),
+}
+
+impl<I> ModeState<I> {
+    /// Set up `mode` for an iterator over `cell`; `snapshot` copies
+    /// the items, if needed.
+    pub(crate) fn new<T>(
+        mode: IterMode,
+        cell: &MCell<T>,
+        snapshot: impl FnOnce() -> Vec<I>,
+    ) -> Self {
+        match mode {
+            IterMode::Live => ModeState::Live,
+            IterMode::PanicOnMutation => ModeState::PanicOnMutation {
+                version: cell.version(),
+            },
+            IterMode::SnapshotAtStart => ModeState::Snapshot(snapshot().into_iter()),
+        }
+    }
+
+    /// The snapshot to take the next item from, if the iterator has
+    /// one; otherwise the item is to be read from `cell`, which this
+    /// checks has not been mutated, if required.
+    pub(crate) fn snapshot<T>(&mut self, cell: &MCell<T>) -> Option<&mut std::vec::IntoIter<I>> {
+        match self {
+            ModeState::Live => None,
+            ModeState::PanicOnMutation { version } => {
+                if cell.version() != *version {
+                    panic!("collection mutated while being iterated");
+                }
+                None
+            }
+            ModeState::Snapshot(items) => Some(items),
+        }
+    }
+}
+
+#[cfg(all(debug_assertions, feature = "log"))]
+pub(crate) use checked::MutationCheck;
+
+#[cfg(not(all(debug_assertions, feature = "log")))]
+pub(crate) use unchecked::MutationCheck;
+
+#[cfg(all(debug_assertions, feature = "log"))]
+mod checked {
+    use super::*;
+    use crate::mcell::track;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::panic::Location;
+
+    thread_local! {
+        /// Locations that have already been warned about.
+        static WARNED: RefCell<HashSet<&'static Location<'static>>> =
+            RefCell::new(HashSet::new());
+    }
+
+    pub(crate) struct MutationCheck {
+        id: u64,
+        revision: u64,
+        location: &'static Location<'static>,
+        done: bool,
+    }
+
+    impl MutationCheck {
+        #[track_caller]
+        pub(crate) fn new<T>(cell: &MCell<T>) -> Self {
+            let id = cell.id();
+            MutationCheck {
+                id,
+                revision: track::changed_at(id),
+                location: Location::caller(),
+                done: false,
+            }
+        }
+
+        /// Invoked on each step of the iteration.
+        pub(crate) fn check(&mut self) {
+            if self.done || track::changed_at(self.id) == self.revision {
+                return;
+            }
+            self.done = true;
+            if WARNED.with(|w| w.borrow_mut().insert(self.location)) {
+                log::warn!(
+                    "collection mutated while being iterated (iterator created at {}); \
+                     elements may be skipped or repeated",
+                    self.location
+                );
+            }
+        }
+    }
+
+    /// True if an iterator created on the given line was warned about.
+    #[cfg(test)]
+    pub(crate) fn warned(file: &str, line: u32) -> bool {
+        WARNED.with(|w| {
+            w.borrow()
+                .iter()
+                .any(|l| l.file() == file && l.line() == line)
+        })
+    }
+}
+
+#[cfg(not(all(debug_assertions, feature = "log")))]
+mod unchecked {
+    use super::*;
+
+    pub(crate) struct MutationCheck;
+
+    impl MutationCheck {
+        pub(crate) fn new<T>(_cell: &MCell<T>) -> Self {
+            MutationCheck
+        }
+
+        pub(crate) fn check(&mut self) {}
+    }
+}
@@ -0,0 +1,35 @@
+#![cfg(all(test, debug_assertions, feature = "log"))]
+
+use super::checked::warned;
+use crate::{MutMap, MutVec};
+
+#[test]
+fn vec_mutated() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let (mut iter, line) = (v.iter(), line!());
+    iter.next();
+    assert!(!warned(file!(), line));
+    v.pop();
+    iter.next();
+    assert!(warned(file!(), line));
+}
+
+#[test]
+fn map_mutated() {
+    let m: MutMap<u32, u32> = (0..3).map(|i| (i, i)).collect();
+    let (keys, line) = (m.keys(), line!());
+    for key in keys {
+        if key == 0 {
+            m.insert(10, 10);
+        }
+    }
+    assert!(warned(file!(), line));
+}
+
+#[test]
+fn untouched() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let (iter, line) = (v.iter(), line!());
+    assert_eq!(iter.count(), 3);
+    assert!(!warned(file!(), line));
+}
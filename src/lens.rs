@@ -0,0 +1,89 @@
+use crate::mutbl::Mut;
+
+mod test;
+
+/// A view of one part (typically a field, or a field of a field) of
+/// the value in a `Mut<S>`. Reading through a lens clones just that
+/// part, and writing through it updates just that part, rather than
+/// cloning and re-setting the whole value.
+///
+/// Lenses are most easily created with the `project!` macro.
+pub struct Lens<'a, S, T> {
+    cell: &'a Mut<S>,
+    get: Box<dyn Fn(&S) -> &T + 'a>,
+    get_mut: Box<dyn Fn(&mut S) -> &mut T + 'a>,
+}
+
+impl<'a, S, T> Lens<'a, S, T> {
+    /// Create a lens focusing on the part of `cell`'s value selected
+    /// by `get` and `get_mut`, which should select the same place.
+    pub fn new(
+        cell: &'a Mut<S>,
+        get: impl Fn(&S) -> &T + 'a,
+        get_mut: impl Fn(&mut S) -> &mut T + 'a,
+    ) -> Self {
+        Lens {
+            cell,
+            get: Box::new(get),
+            get_mut: Box::new(get_mut),
+        }
+    }
+
+    /// Load (a clone of) the focused part.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Invoke `op` with a reference to the focused part. While `op`
+    /// executes, all mut-cells are read-only.
+    #[track_caller]
+    pub fn with<R>(&self, op: impl FnOnce(&T) -> R) -> R {
+        op((self.get)(&self.cell.data.borrow()))
+    }
+
+    /// Store `value` into the focused part, returning the old value.
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace((self.get_mut)(&mut self.cell.data.borrow_mut()), value)
+    }
+
+    /// Store `value` into the focused part.
+    pub fn set(&self, value: T) {
+        // The old value is dropped once the lock is released.
+        self.replace(value);
+    }
+
+    /// Invoke `op` with mutable access to the focused part. While `op`
+    /// executes, no mut-cell may be accessed (even for reading).
+    #[track_caller]
+    pub fn update<R>(&self, op: impl FnOnce(&mut T) -> R) -> R {
+        op((self.get_mut)(&mut self.cell.data.borrow_mut()))
+    }
+}
+
+/// Creates a `Lens` onto a (possibly nested) field of the value in a
+/// `Mut`:
+///
+/// ```
+/// # use mutable::{project, Mut};
+/// struct Window { size: (u32, u32) }
+/// struct Config { window: Window }
+///
+/// let config = Mut::new(Config { window: Window { size: (640, 480) } });
+/// project!(config.window.size).set((800, 600));
+/// assert_eq!(project!(config.window.size).get(), (800, 600));
+/// ```
+///
+/// When the cell is not a plain variable, write `project!(expr =>
+/// field.subfield)`.
+#[macro_export]
+macro_rules! project {
+    ($cell:ident $(. $field:tt)+) => {
+        $crate::project!($cell => $($field).+)
+    };
+    ($cell:expr => $($field:tt).+) => {
+        $crate::lens::Lens::new(&$cell, |s| &s $(.$field)+, |s| &mut s $(.$field)+)
+    };
+}
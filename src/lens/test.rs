@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+use crate::project;
+use std::rc::Rc;
+
+#[derive(Debug)]
+struct Config {
+    window: Window,
+    title: String,
+}
+
+#[derive(Debug)]
+struct Window {
+    size: (u32, u32),
+}
+
+fn config() -> Mut<Config> {
+    Mut::new(Config {
+        window: Window { size: (640, 480) },
+        title: "app".to_string(),
+    })
+}
+
+#[test]
+fn project_nested() {
+    let config = config();
+    let width = project!(config.window.size.0);
+    assert_eq!(width.replace(800), 640);
+    width.update(|w| *w += 24);
+    assert_eq!(project!(config.window.size).get(), (824, 480));
+    assert_eq!(project!(config.title).with(|t| t.len()), 3);
+}
+
+#[test]
+fn project_expr() {
+    let shared = Rc::new(config());
+    let height = project!(shared.as_ref() => window.size.1);
+    height.set(100);
+    assert_eq!(project!(shared.as_ref() => window.size).get(), (640, 100));
+}
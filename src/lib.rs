@@ -1,8 +1,140 @@
+pub mod any;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+pub mod arc_cell;
+pub mod array;
+pub mod bitset;
+pub mod computed;
+pub mod counter;
+pub mod cow;
+#[cfg(feature = "debug")]
+pub mod debug;
+pub mod deque;
+pub mod diff;
+pub mod domain;
+pub mod double_buffered;
+pub mod enum_map;
+pub mod error;
+mod fingerprint;
+pub mod frozen;
+pub mod gc;
+pub mod ghost;
+pub mod graph;
+pub mod grid;
+pub mod guard;
+pub mod history;
+pub mod id_gen;
+pub mod inspect;
+pub mod interval_map;
+mod iter_check;
+pub mod lens;
+pub mod limit;
 pub mod map;
 mod mcell;
+pub mod memory;
+pub mod multi;
+pub mod multi_map;
 mod mutbl;
+pub mod observe;
+pub mod offload;
+pub mod once;
+pub mod oplog;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "im")]
+pub mod persistent;
+pub mod poison;
+pub mod prelude;
+pub mod pretty;
+pub mod queue;
+pub mod raw;
+pub mod rc;
+pub mod registry;
+pub mod revision;
+pub mod ring;
+pub mod scoped;
+pub mod send;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod set;
+pub mod sharded;
+pub mod slab;
+#[cfg(feature = "bincode")]
+pub mod snapshot;
+pub mod sorted;
+pub mod statics;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod string;
+pub mod sync;
+pub mod task;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod traits;
+pub mod transaction;
+pub mod tree;
+pub mod try_lock;
+pub mod unwind;
+pub mod value;
 pub mod vec;
+pub mod violation;
+pub mod watch;
+pub mod weak_map;
+pub mod work_queue;
 
-pub use map::MutMap;
+pub use any::MutAny;
+pub use arc_cell::MutArcCell;
+pub use array::MutArray;
+pub use bitset::MutBitSet;
+pub use computed::Computed;
+pub use counter::MutCounter;
+pub use cow::{MutCowMap, MutCowVec};
+pub use deque::MutDeque;
+pub use domain::LockDomain;
+pub use double_buffered::DoubleBufferedMut;
+pub use enum_map::{EnumKey, MutEnumMap};
+pub use error::{
+    CapacityError, GuardOutlived, IndexError, InsertError, KeyError, LockError, Poisoned,
+};
+pub use frozen::{FrozenMap, FrozenVec};
+pub use ghost::{GhostCell, GhostToken};
+pub use graph::{MutGraph, NodeIndex};
+pub use grid::MutGrid;
+pub use id_gen::{Id, MutIdGen};
+pub use interval_map::MutIntervalMap;
+pub use iter_check::IterMode;
+pub use lens::Lens;
+pub use limit::LimitPolicy;
+pub use map::{MutBTreeMap, MutMap, MutSmallMap};
+pub use memory::MemoryUsage;
+pub use multi::{borrow_mut2, borrow_mut3, Lockable};
+pub use multi_map::MutMultiMap;
 pub use mutbl::Mut;
+pub use once::{MutLazy, MutOnce};
+#[cfg(feature = "im")]
+pub use persistent::{MutImMap, MutImVec};
+pub use queue::MutQueue;
+pub use rc::{MutRc, MutWeak};
+pub use registry::MutRegistry;
+pub use revision::{CellId, DirtySet, Revision, Tracked};
+pub use ring::MutRingBuffer;
+pub use scoped::MutScopedMap;
+pub use set::{MutIndexSet, MutSet};
+pub use sharded::MutShardedMap;
+pub use slab::MutSlab;
+pub use sorted::MutSortedVec;
+pub use string::MutString;
+pub use sync::{SyncMut, SyncMutMap, SyncMutVec};
+pub use traits::{MutMapLike, MutSequence};
+pub use transaction::{transaction, Transaction};
+pub use tree::{MutTree, NodeId};
+pub use try_lock::{try_modify, try_read};
+pub use unwind::catch_unwind_restore;
+pub use value::MutValue;
 pub use vec::MutVec;
+pub use violation::{set_violation_handler, Violation, ViolationInfo};
+pub use weak_map::MutWeakMap;
+pub use work_queue::MutWorkQueue;
+
+#[cfg(feature = "derive")]
+pub use mutable_derive::{EnumKey, Mutable};
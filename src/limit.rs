@@ -0,0 +1,67 @@
+//! Capacity limits for `MutVec` and `MutMap`, so that a shared buffer
+//! in a long-running program cannot grow without bound. See
+//! `MutVec::set_max_len` and `MutMap::set_max_len`.
+
+use std::cell::Cell;
+use std::panic::RefUnwindSafe;
+
+mod test;
+
+/// What a collection with a maximum length does when an insertion
+/// would exceed it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LimitPolicy {
+    /// Reject the insertion: `try_push` and `try_insert` return a
+    /// `CapacityError`, while `push` and `insert` panic.
+    Error,
+
+    /// Remove the oldest element (the first one) to make room.
+    EvictOldest,
+
+    /// Panic.
+    Panic,
+}
+
+/// The maximum length of a collection, if any, together with its
+/// policy and the store-specific data `F` needed to enforce it.
+pub(crate) struct Limit<F: Copy> {
+    limit: Cell<Option<(usize, LimitPolicy, F)>>,
+}
+
+/// The limit is a plain value that is replaced wholesale, so a panic
+/// cannot leave it half-updated.
+impl<F: Copy> RefUnwindSafe for Limit<F> {}
+
+impl<F: Copy> Limit<F> {
+    pub(crate) fn new() -> Self {
+        Limit {
+            limit: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn set(&self, max_len: usize, policy: LimitPolicy, data: F) {
+        assert!(
+            max_len > 0 || policy != LimitPolicy::EvictOldest,
+            "a collection with a maximum length of zero has nothing to evict"
+        );
+        self.limit.set(Some((max_len, policy, data)));
+    }
+
+    pub(crate) fn clear(&self) {
+        self.limit.set(None);
+    }
+
+    pub(crate) fn get(&self) -> Option<(usize, LimitPolicy, F)> {
+        self.limit.get()
+    }
+
+    pub(crate) fn max_len(&self) -> Option<usize> {
+        self.get().map(|(max_len, _, _)| max_len)
+    }
+}
+
+/// Panics for a collection whose limit of `max_len` would be
+/// exceeded.
+pub(crate) fn exceeded(max_len: usize) -> ! {
+    panic!("capacity limit of {} exceeded", max_len)
+}
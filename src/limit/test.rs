@@ -0,0 +1,159 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{CapacityError, MutMap, MutVec};
+
+#[test]
+fn vec_error() {
+    let v = MutVec::from(vec![1, 2]);
+    v.set_max_len(3, LimitPolicy::Error);
+    assert_eq!(v.max_len(), Some(3));
+    assert_eq!(v.try_push(3), Ok(()));
+    let err = v.try_push(4).unwrap_err();
+    assert_eq!(
+        err,
+        CapacityError {
+            value: 4,
+            max_len: 3
+        }
+    );
+    assert_eq!(err.to_string(), "capacity limit of 3 exceeded");
+    assert_eq!(v.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "capacity limit of 1 exceeded")]
+fn vec_push_over_error_limit() {
+    let v = MutVec::from(vec![1]);
+    v.set_max_len(1, LimitPolicy::Error);
+    v.push(2);
+}
+
+#[test]
+#[should_panic(expected = "capacity limit of 1 exceeded")]
+fn vec_panic() {
+    let v = MutVec::from(vec![1]);
+    v.set_max_len(1, LimitPolicy::Panic);
+    let _ = v.try_push(2);
+}
+
+#[test]
+fn vec_evict_oldest() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let v = MutVec::new();
+    v.set_max_len(2, LimitPolicy::EvictOldest);
+    let evicted = Rc::new(RefCell::new(vec![]));
+    let e = evicted.clone();
+    v.on_remove(move |x| e.borrow_mut().push(*x));
+
+    for i in 0..5 {
+        v.push(i);
+    }
+    assert_eq!(*evicted.borrow(), vec![0, 1, 2]);
+
+    v.clear_max_len();
+    v.push(5);
+    assert_eq!(v.into_inner(), vec![3, 4, 5]);
+}
+
+#[test]
+fn map_error() {
+    let m = MutMap::new();
+    m.set_max_len(2, LimitPolicy::Error);
+    m.insert("a", 1);
+    m.insert("b", 2);
+
+    // Replacing a value does not grow the map.
+    assert_eq!(m.try_insert("a", 10), Ok(Some(1)));
+    let err = m.try_insert("c", 3).unwrap_err();
+    assert_eq!(err.value, ("c", 3));
+    assert_eq!(m.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+}
+
+#[test]
+fn map_error_discards_staged_insert() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let m = MutMap::new();
+    m.set_max_len(1, LimitPolicy::Error);
+    let inserted = Rc::new(RefCell::new(vec![]));
+    let i = inserted.clone();
+    m.on_insert(move |k: &&str, _: &u32| i.borrow_mut().push(*k));
+
+    m.insert("a", 1);
+    assert!(m.try_insert("b", 2).is_err());
+    m.clear_max_len();
+    m.insert("c", 3);
+    assert_eq!(*inserted.borrow(), vec!["a", "c"]);
+}
+
+#[test]
+fn map_error_leaves_map_untouched() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let m = MutMap::new();
+    m.set_max_len(1, LimitPolicy::Error);
+    m.insert("a", 1);
+    let notified = Rc::new(Cell::new(0));
+    let n = notified.clone();
+    m.subscribe(move || n.set(n.get() + 1));
+    let version = m.version();
+
+    assert!(m.try_insert("b", 2).is_err());
+    assert_eq!(m.version(), version);
+    assert_eq!(notified.get(), 0);
+}
+
+#[test]
+fn map_evict_oldest() {
+    let m = MutMap::new();
+    m.set_max_len(2, LimitPolicy::EvictOldest);
+    for (i, key) in ["a", "b", "c", "d"].iter().enumerate() {
+        m.insert(*key, i);
+    }
+    assert_eq!(m.iter().collect::<Vec<_>>(), vec![("c", 2), ("d", 3)]);
+}
+
+#[test]
+fn map_error_update_after_lowering() {
+    let m = MutMap::new();
+    m.insert("a", 1);
+    m.insert("b", 2);
+    m.insert("c", 3);
+    m.set_max_len(2, LimitPolicy::Error);
+
+    // Updating an existing key adds no entry, so the limit does not
+    // apply, even though the map is already over it.
+    assert_eq!(m.try_insert("a", 10), Ok(Some(1)));
+    assert_eq!(
+        m.iter().collect::<Vec<_>>(),
+        vec![("a", 10), ("b", 2), ("c", 3)]
+    );
+    assert!(m.try_insert("d", 4).is_err());
+    assert_eq!(m.len(), 3);
+}
+
+#[test]
+fn map_evict_oldest_update_after_lowering() {
+    let m = MutMap::new();
+    m.insert("a", 1);
+    m.insert("b", 2);
+    m.insert("c", 3);
+    m.set_max_len(2, LimitPolicy::EvictOldest);
+
+    assert_eq!(m.insert("c", 30), Some(3));
+    assert_eq!(
+        m.iter().collect::<Vec<_>>(),
+        vec![("a", 1), ("b", 2), ("c", 30)]
+    );
+}
+
+#[test]
+#[should_panic(expected = "nothing to evict")]
+fn evict_with_zero_limit() {
+    MutVec::<u32>::new().set_max_len(0, LimitPolicy::EvictOldest);
+}
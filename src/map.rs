@@ -1,69 +1,460 @@
+use crate::diff::{self, MapDiff};
+use crate::error::{CapacityError, KeyError};
+use crate::fingerprint::{Delta, Fingerprint};
+use crate::guard::ElementGuard;
+use crate::iter_check::{IterMode, ModeState, MutationCheck};
+use crate::limit::{self, Limit, LimitPolicy};
 use crate::mcell::MCell;
-use indexmap::Equivalent;
+use crate::memory::MemoryUsage;
+use crate::observe;
+use crate::observe::hooks;
+use crate::observe::SubscriptionId;
+use crate::pretty::Pretty;
 use indexmap::IndexMap;
+use std::fmt;
 use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
 
+pub mod btree;
+pub mod small;
+pub mod store;
 mod test;
 
-pub struct MutMap<K, V> {
-    data: MCell<IndexMap<K, V>>,
+pub use btree::MutBTreeMap;
+pub use small::SmallMap;
+pub use store::{MapLookup, MapStore};
+
+/// A `MutMap` that stores up to `small::INLINE_CAPACITY` entries
+/// inline. See `SmallMap`.
+pub type MutSmallMap<K, V> = MutMap<K, V, SmallMap<K, V>>;
+
+/// A map of mutable data. By default the entries are kept in an
+/// `IndexMap`, but any `MapStore` (e.g., a `HashMap` or `BTreeMap`)
+/// may be used instead; the index-based methods (`get_index`, `iter`,
+/// and friends) are only available with the default store.
+pub struct MutMap<K, V, S = IndexMap<K, V>> {
+    pub(crate) data: MCell<S>,
+    entries: PhantomData<(K, V)>,
+    limit: Limit<Evict<K, V, S>>,
+    pub(crate) fingerprint: Fingerprint<K, V, S>,
 }
 
-impl<K, V> MutMap<K, V>
-where
-    K: Eq + Hash,
-{
-    pub fn new() -> Self {
-        Self::default()
+/// What enforcing a limit needs of a store: whether a key is present
+/// (a key that is not adds an entry) and how to remove the first
+/// entry, for `EvictOldest`. The latter needs an ordered store, so
+/// limits are only offered for `IndexMap`.
+struct Evict<K, V, S> {
+    contains: fn(&S, &K) -> bool,
+    first: fn(&mut S) -> Option<(K, V)>,
+}
+
+impl<K, V, S> Clone for Evict<K, V, S> {
+    fn clone(&self) -> Self {
+        *self
     }
+}
+
+impl<K, V, S> Copy for Evict<K, V, S> {}
 
+impl<K, V, S> MutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
     pub fn len(&self) -> usize {
         self.data.borrow().len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
     /// Inserts `(key, value)` into the map. During the insertion
     /// operation, all mut-cells are locked and read-only. Attempts to
     /// read from *this* map during insertion will encounter an empty
     /// map.
+    ///
+    /// Panics if this would exceed the maximum length and the policy
+    /// is not `EvictOldest`; see `set_max_len`.
     pub fn insert(&self, key: K, value: V) -> Option<V> {
-        self.data.check_out().insert(key, value)
+        match self.try_insert(key, value) {
+            Ok(old) => old,
+            Err(err) => limit::exceeded(err.max_len),
+        }
+    }
+
+    /// Like `insert`, but if this would exceed the maximum length and
+    /// the policy is `LimitPolicy::Error`, returns the entry in an
+    /// error instead of panicking.
+    pub fn try_insert(&self, key: K, value: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        self.insert_or_reject(key, value)
+            .map_err(|err| match self.limit.get() {
+                Some((_, LimitPolicy::Panic, _)) => limit::exceeded(err.max_len),
+                _ => err,
+            })
+    }
+
+    /// Like `try_insert`, but returns an error under the `Panic`
+    /// policy as well (for `insert_locked`).
+    pub(crate) fn insert_or_reject(
+        &self,
+        key: K,
+        value: V,
+    ) -> Result<Option<V>, CapacityError<(K, V)>> {
+        // Only a new key adds an entry: updating an existing key is
+        // fine even if the limit was lowered below the length.
+        let evict = match self.limit.get() {
+            Some((max_len, policy, evict))
+                if self.len() >= max_len && !(evict.contains)(&self.data.borrow(), &key) =>
+            {
+                match policy {
+                    LimitPolicy::EvictOldest => Some(evict.first),
+                    LimitPolicy::Error | LimitPolicy::Panic => {
+                        return Err(CapacityError {
+                            value: (key, value),
+                            max_len,
+                        })
+                    }
+                }
+            }
+            _ => None,
+        };
+        let id = self.data.existing_id();
+        // Unsafe proof obligation: the elements of a map are `(K, V)`
+        // (see the `hooks` module).
+        let staged = unsafe { hooks::stage_insert(id, &key, &value) };
+        let mut delta = self.fingerprint.begin(id);
+        let key_hash = match &mut delta {
+            Some(delta) => {
+                let key_hash = delta.hash_key(&key);
+                delta.add(key_hash, &value);
+                key_hash
+            }
+            None => 0,
+        };
+        let (old, evicted) = {
+            let mut data = self.data.check_out();
+            let old = data.insert(key, value);
+            (old, evict.and_then(|first| first(&mut data)))
+        };
+        if let Some(delta) = &mut delta {
+            if let Some(old) = &old {
+                delta.sub(key_hash, old);
+            }
+            if let Some((key, value)) = &evicted {
+                delta.sub_entry(key, value);
+            }
+        }
+        self.fingerprint.end(delta);
+        self.removed(evicted);
+        staged.fire();
+        Ok(old)
     }
 
     /// Removes `key` from the map. During the removal operation, all
     /// mut-cells are locked and read-only. Attempts to read from
     /// *this* map during removal will encounter an empty map.
-    pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V, Key = K>,
+    {
+        self.remove_shared(key)
+    }
+
+    /// `remove`, through a shared reference (`remove` takes `&mut
+    /// self` for historical reasons), for the wrappers that journal or
+    /// log removals.
+    pub(crate) fn remove_shared<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V, Key = K>,
+    {
+        let delta = self.fingerprint.begin(self.data.existing_id());
+        let entry = self.data.check_out().remove_entry(key);
+        self.removed_with(delta, entry)
+    }
+
+    /// Applies `patch` (e.g., computed by `diff` on another copy of
+    /// the map): removes its `removed` keys and inserts its `added`
+    /// and `changed` entries, all in one check-out. During the
+    /// operation, all mut-cells are locked and read-only; attempts to
+    /// read from *this* map will encounter an empty map.
+    pub fn apply(&self, patch: MapDiff<K, V>)
+    where
+        S: MapLookup<K, V, Key = K>,
+    {
+        let id = self.data.existing_id();
+        // Staged last to first, as `Staged::fire` takes the most
+        // recently staged entry. Unsafe proof obligation: as in
+        // `insert_or_reject`.
+        let staged = unsafe {
+            hooks::stage_inserts(
+                id,
+                patch
+                    .added
+                    .iter()
+                    .chain(&patch.changed)
+                    .rev()
+                    .map(|(k, v)| (k, v)),
+            )
+        };
+        let mut delta = self.fingerprint.begin(id);
+        let removed = self.with_checked_out(|data| {
+            let removed: Vec<(K, V)> = patch
+                .removed
+                .iter()
+                .filter_map(|key| data.remove_entry(key))
+                .collect();
+            if let Some(delta) = &mut delta {
+                for (key, value) in &removed {
+                    delta.sub_entry(key, value);
+                }
+            }
+            for (key, value) in patch.added.into_iter().chain(patch.changed) {
+                match &mut delta {
+                    Some(delta) => {
+                        let key_hash = delta.hash_key(&key);
+                        delta.add(key_hash, &value);
+                        if let Some(old) = data.insert(key, value) {
+                            delta.sub(key_hash, &old);
+                        }
+                    }
+                    None => {
+                        data.insert(key, value);
+                    }
+                }
+            }
+            removed
+        });
+        self.fingerprint.end(delta);
+        for (key, value) in &removed {
+            // Unsafe proof obligation: as in `insert_or_reject`.
+            unsafe { hooks::fire_remove(id, key, value) }
+        }
+        staged.fire();
+    }
+
+    /// Make `self` a copy of `other`, reusing the storage of `self`
+    /// where the store supports it (as `IndexMap::clone_from` does)
+    /// rather than allocating anew. While the entries are cloned, all
+    /// mut-cells are locked and read-only; attempts to read from
+    /// *this* map will find it empty. The element hooks are not
+    /// invoked.
+    pub fn copy_from(&self, other: &MutMap<K, V, S>)
     where
-        Q: Hash + Equivalent<K>,
+        S: Clone,
     {
-        self.data.check_out().remove(key)
+        if !std::ptr::eq(self, other) {
+            self.data.check_out().clone_from(&other.data.borrow());
+        }
+    }
+
+    /// Replace the contents with `store`, reporting every old entry
+    /// to the `on_remove` hooks and every new one to the `on_insert`
+    /// hooks. During the operation, all mut-cells are locked and
+    /// read-only; attempts to read from *this* map will encounter an
+    /// empty map.
+    pub(crate) fn replace_store(&self, store: S)
+    where
+        for<'a> &'a S: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        let id = self.data.existing_id();
+        let staged = if hooks::any(id) {
+            // Staged last to first, as in `apply`. Unsafe proof
+            // obligation: as in `insert_or_reject`.
+            let entries: Vec<_> = store.into_iter().collect();
+            Some(unsafe { hooks::stage_inserts(id, entries.into_iter().rev()) })
+        } else {
+            None
+        };
+        let old = std::mem::replace(&mut *self.data.check_out(), store);
+        for (key, value) in &old {
+            // Unsafe proof obligation: as in `insert_or_reject`.
+            unsafe { hooks::fire_remove(id, key, value) }
+        }
+        if let Some(staged) = staged {
+            staged.fire();
+        }
+    }
+
+    /// Take ownership of our internal map, consuming `self`.
+    pub fn into_inner(self) -> S {
+        self.data.into_inner()
+    }
+
+    /// Runs `op` with shared access to the underlying map. All
+    /// mut-cells are read-only while `op` runs.
+    pub(crate) fn with_borrowed<R>(&self, op: impl FnOnce(&S) -> R) -> R {
+        op(&self.data.borrow())
+    }
+
+    /// Runs `op` with the map checked out, so that a read-modify-write
+    /// sequence happens in one locked step. As with `insert`, readers
+    /// of *this* map will encounter an empty map while `op` runs.
+    pub(crate) fn with_checked_out<R>(&self, op: impl FnOnce(&mut S) -> R) -> R {
+        op(&mut self.data.check_out())
     }
 
     /// A variant on `insert` where all data is lost on panic. This
     /// exists for benchmarking purposes.
     pub fn insert_not_panic_safe(&self, key: K, value: V) -> Option<V> {
-        self.data
-            .check_out_not_panic_safe(|data| data.insert(key, value))
+        let id = self.data.existing_id();
+        // Unsafe proof obligation: as in `insert_or_reject`.
+        let staged = unsafe { hooks::stage_insert(id, &key, &value) };
+        let mut delta = self.fingerprint.begin(id);
+        let key_hash = match &mut delta {
+            Some(delta) => {
+                let key_hash = delta.hash_key(&key);
+                delta.add(key_hash, &value);
+                key_hash
+            }
+            None => 0,
+        };
+        let old = self
+            .data
+            .check_out_not_panic_safe(|data| data.insert(key, value));
+        if let (Some(delta), Some(old)) = (&mut delta, &old) {
+            delta.sub(key_hash, old);
+        }
+        self.fingerprint.end(delta);
+        staged.fire();
+        old
     }
 
     /// A variant on `insert` where all data is lost on panic. This
     /// exists for benchmarking purposes.
-    pub fn remove_not_panic_safe<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    pub fn remove_not_panic_safe<Q>(&mut self, key: &Q) -> Option<V>
     where
-        Q: Hash + Equivalent<K>,
+        Q: ?Sized,
+        S: MapLookup<Q, V, Key = K>,
     {
-        self.data.check_out_not_panic_safe(|data| data.remove(key))
+        let delta = self.fingerprint.begin(self.data.existing_id());
+        let entry = self
+            .data
+            .check_out_not_panic_safe(|data| data.remove_entry(key));
+        self.removed_with(delta, entry)
     }
 
-    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<V>
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
     where
-        Q: Hash + Equivalent<K>,
+        Q: ?Sized,
+        S: MapLookup<Q, V>,
         V: Clone,
     {
         let data = self.data.borrow();
         data.get(key).cloned()
     }
 
+    /// Like `get`, but returns an error recording the missing key.
+    pub fn try_get<Q>(&self, key: &Q) -> Result<V, KeyError<K>>
+    where
+        Q: ?Sized + ToOwned<Owned = K>,
+        S: MapLookup<Q, V>,
+        V: Clone,
+    {
+        self.get(key).ok_or_else(|| KeyError {
+            key: key.to_owned(),
+        })
+    }
+
+    /// The footprint of the map.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.data.borrow().memory_usage()
+    }
+
+    /// Get a reference to the value for `key`, if any. All mut-cells
+    /// are read-only while the guard exists.
+    #[track_caller]
+    pub fn get_ref<Q>(&self, key: &Q) -> Option<ElementGuard<'_, V>>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V>,
+    {
+        let guard = self.data.borrow().filter_map(|data| data.get(key))?;
+        Some(ElementGuard::new(guard))
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Register `hook` to be invoked with each entry added to `self`
+    /// (by `insert` or `apply`), once the operation is complete. The
+    /// hook receives clones of the key and value, taken just before
+    /// the insertion. Inserting a key that is already present reports
+    /// only the new entry (the old value is returned to the caller).
+    /// Remove it with `unsubscribe`.
+    pub fn on_insert(&self, hook: impl Fn(&K, &V) + 'static) -> SubscriptionId
+    where
+        K: Clone + 'static,
+        V: Clone + 'static,
+    {
+        observe::hooks::on_insert(self.data.id(), hook)
+    }
+
+    /// Register `hook` to be invoked with each entry removed from
+    /// `self` (by `remove`, `remove_index_range`, or `apply`), once
+    /// the operation is complete. Remove it with `unsubscribe`.
+    pub fn on_remove(&self, hook: impl Fn(&K, &V) + 'static) -> SubscriptionId {
+        observe::hooks::on_remove(self.data.id(), hook)
+    }
+
+    /// Remove a callback registered with `subscribe`, `on_insert`, or
+    /// `on_remove`, returning false if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+
+    /// The fingerprint of the contents, if enabled with
+    /// `enable_fingerprint`: a hash that does not depend on the order
+    /// of the entries, so two maps with the same entries have the
+    /// same fingerprint. It is kept up to date by `insert`, `remove`,
+    /// and `apply` in constant time per entry; after any other
+    /// mutation it is recomputed (in time proportional to the length)
+    /// when next requested.
+    pub fn fingerprint(&self) -> Option<u64> {
+        if !self.fingerprint.is_enabled() {
+            return None;
+        }
+        let id = self.data.id();
+        self.fingerprint.get(id, &self.data.borrow())
+    }
+
+    /// Like `removed`, also finishing the fingerprint update begun
+    /// with `delta`.
+    fn removed_with(&self, mut delta: Option<Delta<K, V, S>>, entry: Option<(K, V)>) -> Option<V> {
+        if let (Some(delta), Some((key, value))) = (&mut delta, &entry) {
+            delta.sub_entry(key, value);
+        }
+        self.fingerprint.end(delta);
+        self.removed(entry)
+    }
+
+    /// Report `entry`, just removed, to the `on_remove` hooks,
+    /// returning its value.
+    fn removed(&self, entry: Option<(K, V)>) -> Option<V> {
+        let (key, value) = entry?;
+        // Unsafe proof obligation: as in `insert_or_reject`.
+        unsafe { hooks::fire_remove(self.data.existing_id(), &key, &value) }
+        Some(value)
+    }
+}
+
+impl<K, V> MutMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// Creates an empty map backed by an `IndexMap`. Maps with other
+    /// stores can be created with `Default` or `From`.
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     pub fn get_index(&self, index: usize) -> Option<(K, V)>
     where
         K: Clone,
@@ -74,6 +465,67 @@ where
         Some((k.clone(), v.clone()))
     }
 
+    /// Limit the map to `max_len` entries, handling an `insert` of a
+    /// new key that would exceed it according to `policy`.
+    /// (`EvictOldest` removes the first entry, which takes time
+    /// proportional to the length.) Only `insert` and `try_insert`
+    /// are limited: `apply` is not, and lowering the limit does not
+    /// remove any entries. Clones of the map are not limited.
+    pub fn set_max_len(&self, max_len: usize, policy: LimitPolicy) {
+        let evict = Evict {
+            contains: |map: &IndexMap<K, V>, key: &K| map.contains_key(key),
+            first: |map: &mut IndexMap<K, V>| map.shift_remove_index(0),
+        };
+        self.limit.set(max_len, policy, evict);
+    }
+
+    /// Start maintaining a fingerprint of the contents; see
+    /// `fingerprint`. This hashes every entry once. Clones of the map
+    /// do not maintain a fingerprint.
+    pub fn enable_fingerprint(&self)
+    where
+        V: Hash,
+    {
+        self.fingerprint.enable(self.data.id(), &self.data.borrow());
+    }
+
+    /// Stop maintaining the fingerprint.
+    pub fn disable_fingerprint(&self) {
+        self.fingerprint.disable();
+    }
+
+    /// Remove the limit set with `set_max_len`.
+    pub fn clear_max_len(&self) {
+        self.limit.clear();
+    }
+
+    /// The limit set with `set_max_len`, if any.
+    pub fn max_len(&self) -> Option<usize> {
+        self.limit.max_len()
+    }
+
+    /// Removes the entries whose indices fall within `range`,
+    /// shifting the later entries down, and returns them in order.
+    /// Panics if the range is out of bounds. During the removal, all
+    /// mut-cells are locked and read-only; attempts to read from
+    /// *this* map will find it empty.
+    pub fn remove_index_range(&self, range: Range<usize>) -> Vec<(K, V)> {
+        let id = self.data.existing_id();
+        let mut delta = self.fingerprint.begin(id);
+        let removed: Vec<(K, V)> = self.data.check_out().drain(range).collect();
+        if let Some(delta) = &mut delta {
+            for (key, value) in &removed {
+                delta.sub_entry(key, value);
+            }
+        }
+        self.fingerprint.end(delta);
+        for (key, value) in &removed {
+            // Unsafe proof obligation: as in `insert_or_reject`.
+            unsafe { hooks::fire_remove(id, key, value) }
+        }
+        removed
+    }
+
     pub fn get_key_index(&self, index: usize) -> Option<K>
     where
         K: Clone,
@@ -92,20 +544,74 @@ where
         Some(v.clone())
     }
 
+    /// Compute the changes that turn `older` (typically an earlier
+    /// snapshot of this map) into the current contents. The
+    /// comparison happens under a single read borrow.
+    pub fn diff(&self, older: &IndexMap<K, V>) -> MapDiff<K, V>
+    where
+        K: Clone,
+        V: PartialEq + Clone,
+    {
+        diff::diff_maps(&self.data.borrow(), older)
+    }
+
+    /// An adapter that displays the map with one entry per line.
+    pub fn pretty(&self) -> Pretty<'_, Self> {
+        Pretty::new(self)
+    }
+
     /// Iterate over the elements in `self`, cloning them as we go.
     ///
     /// Note that it is possible to mutate `self` during this
     /// iteration (for example, by pushing or popping elements onto
     /// it). Doing so may lead to surprising results but is not
     /// undefined behavior in any way.
+    ///
+    /// In debug builds, such a mutation logs a warning.
+    #[track_caller]
     pub fn iter(&self) -> Iter<'_, K, V>
     where
         K: Clone,
         V: Clone,
     {
+        self.iter_with(IterMode::Live)
+    }
+
+    /// Like `iter`, but with `mode` determining what happens if `self`
+    /// is mutated during the iteration.
+    #[track_caller]
+    pub fn iter_with(&self, mode: IterMode) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let snapshot = || {
+            let data = self.data.borrow();
+            data.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        };
         Iter {
             map: self,
             index: 0,
+            check: MutationCheck::new(&self.data),
+            mode: ModeState::new(mode, &self.data, snapshot),
+        }
+    }
+
+    /// Iterate over the entries in `self`, yielding the result of
+    /// applying `f` to each rather than a clone, so that neither `K`
+    /// nor `V` need be `Clone`. `f` runs under a read borrow, so all
+    /// mut-cells are read-only while it runs. As with `iter`, `self`
+    /// may be mutated between steps.
+    #[track_caller]
+    pub fn iter_map<R, F>(&self, f: F) -> IterMap<'_, K, V, F>
+    where
+        F: FnMut(&K, &V) -> R,
+    {
+        IterMap {
+            map: self,
+            index: 0,
+            f,
+            check: MutationCheck::new(&self.data),
         }
     }
 
@@ -115,50 +621,133 @@ where
     /// iteration (for example, by pushing or popping elements onto
     /// it). Doing so may lead to surprising results but is not
     /// undefined behavior in any way.
+    ///
+    /// In debug builds, such a mutation logs a warning.
+    #[track_caller]
     pub fn keys(&self) -> Keys<'_, K, V>
+    where
+        K: Clone,
+    {
+        self.keys_with(IterMode::Live)
+    }
+
+    /// Like `keys`, but with `mode` determining what happens if `self`
+    /// is mutated during the iteration.
+    #[track_caller]
+    pub fn keys_with(&self, mode: IterMode) -> Keys<'_, K, V>
     where
         K: Clone,
     {
         Keys {
             map: self,
             index: 0,
+            check: MutationCheck::new(&self.data),
+            mode: ModeState::new(mode, &self.data, || {
+                self.data.borrow().keys().cloned().collect()
+            }),
         }
     }
 }
 
-impl<K: Clone, V: Clone> Clone for MutMap<K, V>
+impl<K, V, S> MutMap<K, Arc<V>, S>
 where
-    K: Eq + Hash,
+    S: MapStore<K, Arc<V>>,
+{
+    /// Modifies the value for `key` in place with `f`, cloning it
+    /// first (with `Arc::make_mut`) if it is shared, e.g. with a
+    /// reader that fetched it with `get`. Returns false if there is no
+    /// such key. While `f` runs, all mut-cells are locked and
+    /// read-only; attempts to read from *this* map will find it
+    /// empty.
+    #[track_caller]
+    pub fn update_make_mut<Q>(&self, key: &Q, f: impl FnOnce(&mut V)) -> bool
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, Arc<V>>,
+        V: Clone,
+    {
+        self.with_checked_out(|data| match data.get_mut(key) {
+            Some(value) => {
+                f(Arc::make_mut(value));
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+impl<K, V, S> Clone for MutMap<K, V, S>
+where
+    S: MapStore<K, V> + Clone,
 {
     fn clone(&self) -> Self {
-        let map = self.data.borrow().clone();
-        MutMap::from(map.clone())
+        MutMap::from(self.data.borrow().clone())
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.copy_from(source);
     }
 }
 
-impl<K, V> std::iter::FromIterator<(K, V)> for MutMap<K, V>
+impl<K, V, S> std::iter::FromIterator<(K, V)> for MutMap<K, V, S>
 where
-    K: Eq + Hash,
+    S: MapStore<K, V> + std::iter::FromIterator<(K, V)>,
 {
+    #[track_caller]
     fn from_iter<T>(iter: T) -> Self
     where
         T: IntoIterator<Item = (K, V)>,
     {
-        let v: IndexMap<K, V> = iter.into_iter().collect();
+        let v: S = iter.into_iter().collect();
         MutMap::from(v)
     }
 }
 
-impl<K, V> Default for MutMap<K, V> {
+impl<K, V, S> Default for MutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    #[track_caller]
     fn default() -> Self {
-        Self::from(IndexMap::new())
+        Self::from(S::default())
     }
 }
 
-impl<K, V> From<IndexMap<K, V>> for MutMap<K, V> {
-    fn from(v: IndexMap<K, V>) -> MutMap<K, V> {
+impl<K, V, S> fmt::Debug for MutMap<K, V, S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.borrow().fmt(f)
+    }
+}
+
+/// Displays as `{k1: v1, k2: v2}`. See also `pretty`.
+impl<K: fmt::Display, V: fmt::Display> fmt::Display for MutMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        write!(f, "{{")?;
+        for (i, (key, value)) in data.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", key, value)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<K, V, S> From<S> for MutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    #[track_caller]
+    fn from(v: S) -> MutMap<K, V, S> {
         MutMap {
-            data: MCell::new(v),
+            data: MCell::new(v).sized(S::memory_usage),
+            entries: PhantomData,
+            limit: Limit::new(),
+            fingerprint: Fingerprint::new(),
         }
     }
 }
@@ -170,6 +759,8 @@ where
 {
     map: &'iter MutMap<K, V>,
     index: usize,
+    check: MutationCheck,
+    mode: ModeState<(K, V)>,
 }
 
 impl<'iter, K, V> Iterator for Iter<'iter, K, V>
@@ -180,18 +771,47 @@ where
     type Item = (K, V);
 
     fn next(&mut self) -> Option<(K, V)> {
+        if let Some(snapshot) = self.mode.snapshot(&self.map.data) {
+            return snapshot.next();
+        }
+        self.check.check();
         let (key, value) = self.map.get_index(self.index)?;
         self.index += 1;
         Some((key, value))
     }
 }
 
+pub struct IterMap<'iter, K, V, F> {
+    map: &'iter MutMap<K, V>,
+    index: usize,
+    f: F,
+    check: MutationCheck,
+}
+
+impl<'iter, K, V, R, F> Iterator for IterMap<'iter, K, V, F>
+where
+    F: FnMut(&K, &V) -> R,
+{
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        self.check.check();
+        let data = self.map.data.borrow();
+        let (key, value) = data.get_index(self.index)?;
+        let result = (self.f)(key, value);
+        self.index += 1;
+        Some(result)
+    }
+}
+
 pub struct Keys<'iter, K, V>
 where
     K: Eq + Hash + Clone,
 {
     map: &'iter MutMap<K, V>,
     index: usize,
+    check: MutationCheck,
+    mode: ModeState<K>,
 }
 
 impl<'iter, K, V> Iterator for Keys<'iter, K, V>
@@ -201,8 +821,30 @@ where
     type Item = K;
 
     fn next(&mut self) -> Option<K> {
+        if let Some(snapshot) = self.mode.snapshot(&self.map.data) {
+            return snapshot.next();
+        }
+        self.check.check();
         let key = self.map.get_key_index(self.index)?;
         self.index += 1;
         Some(key)
     }
 }
+
+/// Creates a `MutMap` (backed by an `IndexMap`) holding the given
+/// entries, in order. As with `insert`, a repeated key keeps its
+/// first position and its last value:
+///
+/// ```
+/// # use mutable::mutmap;
+/// let m = mutmap! { "a" => 1, "b" => 2, "a" => 3 };
+/// assert_eq!(m.iter().collect::<Vec<_>>(), vec![("a", 3), ("b", 2)]);
+/// ```
+#[macro_export]
+macro_rules! mutmap {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        <$crate::MutMap<_, _> as ::std::iter::FromIterator<_>>::from_iter(
+            ::std::vec![$(($key, $value)),*],
+        )
+    };
+}
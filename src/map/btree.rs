@@ -0,0 +1,98 @@
+//! Ordered queries for maps backed by a `BTreeMap`.
+
+use super::MutMap;
+use crate::iter_check::MutationCheck;
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+
+/// A `MutMap` whose entries are kept sorted by key, supporting range
+/// queries and sorted iteration. Create one with `default` or
+/// `collect`.
+pub type MutBTreeMap<K, V> = MutMap<K, V, BTreeMap<K, V>>;
+
+impl<K: Ord, V> MutMap<K, V, BTreeMap<K, V>> {
+    /// The entries whose keys fall within `range`, in order. They are
+    /// read under a single borrow, so they reflect one state of the
+    /// map.
+    pub fn range<Q, R>(&self, range: R) -> Vec<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        V: Clone,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let data = self.data.borrow();
+        data.range(range)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// The entry with the smallest key, if any.
+    pub fn first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let data = self.data.borrow();
+        let (k, v) = data.first_key_value()?;
+        Some((k.clone(), v.clone()))
+    }
+
+    /// The entry with the largest key, if any.
+    pub fn last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let data = self.data.borrow();
+        let (k, v) = data.last_key_value()?;
+        Some((k.clone(), v.clone()))
+    }
+
+    /// Iterate over the entries in key order, cloning them as we go.
+    /// (This is not called `iter`, as `MutMap::iter(&map)` would then
+    /// be ambiguous.)
+    /// Each step finds the entry after the previous key, so `self`
+    /// may be mutated during the iteration: entries inserted ahead of
+    /// the current position are visited, those behind it are not. In
+    /// debug builds, such a mutation logs a warning.
+    #[track_caller]
+    pub fn iter_sorted(&self) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            map: self,
+            last: None,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+}
+
+pub struct Iter<'iter, K, V> {
+    map: &'iter MutBTreeMap<K, V>,
+    last: Option<K>,
+    check: MutationCheck,
+}
+
+impl<'iter, K, V> Iterator for Iter<'iter, K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.check.check();
+        let data = self.map.data.borrow();
+        let (k, v) = match &self.last {
+            None => data.iter().next(),
+            Some(last) => data.range((Bound::Excluded(last), Bound::Unbounded)).next(),
+        }?;
+        self.last = Some(k.clone());
+        Some((k.clone(), v.clone()))
+    }
+}
@@ -0,0 +1,199 @@
+//! An adaptive map store for small maps. Up to `INLINE_CAPACITY`
+//! entries are kept inline and found by linear scan; beyond that,
+//! the entries move to an `IndexMap`. Tiny maps (e.g., per-node
+//! attribute tables) then need no allocation at all, which makes
+//! checking them out essentially free.
+
+use super::store::{MapLookup, MapStore};
+use crate::memory::MemoryUsage;
+use indexmap::{Equivalent, IndexMap};
+use std::fmt;
+use std::hash::Hash;
+
+/// The number of entries kept inline.
+pub const INLINE_CAPACITY: usize = 8;
+
+pub struct SmallMap<K, V> {
+    repr: Repr<K, V>,
+}
+
+enum Repr<K, V> {
+    /// The first `len` slots are occupied.
+    Inline {
+        len: usize,
+        entries: [Option<(K, V)>; INLINE_CAPACITY],
+    },
+    Large(IndexMap<K, V>),
+}
+
+impl<K, V> SmallMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True if the entries are stored inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    /// Iterate over the entries, in insertion order (modulo removals,
+    /// which -- as with `IndexMap::remove` -- move the last entry
+    /// into the vacated slot).
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let (inline, large) = match &self.repr {
+            Repr::Inline { len, entries } => (Some(entries[..*len].iter()), None),
+            Repr::Large(map) => (None, Some(map.iter())),
+        };
+        let inline = inline.into_iter().flatten().map(|entry| {
+            let (k, v) = entry.as_ref().unwrap();
+            (k, v)
+        });
+        inline.chain(large.into_iter().flatten())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SmallMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl<K, V> Default for SmallMap<K, V> {
+    fn default() -> Self {
+        SmallMap {
+            repr: Repr::Inline {
+                len: 0,
+                entries: std::array::from_fn(|_| None),
+            },
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for SmallMap<K, V> {
+    fn clone(&self) -> Self {
+        let repr = match &self.repr {
+            Repr::Inline { len, entries } => Repr::Inline {
+                len: *len,
+                entries: entries.clone(),
+            },
+            Repr::Large(map) => Repr::Large(map.clone()),
+        };
+        SmallMap { repr }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SmallMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> std::iter::FromIterator<(K, V)> for SmallMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = SmallMap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<K, V> MapStore<K, V> for SmallMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { len, .. } => *len,
+            Repr::Large(map) => map.len(),
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (len, entries) = match &mut self.repr {
+            Repr::Inline { len, entries } => (len, entries),
+            Repr::Large(map) => return map.insert(key, value),
+        };
+        for (k, v) in entries[..*len].iter_mut().flatten() {
+            if *k == key {
+                return Some(std::mem::replace(v, value));
+            }
+        }
+        if *len < INLINE_CAPACITY {
+            entries[*len] = Some((key, value));
+            *len += 1;
+            return None;
+        }
+        let mut map: IndexMap<K, V> = entries.iter_mut().filter_map(Option::take).collect();
+        map.insert(key, value);
+        self.repr = Repr::Large(map);
+        None
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        match &self.repr {
+            Repr::Inline { len, .. } => MemoryUsage {
+                len: *len,
+                capacity: INLINE_CAPACITY,
+                heap_bytes: 0,
+            },
+            Repr::Large(map) => MemoryUsage::of_index_map(map),
+        }
+    }
+}
+
+impl<K, V, Q> MapLookup<Q, V> for SmallMap<K, V>
+where
+    K: Eq + Hash,
+    Q: ?Sized + Hash + Equivalent<K>,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        match &self.repr {
+            Repr::Inline { len, entries } => entries[..*len]
+                .iter()
+                .flatten()
+                .find(|(k, _)| key.equivalent(k))
+                .map(|(_, v)| v),
+            Repr::Large(map) => map.get(key),
+        }
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        match &mut self.repr {
+            Repr::Inline { len, entries } => entries[..*len]
+                .iter_mut()
+                .flatten()
+                .find(|(k, _)| key.equivalent(k))
+                .map(|(_, v)| v),
+            Repr::Large(map) => map.get_mut(key),
+        }
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        let (len, entries) = match &mut self.repr {
+            Repr::Inline { len, entries } => (len, entries),
+            Repr::Large(map) => return map.remove_entry(key),
+        };
+        let index = entries[..*len]
+            .iter()
+            .position(|entry| key.equivalent(&entry.as_ref().unwrap().0))?;
+        *len -= 1;
+        entries.swap(index, *len);
+        entries[*len].take()
+    }
+}
@@ -0,0 +1,166 @@
+//! The backing stores a `MutMap` can be built on.
+
+use crate::memory::MemoryUsage;
+use indexmap::{Equivalent, IndexMap};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// A map that can back a `MutMap`.
+pub trait MapStore<K, V>: Default {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    /// The footprint of the store. By default, this counts just the
+    /// entries themselves.
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            len: self.len(),
+            capacity: self.len(),
+            heap_bytes: self.len() * std::mem::size_of::<(K, V)>(),
+        }
+    }
+}
+
+/// Lookup of a store's values by a key of type `Q`. This is separate
+/// from `MapStore` because each store accepts different kinds of
+/// borrowed keys (e.g., `IndexMap` accepts any `Equivalent` type).
+pub trait MapLookup<Q: ?Sized, V> {
+    /// The type of the stored keys.
+    type Key;
+
+    fn get(&self, key: &Q) -> Option<&V>;
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V>;
+
+    fn remove(&mut self, key: &Q) -> Option<V>;
+
+    /// Like `remove`, but also returns the stored key.
+    fn remove_entry(&mut self, key: &Q) -> Option<(Self::Key, V)>;
+}
+
+impl<K, V> MapStore<K, V> for IndexMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn len(&self) -> usize {
+        IndexMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        IndexMap::insert(self, key, value)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_index_map(self)
+    }
+}
+
+impl<K, V, Q> MapLookup<Q, V> for IndexMap<K, V>
+where
+    K: Eq + Hash,
+    Q: ?Sized + Hash + Equivalent<K>,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        IndexMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        IndexMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        IndexMap::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        IndexMap::remove_entry(self, key)
+    }
+}
+
+impl<K, V, H> MapStore<K, V> for HashMap<K, V, H>
+where
+    K: Eq + Hash,
+    H: BuildHasher + Default,
+{
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_hash_map(self)
+    }
+}
+
+impl<K, V, H, Q> MapLookup<Q, V> for HashMap<K, V, H>
+where
+    K: Eq + Hash + Borrow<Q>,
+    H: BuildHasher,
+    Q: ?Sized + Eq + Hash,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        HashMap::remove_entry(self, key)
+    }
+}
+
+impl<K, V> MapStore<K, V> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+}
+
+impl<K, V, Q> MapLookup<Q, V> for BTreeMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        BTreeMap::remove_entry(self, key)
+    }
+}
@@ -22,3 +22,229 @@ fn iter1() {
         vec![Some((22, 23)), Some((44, 45)), Some((66, 67))]
     );
 }
+
+#[test]
+fn btree_store() {
+    use std::collections::BTreeMap;
+
+    let mut m: MutMap<String, u32, BTreeMap<String, u32>> = MutMap::default();
+    m.insert("b".to_string(), 2);
+    m.insert("a".to_string(), 1);
+    assert_eq!(m.get("a"), Some(1));
+    assert_eq!(m.remove("b"), Some(2));
+    assert_eq!(m.len(), 1);
+    assert_eq!(format!("{:?}", m), r#"{"a": 1}"#);
+}
+
+#[test]
+fn hash_store() {
+    use std::collections::HashMap;
+
+    let m: MutMap<u32, u32, HashMap<u32, u32>> = (0..10).map(|i| (i, i * i)).collect();
+    assert_eq!(m.get(&3), Some(9));
+    assert_eq!(m.into_inner().len(), 10);
+}
+
+#[test]
+fn small_store() {
+    let mut m: MutSmallMap<u32, u32> = MutMap::default();
+    for i in 0..8 {
+        m.insert(i, i);
+    }
+    assert_eq!(m.insert(3, 30), Some(3));
+    assert!(m.with_borrowed(|s| s.is_inline()));
+    assert_eq!(m.remove(&0), Some(0));
+    assert_eq!(
+        format!("{:?}", m),
+        "{7: 7, 1: 1, 2: 2, 3: 30, 4: 4, 5: 5, 6: 6}"
+    );
+
+    m.insert(8, 8);
+    m.insert(9, 9);
+    assert!(!m.with_borrowed(|s| s.is_inline()));
+    assert_eq!(m.len(), 9);
+    assert_eq!(m.get(&3), Some(30));
+    assert_eq!(m.remove(&9), Some(9));
+    assert_eq!(m.get(&9), None);
+}
+
+#[test]
+fn update_make_mut() {
+    use std::sync::Arc;
+
+    let m: MutMap<&str, Arc<Vec<u32>>> = MutMap::new();
+    m.insert("a", Arc::new(vec![1]));
+    let reader = m.get("a").unwrap();
+
+    assert!(m.update_make_mut("a", |v| v.push(2)));
+    assert!(!m.update_make_mut("b", |v| v.push(2)));
+    assert_eq!(*reader, vec![1]);
+    assert_eq!(*m.get("a").unwrap(), vec![1, 2]);
+
+    // Unshared values are modified without a copy.
+    let before = Arc::as_ptr(&m.get("a").unwrap());
+    assert!(m.update_make_mut("a", |v| v.push(3)));
+    assert_eq!(Arc::as_ptr(&m.get("a").unwrap()), before);
+}
+
+#[test]
+fn remove_index_range() {
+    let m: MutMap<u32, &str> = vec![(1, "a"), (2, "b"), (3, "c"), (4, "d")]
+        .into_iter()
+        .collect();
+    assert_eq!(m.remove_index_range(0..2), vec![(1, "a"), (2, "b")]);
+    assert_eq!(m.keys().collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(m.get(&4), Some("d"));
+}
+
+#[test]
+fn apply_diff() {
+    let mut primary: MutMap<&str, u32> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    let replica = primary.clone();
+    let snapshot = primary.clone().into_inner();
+
+    primary.insert("b", 20);
+    primary.insert("d", 4);
+    primary.remove("a");
+
+    replica.apply(primary.diff(&snapshot));
+    assert!(primary.diff(&replica.clone().into_inner()).is_empty());
+    assert_eq!(replica.len(), 3);
+    assert_eq!(replica.get("b"), Some(20));
+}
+
+#[test]
+fn element_hooks() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut m: MutMap<&str, u32> = MutMap::new();
+    let by_value: Rc<MutMap<u32, &str>> = Rc::new(MutMap::new());
+    let b = by_value.clone();
+    m.on_insert(move |k, v| {
+        b.insert(*v, *k);
+    });
+    let b = by_value.clone();
+    let removed = Rc::new(RefCell::new(vec![]));
+    let r = removed.clone();
+    m.on_remove(move |k, v| {
+        r.borrow_mut().push(*k);
+        b.with_checked_out(|data| data.swap_remove(v));
+    });
+
+    m.insert("a", 1);
+    m.insert("b", 2);
+    m.insert("c", 3);
+    assert_eq!(m.remove("a"), Some(1));
+    assert_eq!(m.remove("z"), None);
+    m.remove_index_range(0..1);
+    // `remove` moved "c" into the place of "a".
+    assert_eq!(*removed.borrow(), vec!["a", "c"]);
+    assert_eq!(by_value.keys().collect::<Vec<_>>(), vec![2]);
+
+    let snapshot = m.clone().into_inner();
+    let mut other = m.clone();
+    other.insert("d", 4);
+    other.remove("b");
+    m.apply(other.diff(&snapshot));
+    assert_eq!(*removed.borrow(), vec!["a", "c", "b"]);
+    assert_eq!(by_value.get(&4), Some("d"));
+    assert_eq!(by_value.len(), 1);
+}
+
+#[test]
+fn iter_map() {
+    struct NoClone(u32);
+
+    let m = MutMap::new();
+    m.insert("a", NoClone(1));
+    m.insert("b", NoClone(2));
+    assert_eq!(
+        m.iter_map(|k, v| format!("{}={}", k, v.0))
+            .collect::<Vec<_>>(),
+        vec!["a=1", "b=2"]
+    );
+}
+
+#[test]
+fn copy_from() {
+    let mut dst: MutMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let capacity = dst.memory_usage().capacity;
+    let src: MutMap<u32, u32> = vec![(1, 10)].into_iter().collect();
+
+    dst.copy_from(&src);
+    assert_eq!(dst.memory_usage().capacity, capacity);
+    assert_eq!(dst.iter().collect::<Vec<_>>(), vec![(1, 10)]);
+
+    dst.copy_from(&dst);
+    assert_eq!(dst.len(), 1);
+
+    dst.clone_from(&MutMap::new());
+    assert!(dst.is_empty());
+}
+
+#[test]
+fn btree_range() {
+    let m: MutBTreeMap<u32, &str> = vec![(5, "e"), (1, "a"), (3, "c")].into_iter().collect();
+    m.insert(4, "d");
+    assert_eq!(m.range(2..5), vec![(3, "c"), (4, "d")]);
+    assert_eq!(m.range(..=1), vec![(1, "a")]);
+    assert_eq!(m.first(), Some((1, "a")));
+    assert_eq!(m.last(), Some((5, "e")));
+    assert_eq!(m.get(&3), Some("c"));
+}
+
+#[test]
+fn btree_iter() {
+    let m: MutBTreeMap<String, u32> = MutBTreeMap::default();
+    m.insert("b".to_string(), 2);
+    m.insert("a".to_string(), 1);
+
+    // An entry inserted ahead of the current position is visited.
+    let keys: Vec<String> = m
+        .iter_sorted()
+        .map(|(k, _)| {
+            if k == "a" {
+                m.insert("c".to_string(), 3);
+            }
+            k
+        })
+        .collect();
+    assert_eq!(keys, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn mutmap_macro() {
+    let empty: MutMap<u8, u8> = crate::mutmap! {};
+    assert!(empty.is_empty());
+    let m = crate::mutmap! {
+        "x" => vec![1],
+        "y" => vec![],
+    };
+    assert_eq!(m.get("x"), Some(vec![1]));
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn iter_modes() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let m: MutMap<u32, u32> = (0..3).map(|i| (i, i)).collect();
+    let snapshot: Vec<_> = m
+        .keys_with(IterMode::SnapshotAtStart)
+        .inspect(|&k| {
+            m.insert(k + 10, k);
+        })
+        .collect();
+    assert_eq!(snapshot, vec![0, 1, 2]);
+    assert_eq!(m.len(), 6);
+
+    let entries: Vec<_> = m.iter_with(IterMode::SnapshotAtStart).take(2).collect();
+    assert_eq!(entries, vec![(0, 0), (1, 1)]);
+
+    let mut iter = m.iter_with(IterMode::PanicOnMutation);
+    assert_eq!(iter.next(), Some((0, 0)));
+    m.insert(0, 5);
+    let result = catch_unwind(AssertUnwindSafe(|| iter.next()));
+    assert!(result.is_err());
+}
@@ -1,44 +1,139 @@
+use crate::memory::MemoryUsage;
 use std::cell::Cell;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::panic::RefUnwindSafe;
+
+#[macro_use]
+mod local;
 
 mod borrow;
 mod borrow_mut;
 mod check_out;
-mod lock;
+mod id;
+pub(crate) mod lock;
+mod poison;
+pub(crate) mod track;
+
+pub(crate) use borrow::ShareGuard;
+pub(crate) use borrow_mut::{borrow_mut2, borrow_mut3};
 
 /// Like a std cell, but supports borrow operations. The key thing is
 /// that these operations simultaneously lock/unlock **all the cells
-/// accessible to this thread**.  So if you do `cell.borrow()`, then
-/// *all* MCell's are borrowed.
+/// accessible to this thread** (in the same lock domain, see the
+/// crate's `domain` module).  So if you do `cell.borrow()`, then *all*
+/// MCell's of its domain are borrowed.
 ///
 /// It exposes a **safe interface**.
 pub(crate) struct MCell<T> {
     data: Cell<T>,
+    id: id::LazyId,
+
+    /// Reports the size of the data to the debug registry.
+    #[cfg(feature = "debug")]
+    size: Option<fn(&T) -> MemoryUsage>,
 }
 
+/// Sharing a cell across `catch_unwind` is safe: every guard releases
+/// its part of the thread lock when dropped during unwinding, and a
+/// cell always holds a valid `T` afterwards (a checked-out value is
+/// put back). A closure that panics halfway through modifying the
+/// contents does leave its partial modifications behind, just as
+/// with a `Mutex` whose poisoning is ignored.
+impl<T: RefUnwindSafe> RefUnwindSafe for MCell<T> {}
+
 impl<T> MCell<T> {
+    #[track_caller]
     pub(crate) fn new(data: T) -> Self {
-        MCell {
+        let cell = MCell {
             data: Cell::new(data),
+            id: id::LazyId::new(),
+            #[cfg(feature = "debug")]
+            size: None,
+        };
+        #[cfg(feature = "debug")]
+        crate::debug::register(
+            cell.id(),
+            std::any::type_name::<T>(),
+            std::panic::Location::caller(),
+        );
+        cell
+    }
+
+    /// Use `size` to report the footprint of the data (for
+    /// collections) to the debug registry. Without the `debug`
+    /// feature, this does nothing.
+    #[cfg_attr(not(feature = "debug"), allow(unused_mut, unused_variables))]
+    pub(crate) fn sized(mut self, size: fn(&T) -> MemoryUsage) -> Self {
+        #[cfg(feature = "debug")]
+        {
+            crate::debug::update_size(self.id(), size(self.data.get_mut()));
+            self.size = Some(size);
+        }
+        self
+    }
+
+    /// Report the current size of the data to the debug registry.
+    #[cfg(feature = "debug")]
+    fn update_debug_size(&self) {
+        if let Some(size) = self.size {
+            lock::acquire_read_lock(&self.id);
+            // Safe: we hold the read lock.
+            let n = size(unsafe { &*self.data.as_ptr() });
+            lock::release_read_lock(self.id.domain());
+            crate::debug::update_size(self.id(), n);
         }
     }
 
+    /// Access the data directly; no lock is needed, as `&mut self`
+    /// guarantees that no guard is live.
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.id.bump_version();
+        self.data.get_mut()
+    }
+
+    /// A pointer to the data, for the unchecked access of the `raw`
+    /// module; dereferencing it is subject to the obligations spelled
+    /// out there.
+    pub(crate) fn as_ptr(&self) -> *mut T {
+        self.data.as_ptr()
+    }
+
+    /// The lock domain of this cell.
+    pub(crate) fn domain(&self) -> lock::Domain {
+        self.id.domain()
+    }
+
+    pub(crate) fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    #[track_caller]
     pub(crate) fn take(&self) -> T
     where
         T: Default,
     {
-        lock::assert_unlocked();
-        self.data.take()
+        poison::check(&self.id);
+        lock::assert_unlocked(&self.id);
+        let data = self.data.take();
+        self.mutated();
+        data
     }
 
+    #[track_caller]
     pub(crate) fn set(&self, value: T) {
-        lock::assert_unlocked();
-        self.data.set(value)
+        poison::check(&self.id);
+        lock::assert_unlocked(&self.id);
+        self.data.set(value);
+        self.mutated();
     }
 
+    #[track_caller]
     pub(crate) fn replace(&self, value: T) -> T {
-        lock::assert_unlocked();
-        self.data.replace(value)
+        poison::check(&self.id);
+        lock::assert_unlocked(&self.id);
+        let old = self.data.replace(value);
+        self.mutated();
+        old
     }
 }
@@ -4,17 +4,41 @@ impl<T> MCell<T> {
     /// Acquire shared access to this mcell -- but at the cost that
     /// the current thread cannot mutate **any other mcells** while
     /// the borrow is active.
+    #[track_caller]
     pub(crate) fn borrow(&self) -> ShareGuard<'_, T> {
-        lock::acquire_read_lock();
+        poison::check(&self.id);
+        lock::acquire_read_lock(&self.id);
+        track::record_read(&self.id);
 
         // Unsafe proof obligation: we must hold the read-lock.
         unsafe { ShareGuard::new(self, self.data.as_ptr()) }
     }
 }
 
+impl<T> MCell<T> {
+    /// Acquire the read lock for a guard that releases it itself
+    /// (with `lock::release_read_lock`), rather than via `ShareGuard`.
+    #[track_caller]
+    pub(crate) fn acquire_shared(&self) {
+        poison::check(&self.id);
+        lock::acquire_read_lock(&self.id);
+        track::record_read(&self.id);
+    }
+
+    /// Unsafe proof obligation: the read lock must be held for as long
+    /// as the result is in use.
+    pub(crate) unsafe fn shared_ref(&self) -> &T {
+        lock::debug_assert_read_locked(self.domain());
+        &*self.data.as_ptr()
+    }
+}
+
 pub(crate) struct ShareGuard<'me, T> {
     data: &'me T,
 
+    /// The domain whose read lock we hold.
+    domain: lock::Domain,
+
     /// Subtle: Dummy field so that `ShareGuard` is not considered `Send`.
     _thread_local: *const (),
 }
@@ -24,19 +48,42 @@ impl<'me, T> ShareGuard<'me, T> {
     ///
     /// Unsafe proof obligation:
     /// - the read lock must be held (and delegated to us), and
-    /// - `data` must come from `_cell`.
-    unsafe fn new(_cell: &'me MCell<T>, data: *const T) -> Self {
-        lock::debug_assert_read_locked();
+    /// - `data` must come from `cell`.
+    unsafe fn new(cell: &'me MCell<T>, data: *const T) -> Self {
+        lock::debug_assert_read_locked(cell.domain());
 
         // The write lock is held so long as we exist, so will retain
         // unique access to `*data`. Moreover, we will assign it a
-        // lifetime of `'me` which is tied to the cell `_cell`, so the
+        // lifetime of `'me` which is tied to the cell `cell`, so the
         // data will not be deinitialized.
         ShareGuard {
             data: &*data,
+            domain: cell.domain(),
             _thread_local: std::ptr::null(),
         }
     }
+
+    /// Narrow the guard to a part of the data (or `None`, releasing
+    /// the lock). The lock remains held by the returned guard.
+    pub(crate) fn filter_map<U>(
+        self,
+        op: impl FnOnce(&'me T) -> Option<&'me U>,
+    ) -> Option<ShareGuard<'me, U>> {
+        let data = op(self.data);
+        match data {
+            Some(data) => {
+                // The new guard takes over our read lock.
+                let domain = self.domain;
+                std::mem::forget(self);
+                Some(ShareGuard {
+                    data,
+                    domain,
+                    _thread_local: std::ptr::null(),
+                })
+            }
+            None => None,
+        }
+    }
 }
 
 impl<'me, T> Deref for ShareGuard<'me, T> {
@@ -49,6 +96,6 @@ impl<'me, T> Deref for ShareGuard<'me, T> {
 
 impl<'me, T> Drop for ShareGuard<'me, T> {
     fn drop(&mut self) {
-        lock::release_read_lock();
+        lock::release_read_lock(self.domain);
     }
 }
@@ -4,8 +4,10 @@ impl<T> MCell<T> {
     /// Acquire mutable access to this mcell -- but at the cost that
     /// the current thread cannot access (read or write) **any other
     /// mcells** while the borrow is active.
+    #[track_caller]
     pub(crate) fn borrow_mut(&self) -> MutGuard<'_, T> {
-        lock::acquire_write_lock();
+        poison::check(&self.id);
+        lock::acquire_write_lock(&self.id);
 
         // Proof obligation: we must hold the write-lock.
         unsafe { MutGuard::new(self, self.data.as_ptr()) }
@@ -13,7 +15,9 @@ impl<T> MCell<T> {
 }
 
 pub(crate) struct MutGuard<'me, T> {
+    cell: &'me MCell<T>,
     data: &'me mut T,
+    poison: poison::Flag,
 
     /// Subtle: Dummy field so that `MutGuard` is not considered `Send`.
     _thread_local: *const (),
@@ -24,16 +28,18 @@ impl<'me, T> MutGuard<'me, T> {
     ///
     /// Unsafe proof obligation:
     /// - the write lock must be held (and delegated to us), and
-    /// - `data` must come from `_cell`.
-    unsafe fn new(_cell: &'me MCell<T>, data: *mut T) -> Self {
-        lock::debug_assert_write_locked();
+    /// - `data` must come from `cell`.
+    unsafe fn new(cell: &'me MCell<T>, data: *mut T) -> Self {
+        lock::debug_assert_write_locked(cell.domain());
 
         // The write lock is held so long as we exist, so will retain
         // unique access to `*data`. Moreover, we will assign it a
-        // lifetime of `'me` which is tied to the cell `_cell`, so the
+        // lifetime of `'me` which is tied to the cell `cell`, so the
         // data will not be deinitialized.
         MutGuard {
+            cell,
             data: &mut *data,
+            poison: poison::Flag::new(),
             _thread_local: std::ptr::null(),
         }
     }
@@ -55,6 +61,127 @@ impl<'me, T> DerefMut for MutGuard<'me, T> {
 
 impl<'me, T> Drop for MutGuard<'me, T> {
     fn drop(&mut self) {
-        lock::release_write_lock();
+        lock::release_write_lock(self.cell.domain());
+        self.poison.done(&self.cell.id);
+        self.cell.mutated();
+    }
+}
+
+/// Runs `op` with mutable access to the data of both `a` and `b`,
+/// under the write lock (of each of their domains). Panics if `a` and
+/// `b` are the same cell.
+#[track_caller]
+pub(crate) fn borrow_mut2<A, B, R>(
+    a: &MCell<A>,
+    b: &MCell<B>,
+    op: impl FnOnce(&mut A, &mut B) -> R,
+) -> R {
+    assert_distinct(&[addr(a), addr(b)]);
+    poison::check(&a.id);
+    poison::check(&b.id);
+    let _guard = MultiMutGuard::acquire([&a.id, &b.id]);
+
+    // Safe: we hold the write lock, and the cells are distinct (and,
+    // as no guard can be live, neither is inside the other), so the
+    // references are unique.
+    let (a, b) = unsafe { (&mut *a.data.as_ptr(), &mut *b.data.as_ptr()) };
+    op(a, b)
+}
+
+/// The three-cell version of `borrow_mut2`.
+#[track_caller]
+pub(crate) fn borrow_mut3<A, B, C, R>(
+    a: &MCell<A>,
+    b: &MCell<B>,
+    c: &MCell<C>,
+    op: impl FnOnce(&mut A, &mut B, &mut C) -> R,
+) -> R {
+    assert_distinct(&[addr(a), addr(b), addr(c)]);
+    poison::check(&a.id);
+    poison::check(&b.id);
+    poison::check(&c.id);
+    let _guard = MultiMutGuard::acquire([&a.id, &b.id, &c.id]);
+
+    // Safe: as in `borrow_mut2`.
+    let (a, b, c) = unsafe {
+        (
+            &mut *a.data.as_ptr(),
+            &mut *b.data.as_ptr(),
+            &mut *c.data.as_ptr(),
+        )
+    };
+    op(a, b, c)
+}
+
+fn addr<T>(cell: &MCell<T>) -> *const () {
+    cell as *const MCell<T> as *const ()
+}
+
+fn assert_distinct(addrs: &[*const ()]) {
+    for (i, a) in addrs.iter().enumerate() {
+        assert!(
+            !addrs[i + 1..].contains(a),
+            "cannot mutably borrow the same cell twice"
+        );
+    }
+}
+
+/// Holds the write lock on behalf of several cells, which may be in
+/// different domains. When dropped, it releases the locks and then
+/// reports each cell as mutated.
+struct MultiMutGuard<'me, const N: usize> {
+    ids: [&'me id::LazyId; N],
+
+    /// The number of `ids` whose domain has been locked (by them or
+    /// by an earlier id in the same domain).
+    acquired: usize,
+
+    poison: poison::Flag,
+
+    /// Subtle: Dummy field so that `MultiMutGuard` is not considered `Send`.
+    _thread_local: *const (),
+}
+
+impl<'me, const N: usize> MultiMutGuard<'me, N> {
+    #[track_caller]
+    fn acquire(ids: [&'me id::LazyId; N]) -> Self {
+        let mut guard = MultiMutGuard {
+            ids,
+            acquired: 0,
+            poison: poison::Flag::new(),
+            _thread_local: std::ptr::null(),
+        };
+        // If a domain turns out to be locked, the violation panics and
+        // the guard releases the domains locked so far.
+        for i in 0..N {
+            if guard.first_in_domain(i) {
+                lock::acquire_write_lock(guard.ids[i]);
+            }
+            guard.acquired = i + 1;
+        }
+        guard
+    }
+
+    /// True if `ids[i]` is the first of the ids in its domain.
+    fn first_in_domain(&self, i: usize) -> bool {
+        let domain = self.ids[i].domain();
+        self.ids[..i].iter().all(|id| id.domain() != domain)
+    }
+}
+
+impl<const N: usize> Drop for MultiMutGuard<'_, N> {
+    fn drop(&mut self) {
+        for i in 0..self.acquired {
+            if self.first_in_domain(i) {
+                lock::release_write_lock(self.ids[i].domain());
+            }
+        }
+        if self.acquired < N {
+            return;
+        }
+        for id in &self.ids {
+            self.poison.done(id);
+            id.mutated();
+        }
     }
 }
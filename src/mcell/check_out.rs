@@ -5,9 +5,11 @@ impl<T: Default> MCell<T> {
     /// other cells to read-only access. Any attempt to read this
     /// particular cell in that time will encounter the `T::Default`
     /// value.
+    #[track_caller]
     pub(crate) fn check_out(&self) -> CheckOutGuard<'_, T> {
-        lock::assert_unlocked();
-        lock::acquire_read_lock();
+        poison::check(&self.id);
+        lock::assert_unlocked(&self.id);
+        lock::acquire_read_lock(&self.id);
         let data = self.data.take();
 
         // Unsafe proof obligation: we acquired read-lock above.
@@ -19,12 +21,16 @@ impl<T: Default> MCell<T> {
     /// particular cell in that time will encounter the `T::Default`
     /// value. **This variant does not restore `self.data` on panic,
     /// but simply leaves the default value.**
+    #[track_caller]
     pub(crate) fn check_out_not_panic_safe<R>(&self, closure: impl FnOnce(&mut T) -> R) -> R {
-        lock::assert_unlocked();
+        poison::check(&self.id);
+        lock::assert_unlocked(&self.id);
         let mut data = self.data.take();
-        let _cell = self.borrow();
+        let lock = self.borrow();
         let result = closure(&mut data);
         self.data.set(data);
+        drop(lock);
+        self.mutated();
         result
     }
 }
@@ -32,6 +38,7 @@ impl<T: Default> MCell<T> {
 pub(crate) struct CheckOutGuard<'me, T: Default> {
     data: T,
     cell: &'me MCell<T>,
+    poison: poison::Flag,
 }
 
 impl<'me, T: Default> CheckOutGuard<'me, T> {
@@ -40,13 +47,17 @@ impl<'me, T: Default> CheckOutGuard<'me, T> {
     /// Unsafe proof obligation:
     /// - the read lock must be held (and delegated to us).
     unsafe fn new(cell: &'me MCell<T>, data: T) -> Self {
-        lock::debug_assert_read_locked();
+        lock::debug_assert_read_locked(cell.domain());
 
         // The write lock is held so long as we exist, so will retain
         // unique access to `*data`. Moreover, we will assign it a
         // lifetime of `'me` which is tied to the cell `_cell`, so the
         // data will not be deinitialized.
-        CheckOutGuard { cell, data }
+        CheckOutGuard {
+            cell,
+            data,
+            poison: poison::Flag::new(),
+        }
     }
 }
 
@@ -66,7 +77,8 @@ impl<'me, T: Default> DerefMut for CheckOutGuard<'me, T> {
 
 impl<'me, T: Default> Drop for CheckOutGuard<'me, T> {
     fn drop(&mut self) {
-        lock::release_read_lock();
+        lock::release_read_lock(self.cell.domain());
+        self.poison.done(&self.cell.id);
 
         // Annoyingly, drop has an `&mut self` type that forbids us
         // from taking ownership of `self.data`, so swap the data back.
@@ -76,6 +88,8 @@ impl<'me, T: Default> Drop for CheckOutGuard<'me, T> {
         // operations with it and it never escapes. Further, there
         // should be no other extant `&mut` references to its interior
         // (hmm, double check that?). So should be fine.
-        std::mem::swap(&mut self.data, unsafe { &mut *self.cell.data.as_ptr() })
+        std::mem::swap(&mut self.data, unsafe { &mut *self.cell.data.as_ptr() });
+
+        self.cell.mutated();
     }
 }
@@ -0,0 +1,146 @@
+use super::*;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// Source of cell ids; these are unique across all threads so that a
+/// cell keeps a meaningful id even if it is sent elsewhere.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The identity of an mcell, assigned lazily the first time some
+/// subsystem (such as `observe`) needs to refer to the cell. Cells
+/// that are never referred to in this way never pay for an id.
+///
+/// This is a separate type (rather than a field of `MCell`) so that
+/// `MCell` itself need not implement `Drop`.
+pub(crate) struct LazyId {
+    id: Cell<u64>,
+
+    /// The lock domain of the cell, fixed when it is created. It is
+    /// kept here because every lock operation is passed the id.
+    domain: lock::Domain,
+
+    /// The number of mutations of the cell; see `MCell::version`.
+    /// Unlike change tracking, this needs no id.
+    version: Cell<u64>,
+}
+
+impl LazyId {
+    pub(super) fn new() -> Self {
+        LazyId {
+            id: Cell::new(0),
+            domain: lock::current_domain(),
+            version: Cell::new(0),
+        }
+    }
+
+    pub(super) fn domain(&self) -> lock::Domain {
+        self.domain
+    }
+
+    /// The id, if one has been assigned.
+    pub(super) fn get(&self) -> Option<u64> {
+        match self.id.get() {
+            0 => None,
+            id => Some(id),
+        }
+    }
+
+    pub(super) fn get_or_assign(&self) -> u64 {
+        match self.get() {
+            Some(id) => id,
+            None => {
+                let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+                self.id.set(id);
+                id
+            }
+        }
+    }
+}
+
+impl LazyId {
+    pub(super) fn version(&self) -> u64 {
+        self.version.get()
+    }
+
+    pub(super) fn bump_version(&self) {
+        self.version.set(self.version.get().wrapping_add(1));
+    }
+
+    /// See `MCell::mutated`.
+    pub(super) fn mutated(&self) {
+        self.bump_version();
+        if let Some(id) = self.get() {
+            track::record_change(id);
+            if !std::thread::panicking() {
+                crate::observe::notify(id);
+            }
+        }
+    }
+
+    /// Discards the state that the current thread keeps for this id
+    /// (subscriptions and the like), and unassigns it.
+    fn reset(&self) {
+        if let Some(id) = self.get() {
+            track::forget(id);
+            crate::observe::forget(id);
+            poison::forget(id);
+            #[cfg(feature = "debug")]
+            crate::debug::forget(id);
+            self.id.set(0);
+        }
+    }
+}
+
+impl Drop for LazyId {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+impl<T> MCell<T> {
+    /// The id of this cell, assigning one if needed.
+    pub(crate) fn id(&self) -> u64 {
+        self.id.get_or_assign()
+    }
+
+    /// The id of this cell, if one has been assigned.
+    pub(crate) fn existing_id(&self) -> Option<u64> {
+        self.id.get()
+    }
+
+    /// The number of times this cell has been mutated (through the
+    /// lock, or `get_mut`), wrapping on overflow.
+    pub(crate) fn version(&self) -> u64 {
+        self.id.version()
+    }
+
+    /// Prepares the cell to move to another thread: see
+    /// `send::Handoff`.
+    pub(crate) fn detach(&self) {
+        self.id.reset();
+    }
+
+    /// Undoes `detach`, on the receiving thread.
+    #[track_caller]
+    pub(crate) fn attach(&self) {
+        #[cfg(feature = "debug")]
+        {
+            crate::debug::register(
+                self.id(),
+                std::any::type_name::<T>(),
+                std::panic::Location::caller(),
+            );
+            self.update_debug_size();
+        }
+    }
+
+    /// Invoked once a mutation of this cell is complete and the
+    /// thread lock has been released.
+    pub(super) fn mutated(&self) {
+        self.id.mutated();
+        #[cfg(feature = "debug")]
+        if !std::thread::panicking() {
+            self.update_debug_size();
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Storage for the per-thread state that every cell access touches
+//! (the thread lock, and the checks for tracking and poisoning).
+//!
+//! Normally this is a `thread_local!`. On wasm without the `atomics`
+//! target feature (e.g., `wasm32-unknown-unknown`), there is only one
+//! thread, and the thread-local access is pure overhead: there the
+//! state is kept in plain statics instead.
+
+/// Declares per-thread state, like `thread_local!`, except that only
+/// `const` initializers are supported. Accessed with `with`, as a
+/// thread-local is.
+macro_rules! thread_state {
+    ($($(#[$attr:meta])* static $name:ident: $t:ty = const { $init:expr };)*) => {
+        #[cfg(not(all(target_arch = "wasm32", not(target_feature = "atomics"))))]
+        thread_local! {
+            $($(#[$attr])* static $name: $t = const { $init };)*
+        }
+
+        $(
+            #[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+            $(#[$attr])*
+            static $name: crate::mcell::local::Static<$t> = crate::mcell::local::Static::new($init);
+        )*
+    };
+}
+
+/// Per-thread state on a target with a single thread.
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+pub(crate) struct Static<T>(T);
+
+// Safe: without the `atomics` target feature, wasm has no threads, so
+// the state is never accessed from two threads.
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+unsafe impl<T> Sync for Static<T> {}
+
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+impl<T> Static<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Static(value)
+    }
+
+    pub(crate) fn with<R>(&'static self, op: impl FnOnce(&T) -> R) -> R {
+        op(&self.0)
+    }
+}
@@ -1,67 +1,263 @@
 //! The thread-lock lock used by mcell in its borrow/check-out operations.
+//!
+//! Each lock domain (see the crate's `domain` module) has its own
+//! lock, kept in two words: a count of read locks and a flag for the
+//! write lock. The count is a `u64`, so the reader limit cannot be
+//! reached in practice (each read lock is held by a guard on the stack
+//! or heap); should it be reached anyway, `try_acquire_read_lock`
+//! reports `Violation::TooManyReaders` rather than overflowing, and
+//! `check_read` (behind the `try_*` API) returns
+//! `LockError::TooManyReaders`.
 
-use std::cell::Cell;
+use super::id::LazyId;
+use crate::error::LockError;
+use crate::revision::CellId;
+use crate::violation::{self, Violation};
+use std::cell::{Cell, RefCell};
+use std::panic::Location;
 
-thread_local! {
-    static THREAD_LOCK: Cell<u32> = Cell::new(0);
+/// Identifies a lock domain. Cells in different domains are locked
+/// independently.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Domain(pub(crate) u32);
+
+impl Domain {
+    /// The domain of cells created outside of `LockDomain::enter`.
+    pub(crate) const DEFAULT: Domain = Domain(0);
 }
 
-const WRITE_LOCK: u32 = std::u32::MAX;
+/// The lock of one domain.
+#[derive(Copy, Clone, Default, PartialEq)]
+struct State {
+    /// The number of read locks held by the current thread.
+    readers: u64,
+
+    /// True while the current thread holds the write lock.
+    write_locked: bool,
+
+    /// Read locks acquired beyond the limit, after the violation
+    /// handler elected to continue.
+    overflow_readers: u64,
+
+    /// Where the outermost lock currently held was acquired. Only
+    /// recorded in debug builds.
+    held_at: Option<&'static Location<'static>>,
+}
 
-pub(super) fn assert_unlocked() {
-    THREAD_LOCK.with(|lock| {
-        let v = lock.get();
+impl State {
+    fn is_locked(&self) -> bool {
+        self.readers != 0 || self.write_locked
+    }
+}
+
+thread_state! {
+    /// The lock of each domain, indexed by domain.
+    static STATES: RefCell<Vec<State>> = const { RefCell::new(Vec::new()) };
+
+    /// The domain that newly created cells belong to.
+    static CURRENT: Cell<Domain> = const { Cell::new(Domain::DEFAULT) };
+}
 
-        if v != 0 {
-            panic!("cannot modify mutable data right now, lock is held");
+fn with_state<R>(domain: Domain, op: impl FnOnce(&mut State) -> R) -> R {
+    STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        let index = domain.0 as usize;
+        if index >= states.len() {
+            states.resize(index + 1, State::default());
         }
-    });
+        op(&mut states[index])
+    })
+}
+
+/// The domain that cells created now will belong to.
+pub(crate) fn current_domain() -> Domain {
+    CURRENT.with(Cell::get)
+}
+
+/// Make cells created from now on belong to `domain`, returning the
+/// previous domain.
+pub(crate) fn set_current_domain(domain: Domain) -> Domain {
+    CURRENT.with(|c| c.replace(domain))
+}
+
+/// Where the lock currently held by this thread in `domain` (or, for
+/// `None`, in any domain) was acquired, if it is held and this is a
+/// debug build.
+pub(crate) fn held_at(domain: Option<Domain>) -> Option<&'static Location<'static>> {
+    match domain {
+        Some(domain) => with_state(domain, |state| state.held_at),
+        None => STATES.with(|states| states.borrow().iter().find_map(|state| state.held_at)),
+    }
+}
+
+/// Records where a lock was taken, in debug builds.
+fn location(caller: &'static Location<'static>) -> Option<&'static Location<'static>> {
+    if cfg!(debug_assertions) {
+        Some(caller)
+    } else {
+        None
+    }
 }
 
-pub(super) fn debug_assert_read_locked() {
-    debug_assert!(THREAD_LOCK.with(|lock| lock.get() > 0));
-    debug_assert_ne!(THREAD_LOCK.with(|lock| lock.get()), WRITE_LOCK);
+/// The maximum number of read locks a thread can hold at once.
+pub(crate) const MAX_READERS: u64 = u64::MAX;
+
+/// Report `violation` in an access to `cell`.
+#[track_caller]
+fn report(violation: Violation, cell: &LazyId) {
+    violation::report(
+        violation,
+        Some(CellId(cell.get_or_assign())),
+        held_at(Some(cell.domain())),
+    );
+}
+
+#[track_caller]
+pub(super) fn assert_unlocked(cell: &LazyId) {
+    if with_state(cell.domain(), |state| state.is_locked()) {
+        report(Violation::ModifyWhileLocked, cell);
+    }
+}
+
+/// True if the current thread holds a read or write lock in any
+/// domain.
+pub(crate) fn is_locked() -> bool {
+    STATES.with(|states| states.borrow().iter().any(State::is_locked))
+}
+
+/// The complete state of the current thread's locks, for checking
+/// that it has been restored.
+pub(crate) fn state() -> Vec<(usize, u64, bool, u64)> {
+    STATES.with(|states| {
+        states
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| **state != State::default())
+            .map(|(i, state)| (i, state.readers, state.write_locked, state.overflow_readers))
+            .collect()
+    })
+}
+
+/// Ok if a read lock could be acquired now in `domain` (or, for
+/// `None`, in every domain).
+pub(crate) fn check_read(domain: Option<Domain>) -> Result<(), LockError> {
+    let any = |test: fn(&State) -> bool| match domain {
+        Some(domain) => with_state(domain, |state| test(state)),
+        None => STATES.with(|states| states.borrow().iter().any(test)),
+    };
+    if any(|state| state.write_locked) {
+        Err(LockError::WriteLocked)
+    } else if any(|state| state.readers == MAX_READERS) {
+        Err(LockError::TooManyReaders)
+    } else {
+        Ok(())
+    }
 }
 
-pub(super) fn debug_assert_write_locked() {
-    debug_assert_eq!(THREAD_LOCK.with(|lock| lock.get()), WRITE_LOCK);
+/// Ok if cells in `domain` (or, for `None`, in every domain) could be
+/// modified (or the write lock acquired) now.
+pub(crate) fn check_write(domain: Option<Domain>) -> Result<(), LockError> {
+    check_read(domain)?;
+    let read_locked = match domain {
+        Some(domain) => with_state(domain, |state| state.readers != 0),
+        None => STATES.with(|states| states.borrow().iter().any(|state| state.readers != 0)),
+    };
+    if read_locked {
+        Err(LockError::ReadLocked)
+    } else {
+        Ok(())
+    }
 }
 
-pub(super) fn acquire_read_lock() {
-    THREAD_LOCK.with(|lock| {
-        let v = lock.get();
+pub(super) fn debug_assert_read_locked(domain: Domain) {
+    if cfg!(debug_assertions) {
+        with_state(domain, |state| {
+            debug_assert!(state.readers > 0);
+            debug_assert!(!state.write_locked);
+        });
+    }
+}
+
+pub(super) fn debug_assert_write_locked(domain: Domain) {
+    if cfg!(debug_assertions) {
+        with_state(domain, |state| debug_assert!(state.write_locked));
+    }
+}
 
-        if v == WRITE_LOCK {
-            panic!("cannot read from a Mut cell now");
+/// Acquire a read lock in `domain`, or report why it cannot be
+/// acquired.
+#[track_caller]
+pub(crate) fn try_acquire_read_lock(domain: Domain) -> Result<(), Violation> {
+    let caller = Location::caller();
+    with_state(domain, |state| {
+        if state.write_locked {
+            return Err(Violation::ReadWhileWriteLocked);
         }
+        match state.readers {
+            MAX_READERS => Err(Violation::TooManyReaders),
+            n => {
+                if n == 0 {
+                    state.held_at = location(caller);
+                }
+                state.readers = n + 1;
+                Ok(())
+            }
+        }
+    })
+}
 
-        if v == WRITE_LOCK - 1 {
-            panic!("too many readers");
+#[track_caller]
+pub(super) fn acquire_read_lock(cell: &LazyId) {
+    let domain = cell.domain();
+    if let Err(violation) = try_acquire_read_lock(domain) {
+        // Returns only for `TooManyReaders`, if the handler elects
+        // to continue.
+        report(violation, cell);
+        with_state(domain, |state| state.overflow_readers += 1);
+    }
+}
+
+pub(crate) fn release_read_lock(domain: Domain) {
+    with_state(domain, |state| {
+        if state.overflow_readers > 0 {
+            state.overflow_readers -= 1;
+            return;
         }
 
-        lock.set(v + 1);
+        let n = state.readers;
+        assert!(n > 0 && !state.write_locked);
+        state.readers = n - 1;
+        if n == 1 {
+            state.held_at = None;
+        }
     });
 }
 
-pub(super) fn release_read_lock() {
-    THREAD_LOCK.with(|lock| {
-        let v = lock.get();
-        assert!(v > 0 && v != WRITE_LOCK);
-        lock.set(v - 1);
+#[track_caller]
+pub(super) fn acquire_write_lock(cell: &LazyId) {
+    let domain = cell.domain();
+    if with_state(domain, |state| state.is_locked()) {
+        report(Violation::WriteWhileLocked, cell);
+    }
+    let caller = Location::caller();
+    with_state(domain, |state| {
+        state.write_locked = true;
+        state.held_at = location(caller);
     });
 }
 
-pub(super) fn acquire_write_lock() {
-    THREAD_LOCK.with(|lock| {
-        assert!(lock.get() == 0, "lock already held");
-        lock.set(WRITE_LOCK);
+pub(super) fn release_write_lock(domain: Domain) {
+    with_state(domain, |state| {
+        assert!(state.write_locked);
+        state.write_locked = false;
+        state.held_at = None;
     });
 }
 
-pub(super) fn release_write_lock() {
-    THREAD_LOCK.with(|lock| {
-        let v = lock.get();
-        assert!(v == WRITE_LOCK);
-        lock.set(0);
-    });
+/// Pretend that the current thread holds `n` read locks in the
+/// default domain, to test the behavior at the limit.
+#[cfg(test)]
+pub(crate) fn set_readers(n: u64) {
+    with_state(Domain::DEFAULT, |state| state.readers = n);
 }
@@ -0,0 +1,109 @@
+//! Per-cell poisoning state; see the crate's `poison` module. Cells
+//! opt in, so the state lives in a thread-local table keyed by cell
+//! id rather than in every cell.
+
+use super::*;
+use crate::error::Poisoned;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    /// For each cell with poisoning enabled, whether it is poisoned.
+    static POISONING: RefCell<HashMap<u64, bool>> = RefCell::new(HashMap::new());
+}
+
+thread_state! {
+    /// The number of entries in `POISONING`, so that accesses to
+    /// cells can skip the table lookup in the common case.
+    static ENABLED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether a guard was created while the thread was already
+/// panicking. Such a guard (e.g., one taken to roll back a
+/// transaction during unwinding) is not interrupted by the panic, so
+/// it does not poison its cell when dropped. As with
+/// `std::sync::Mutex`.
+pub(super) struct Flag {
+    panicking: bool,
+}
+
+impl Flag {
+    pub(super) fn new() -> Self {
+        Flag {
+            panicking: std::thread::panicking(),
+        }
+    }
+
+    /// Called by the guard when it is dropped: poisons `id` if a
+    /// panic started while the guard was live.
+    pub(super) fn done(&self, id: &id::LazyId) {
+        if !self.panicking && std::thread::panicking() {
+            poison(id);
+        }
+    }
+}
+
+fn poison(id: &id::LazyId) {
+    if let Some(id) = id.get() {
+        if ENABLED.with(Cell::get) != 0 {
+            POISONING.with(|p| {
+                if let Some(poisoned) = p.borrow_mut().get_mut(&id) {
+                    *poisoned = true;
+                }
+            });
+        }
+    }
+}
+
+/// Panics if the cell is poisoned.
+#[track_caller]
+pub(super) fn check(id: &id::LazyId) {
+    if ENABLED.with(Cell::get) == 0 {
+        return;
+    }
+    if let Some(id) = id.get() {
+        if POISONING.with(|p| p.borrow().get(&id) == Some(&true)) {
+            panic!("{}", Poisoned);
+        }
+    }
+}
+
+/// Discards the state of `id`.
+pub(super) fn forget(id: u64) {
+    let _ = POISONING.try_with(|p| {
+        if p.borrow_mut().remove(&id).is_some() {
+            ENABLED.with(|n| n.set(n.get() - 1));
+        }
+    });
+}
+
+impl<T> MCell<T> {
+    pub(crate) fn enable_poisoning(&self) {
+        let id = self.id();
+        POISONING.with(|p| {
+            if p.borrow_mut().insert(id, false).is_none() {
+                ENABLED.with(|n| n.set(n.get() + 1));
+            }
+        });
+    }
+
+    pub(crate) fn is_poisoned(&self) -> bool {
+        let id = match self.existing_id() {
+            Some(id) => id,
+            None => return false,
+        };
+        POISONING.with(|p| p.borrow().get(&id) == Some(&true))
+    }
+
+    /// Clear the poison, returning whether the cell was poisoned.
+    pub(crate) fn unpoison(&self) -> bool {
+        let id = match self.existing_id() {
+            Some(id) => id,
+            None => return false,
+        };
+        POISONING.with(|p| match p.borrow_mut().get_mut(&id) {
+            Some(poisoned) => std::mem::replace(poisoned, false),
+            None => false,
+        })
+    }
+}
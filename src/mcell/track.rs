@@ -0,0 +1,91 @@
+//! Dependency tracking: records which cells are read while a
+//! computation runs, and when each (identified) cell last changed.
+//!
+//! Changes are stamped with a thread-local revision number that is
+//! bumped every time a cell with an id is mutated. A computation that
+//! started at revision `R` is out of date if any cell it read has a
+//! change stamp greater than `R`.
+
+use super::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_state! {
+    /// Number of active tracking frames; checked on every read, so it
+    /// is kept separate from `FRAMES` to make the common case cheap.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+thread_local! {
+    /// For each active tracking frame, the ids of the cells read.
+    static FRAMES: RefCell<Vec<Vec<u64>>> = const { RefCell::new(Vec::new()) };
+
+    static REVISION: Cell<u64> = const { Cell::new(0) };
+
+    /// Revision at which each identified cell last changed.
+    static CHANGED_AT: RefCell<HashMap<u64, u64>> = RefCell::new(HashMap::new());
+}
+
+/// The current revision.
+pub(crate) fn current_revision() -> u64 {
+    REVISION.with(|r| r.get())
+}
+
+/// The revision at which cell `id` last changed (0 if it has not
+/// changed since it was assigned an id).
+pub(crate) fn changed_at(id: u64) -> u64 {
+    CHANGED_AT.with(|c| c.borrow().get(&id).copied().unwrap_or(0))
+}
+
+/// Run `op`, returning its result along with the (sorted,
+/// de-duplicated) ids of the cells it read. Reads are also reported
+/// to any enclosing frame.
+pub(crate) fn tracking<R>(op: impl FnOnce() -> R) -> (R, Vec<u64>) {
+    struct PopFrame;
+
+    impl Drop for PopFrame {
+        fn drop(&mut self) {
+            DEPTH.with(|d| d.set(d.get() - 1));
+            FRAMES.with(|f| f.borrow_mut().pop());
+        }
+    }
+
+    FRAMES.with(|f| f.borrow_mut().push(vec![]));
+    DEPTH.with(|d| d.set(d.get() + 1));
+    let result = {
+        let _pop = PopFrame;
+        let result = op();
+        let mut reads = FRAMES.with(|f| std::mem::take(f.borrow_mut().last_mut().unwrap()));
+        reads.sort_unstable();
+        reads.dedup();
+        (result, reads)
+    };
+    record_reads(&result.1);
+    result
+}
+
+/// Report that the current computation (if any) depends on each of
+/// the cells in `ids`.
+pub(crate) fn record_reads(ids: &[u64]) {
+    if DEPTH.with(|d| d.get()) > 0 {
+        FRAMES.with(|f| f.borrow_mut().last_mut().unwrap().extend_from_slice(ids));
+    }
+}
+
+pub(super) fn record_read(id: &id::LazyId) {
+    if DEPTH.with(|d| d.get()) > 0 {
+        record_reads(&[id.get_or_assign()]);
+    }
+}
+
+pub(super) fn record_change(id: u64) {
+    let revision = REVISION.with(|r| {
+        r.set(r.get() + 1);
+        r.get()
+    });
+    CHANGED_AT.with(|c| c.borrow_mut().insert(id, revision));
+}
+
+pub(super) fn forget(id: u64) {
+    let _ = CHANGED_AT.try_with(|c| c.borrow_mut().remove(&id));
+}
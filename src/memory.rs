@@ -0,0 +1,56 @@
+//! Accounting for the memory used by collections, via the
+//! `memory_usage` methods (and, with the `debug` feature,
+//! `debug::total_heap_bytes`).
+
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::mem::size_of;
+
+mod test;
+
+/// The footprint of a collection.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemoryUsage {
+    /// The number of elements.
+    pub len: usize,
+
+    /// The number of elements there is room for without
+    /// reallocating.
+    pub capacity: usize,
+
+    /// The approximate number of bytes allocated on the heap by the
+    /// collection itself. Elements are counted by their shallow size;
+    /// memory they own in turn is not included.
+    pub heap_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub(crate) fn of_vec<T>(vec: &Vec<T>) -> Self {
+        MemoryUsage {
+            len: vec.len(),
+            capacity: vec.capacity(),
+            heap_bytes: vec.capacity() * size_of::<T>(),
+        }
+    }
+
+    pub(crate) fn of_index_map<K, V>(map: &IndexMap<K, V>) -> Self {
+        // An `IndexMap` stores its entries (with their hashes) in a
+        // vector, plus a table of indices into it.
+        let entry = size_of::<(u64, K, V)>() + size_of::<usize>();
+        MemoryUsage {
+            len: map.len(),
+            capacity: map.capacity(),
+            heap_bytes: map.capacity() * entry,
+        }
+    }
+
+    pub(crate) fn of_hash_map<K, V, S>(map: &HashMap<K, V, S>) -> Self {
+        // One control byte per bucket, in addition to the entry.
+        let entry = size_of::<(K, V)>() + 1;
+        MemoryUsage {
+            len: map.len(),
+            capacity: map.capacity(),
+            heap_bytes: map.capacity() * entry,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg(test)]
+
+use crate::{MutMap, MutVec};
+use std::collections::BTreeMap;
+
+#[test]
+fn vec_usage() {
+    let v: MutVec<u64> = MutVec::from(Vec::with_capacity(10));
+    v.push(1);
+    let usage = v.memory_usage();
+    assert_eq!(usage.len, 1);
+    assert_eq!(usage.capacity, 10);
+    assert_eq!(usage.heap_bytes, 80);
+}
+
+#[test]
+fn map_usage() {
+    let m: MutMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    let usage = m.memory_usage();
+    assert_eq!(usage.len, 10);
+    assert!(usage.capacity >= 10);
+    assert!(usage.heap_bytes >= 10 * 8);
+
+    let b: MutMap<u32, u32, BTreeMap<u32, u32>> = (0..3).map(|i| (i, i)).collect();
+    assert_eq!(b.memory_usage().len, 3);
+    assert!(b.memory_usage().heap_bytes >= 3 * 8);
+}
@@ -0,0 +1,93 @@
+//! Simultaneous mutable access to several cells. For example, moving
+//! an element from one `MutVec` to another:
+//!
+//! ```
+//! use mutable::{borrow_mut2, MutVec};
+//!
+//! let from = MutVec::from(vec![String::from("a")]);
+//! let to = MutVec::new();
+//! borrow_mut2(&from, &to, |from, to| to.push(from.remove(0)));
+//! assert_eq!(to.into_inner(), vec!["a"]);
+//! ```
+
+use crate::map::MutMap;
+use crate::mcell;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use sealed::CellRef;
+
+mod test;
+
+pub(crate) mod sealed {
+    use crate::mcell::MCell;
+
+    /// A reference to the cell of a `Lockable` type.
+    pub struct CellRef<'a, T>(pub(crate) &'a MCell<T>);
+
+    pub trait HasCell {
+        type Target;
+
+        fn mcell(&self) -> CellRef<'_, Self::Target>;
+    }
+}
+
+/// The types whose contents `borrow_mut2` and `borrow_mut3` can
+/// access: `Mut<T>` (as a `T`), `MutVec<T>` (as a `Vec<T>`), and
+/// `MutMap<K, V, S>` (as its store `S`).
+pub trait Lockable: sealed::HasCell {}
+
+impl<C: sealed::HasCell> Lockable for C {}
+
+/// Runs `op` with mutable access to the contents of both `a` and `b`,
+/// under a single write lock: while `op` runs, no other mut-cell (of
+/// the domains of `a` and `b`) can be accessed. Panics if `a` and `b`
+/// are the same.
+#[track_caller]
+pub fn borrow_mut2<A, B, R>(a: &A, b: &B, op: impl FnOnce(&mut A::Target, &mut B::Target) -> R) -> R
+where
+    A: Lockable,
+    B: Lockable,
+{
+    mcell::borrow_mut2(a.mcell().0, b.mcell().0, op)
+}
+
+/// The three-way version of `borrow_mut2`. Panics if any two of `a`,
+/// `b`, and `c` are the same.
+#[track_caller]
+pub fn borrow_mut3<A, B, C, R>(
+    a: &A,
+    b: &B,
+    c: &C,
+    op: impl FnOnce(&mut A::Target, &mut B::Target, &mut C::Target) -> R,
+) -> R
+where
+    A: Lockable,
+    B: Lockable,
+    C: Lockable,
+{
+    mcell::borrow_mut3(a.mcell().0, b.mcell().0, c.mcell().0, op)
+}
+
+impl<T> sealed::HasCell for Mut<T> {
+    type Target = T;
+
+    fn mcell(&self) -> CellRef<'_, T> {
+        CellRef(&self.data)
+    }
+}
+
+impl<T> sealed::HasCell for MutVec<T> {
+    type Target = Vec<T>;
+
+    fn mcell(&self) -> CellRef<'_, Vec<T>> {
+        CellRef(&self.data)
+    }
+}
+
+impl<K, V, S> sealed::HasCell for MutMap<K, V, S> {
+    type Target = S;
+
+    fn mcell(&self) -> CellRef<'_, S> {
+        CellRef(&self.data)
+    }
+}
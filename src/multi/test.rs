@@ -0,0 +1,47 @@
+#![cfg(test)]
+
+use super::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn swap_between_cells() {
+    let a = Mut::new(1);
+    let b = Mut::new(2);
+    let v = MutVec::from(vec![3]);
+    borrow_mut3(&a, &b, &v, |a, b, v| {
+        std::mem::swap(a, b);
+        v.push(*a + *b);
+    });
+    assert_eq!(a.get(), 2);
+    assert_eq!(b.get(), 1);
+    assert_eq!(v.into_inner(), vec![3, 3]);
+}
+
+#[test]
+fn same_cell_panics() {
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| borrow_mut2(&v, &v, |_, _| ())));
+    assert!(result.is_err());
+
+    // The lock was never taken.
+    v.push(2);
+    assert_eq!(v.len(), 2);
+}
+
+#[test]
+fn notifies_both() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let a = Mut::new(1);
+    let m = MutMap::new();
+    let count = Rc::new(Cell::new(0));
+    let (c1, c2) = (count.clone(), count.clone());
+    a.subscribe(move || c1.set(c1.get() + 1));
+    m.subscribe(move || c2.set(c2.get() + 1));
+    borrow_mut2(&a, &m, |a, m| {
+        m.insert("a", *a);
+    });
+    assert_eq!(count.get(), 2);
+    assert_eq!(m.get("a"), Some(1));
+}
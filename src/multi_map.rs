@@ -0,0 +1,187 @@
+//! A map from each key to a list of values.
+
+use crate::iter_check::MutationCheck;
+use crate::mcell::MCell;
+use indexmap::Equivalent;
+use indexmap::IndexMap;
+use std::fmt;
+use std::hash::Hash;
+
+mod test;
+
+/// A map from each key to a bucket of values, in the order they were
+/// inserted. Keys with no values are not stored: removing the last
+/// value of a key removes the key.
+///
+/// Operations that hash or compare keys (or values) borrow the map,
+/// or check it out to modify it, so all mut-cells are read-only
+/// while the user's `Hash` and `Eq` impls run.
+pub struct MutMultiMap<K, V> {
+    data: MCell<IndexMap<K, Vec<V>>>,
+}
+
+impl<K, V> MutMultiMap<K, V>
+where
+    K: Eq + Hash,
+{
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of values, across all keys.
+    pub fn total_len(&self) -> usize {
+        self.data.borrow().values().map(Vec::len).sum()
+    }
+
+    /// Append `value` to the bucket of `key`.
+    pub fn insert(&self, key: K, value: V) {
+        self.data.check_out().entry(key).or_default().push(value);
+    }
+
+    /// A clone of the values of `key`, in insertion order; empty if
+    /// there are none.
+    pub fn get<Q>(&self, key: &Q) -> Vec<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        V: Clone,
+    {
+        self.with(key, <[V]>::to_vec)
+    }
+
+    /// Runs `f` with the values of `key` (empty if there are none).
+    /// All mut-cells are read-only while `f` runs.
+    #[track_caller]
+    pub fn with<Q, R>(&self, key: &Q, f: impl FnOnce(&[V]) -> R) -> R
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let data = self.data.borrow();
+        f(data.get(key).map_or(&[], Vec::as_slice))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.data.borrow().contains_key(key)
+    }
+
+    /// Remove `key`, returning its values. The order of the remaining
+    /// keys is preserved.
+    pub fn remove<Q>(&self, key: &Q) -> Vec<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let values = self.data.check_out().shift_remove(key);
+        values.unwrap_or_default()
+    }
+
+    /// Remove the first value of `key` that equals `value`, returning
+    /// it, and the key itself if that was its last value.
+    pub fn remove_value<Q>(&self, key: &Q, value: &V) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        V: PartialEq,
+    {
+        let mut data = self.data.check_out();
+        let (index, _, values) = data.get_full_mut(key)?;
+        let position = values.iter().position(|v| v == value)?;
+        let removed = values.remove(position);
+        if values.is_empty() {
+            data.shift_remove_index(index);
+        }
+        Some(removed)
+    }
+
+    /// Iterate over the keys and their values, in the order the keys
+    /// were first inserted, cloning as we go. The map is borrowed
+    /// only for each step, so it may be modified during the
+    /// iteration; in debug builds, doing so logs a warning.
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            map: self,
+            index: 0,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+}
+
+impl<K, V> Default for MutMultiMap<K, V> {
+    #[track_caller]
+    fn default() -> Self {
+        MutMultiMap {
+            data: MCell::new(IndexMap::new()),
+        }
+    }
+}
+
+impl<K, V> Clone for MutMultiMap<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        MutMultiMap {
+            data: MCell::new(self.data.borrow().clone()),
+        }
+    }
+}
+
+impl<K, V> std::iter::FromIterator<(K, V)> for MutMultiMap<K, V>
+where
+    K: Eq + Hash,
+{
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut data: IndexMap<K, Vec<V>> = IndexMap::new();
+        for (key, value) in iter {
+            data.entry(key).or_default().push(value);
+        }
+        MutMultiMap {
+            data: MCell::new(data),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for MutMultiMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+pub struct Iter<'iter, K, V> {
+    map: &'iter MutMultiMap<K, V>,
+    index: usize,
+    check: MutationCheck,
+}
+
+impl<'iter, K, V> Iterator for Iter<'iter, K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    type Item = (K, Vec<V>);
+
+    fn next(&mut self) -> Option<(K, Vec<V>)> {
+        self.check.check();
+        let data = self.map.data.borrow();
+        let (key, values) = data.get_index(self.index)?;
+        self.index += 1;
+        Some((key.clone(), values.clone()))
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn buckets() {
+    let map = MutMultiMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("a", 3);
+    assert_eq!(map.get("a"), vec![1, 3]);
+    assert_eq!(map.get("z"), Vec::<i32>::new());
+    assert_eq!((map.len(), map.total_len()), (2, 3));
+    assert_eq!(map.with("a", |values| values.len()), 2);
+
+    assert_eq!(map.remove_value("a", &1), Some(1));
+    assert_eq!(map.remove_value("a", &1), None);
+    assert_eq!(map.remove_value("b", &2), Some(2));
+    assert!(!map.contains_key("b"));
+    assert_eq!(format!("{:?}", map), "{\"a\": [3]}");
+
+    assert_eq!(map.remove("a"), vec![3]);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn iter() {
+    let map: MutMultiMap<_, _> = vec![(1, 'x'), (2, 'y'), (1, 'z')].into_iter().collect();
+    let entries: Vec<_> = map.iter().collect();
+    assert_eq!(entries, vec![(1, vec!['x', 'z']), (2, vec!['y'])]);
+}
+
+#[test]
+fn mutate_while_reading_is_a_violation() {
+    let map = MutMultiMap::new();
+    map.insert(1, 1);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.with(&1, |_| map.insert(2, 2));
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn reads_do_not_mutate() {
+    let map: MutMultiMap<_, _> = vec![(1, 'x'), (2, 'y')].into_iter().collect();
+    let version = map.data.version();
+    assert_eq!(map.get(&1), vec!['x']);
+    assert!(map.contains_key(&2));
+    map.with(&1, |values| {
+        // Nested reads see the contents.
+        assert_eq!(values, ['x']);
+        assert_eq!(map.get(&2), vec!['y']);
+    });
+    assert_eq!(map.iter().count(), 2);
+    let _ = map.clone();
+    assert_eq!(map.data.version(), version);
+}
@@ -1,10 +1,15 @@
 use crate::mcell::MCell;
+use crate::observe;
+use crate::observe::hooks;
+use crate::observe::SubscriptionId;
+use std::fmt;
 
 pub struct Mut<T> {
-    data: MCell<T>,
+    pub(crate) data: MCell<T>,
 }
 
 impl<T> Mut<T> {
+    #[track_caller]
     pub fn new(value: T) -> Self {
         Mut {
             data: MCell::new(value),
@@ -12,7 +17,12 @@ impl<T> Mut<T> {
     }
 
     pub fn replace(&self, new_value: T) -> T {
-        self.data.replace(new_value)
+        // Unsafe proof obligation: the elements of a cell are `(T, ())`
+        // (see the `hooks` module).
+        let staged = unsafe { hooks::stage_insert(self.data.existing_id(), &new_value, &()) };
+        let old = self.data.replace(new_value);
+        staged.fire();
+        old
     }
 
     pub fn get(&self) -> T
@@ -23,6 +33,57 @@ impl<T> Mut<T> {
     }
 
     pub fn set(&self, new_value: T) {
-        self.data.set(new_value)
+        // Unsafe proof obligation: as in `replace`.
+        let staged = unsafe { hooks::stage_insert(self.data.existing_id(), &new_value, &()) };
+        self.data.set(new_value);
+        staged.fire();
+    }
+
+    /// Take ownership of the value, consuming the cell.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Register `hook` to be invoked with each value stored into
+    /// `self` (by `set` or `replace`), once the operation is complete
+    /// and after the callbacks registered with `subscribe`. The hook
+    /// receives a clone of the value, taken just before it was
+    /// stored. Remove it with `unsubscribe`.
+    pub fn on_set(&self, hook: impl Fn(&T) + 'static) -> SubscriptionId
+    where
+        T: Clone + 'static,
+    {
+        hooks::on_insert(self.data.id(), move |t: &T, _: &()| hook(t))
+    }
+
+    /// Remove a callback registered with `subscribe` or `on_set`,
+    /// returning false if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+}
+
+impl<T: Default> Default for Mut<T> {
+    #[track_caller]
+    fn default() -> Self {
+        Mut::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Mut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Mut").field(&*self.data.borrow()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Mut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.borrow().fmt(f)
     }
 }
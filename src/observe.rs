@@ -0,0 +1,163 @@
+//! Change notification. `Mut`, `MutVec`, and `MutMap` offer a
+//! `subscribe` method that registers a callback to be invoked each
+//! time the collection is mutated.
+//!
+//! Callbacks run once the mutating operation is complete and the
+//! thread lock has been released, so they are free to read (or even
+//! mutate) any cell, including the one that changed. Notifications
+//! are coarse-grained: a callback learns *that* a mutating operation
+//! took place, not what it did, and it may be invoked for operations
+//! that turned out to be no-ops (e.g., `pop` on an empty vector).
+//!
+//! Some collections additionally offer fine-grained notifications
+//! (e.g., `MutVec::subscribe_changes`) describing each change, and
+//! hooks invoked with each element inserted or removed (e.g.,
+//! `MutVec::on_insert`, `MutVec::on_push`, or `Mut::on_set`); these
+//! are delivered after the coarse-grained callbacks and are removed
+//! with the same `unsubscribe` method.
+//!
+//! Subscriptions are recorded in a thread-local table, so a
+//! collection that is sent to another thread leaves its subscriptions
+//! behind.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+pub(crate) mod hooks;
+mod test;
+
+/// Identifies a callback registered with `subscribe`, so that it can
+/// later be removed with `unsubscribe`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Callback = Rc<dyn Fn()>;
+
+/// A callback for events of type `E`.
+type TypedCallback<E> = Rc<dyn Fn(&E)>;
+
+/// A `TypedCallback<E>` for some event type `E`.
+type EventCallback = Rc<dyn Any>;
+
+/// Subscriptions, keyed by cell id.
+type Table<C> = RefCell<HashMap<u64, Vec<(SubscriptionId, C)>>>;
+
+thread_local! {
+    /// Maps a cell id to the callbacks subscribed to that cell.
+    static OBSERVERS: Table<Callback> =
+        RefCell::new(HashMap::new());
+
+    /// Maps a cell id to the event callbacks subscribed to that cell.
+    static EVENT_OBSERVERS: Table<EventCallback> =
+        RefCell::new(HashMap::new());
+}
+
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(0);
+
+fn next_subscription() -> SubscriptionId {
+    SubscriptionId(NEXT_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed))
+}
+
+pub(crate) fn subscribe(cell_id: u64, callback: impl Fn() + 'static) -> SubscriptionId {
+    let id = next_subscription();
+    OBSERVERS.with(|observers| {
+        observers
+            .borrow_mut()
+            .entry(cell_id)
+            .or_default()
+            .push((id, Rc::new(callback)));
+    });
+    id
+}
+
+/// Subscribe to events of type `E` describing the changes to
+/// `cell_id`; see `notify_event`.
+pub(crate) fn subscribe_events<E: 'static>(
+    cell_id: u64,
+    callback: impl Fn(&E) + 'static,
+) -> SubscriptionId {
+    let id = next_subscription();
+    let callback: TypedCallback<E> = Rc::new(callback);
+    EVENT_OBSERVERS.with(|observers| {
+        observers
+            .borrow_mut()
+            .entry(cell_id)
+            .or_default()
+            .push((id, Rc::new(callback)));
+    });
+    id
+}
+
+pub(crate) fn unsubscribe(cell_id: u64, subscription: SubscriptionId) -> bool {
+    remove_subscription(&OBSERVERS, cell_id, subscription)
+        || remove_subscription(&EVENT_OBSERVERS, cell_id, subscription)
+        || remove_subscription(&hooks::HOOKS, cell_id, subscription)
+}
+
+fn remove_subscription<C: 'static>(
+    table: &'static std::thread::LocalKey<Table<C>>,
+    cell_id: u64,
+    subscription: SubscriptionId,
+) -> bool {
+    table.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        let callbacks = match observers.get_mut(&cell_id) {
+            Some(callbacks) => callbacks,
+            None => return false,
+        };
+        let len = callbacks.len();
+        callbacks.retain(|(id, _)| *id != subscription);
+        let removed = callbacks.len() != len;
+        if callbacks.is_empty() {
+            observers.remove(&cell_id);
+        }
+        removed
+    })
+}
+
+/// Invoke the callbacks subscribed to `cell_id`. The table is not
+/// borrowed while they run, so callbacks may subscribe and
+/// unsubscribe.
+pub(crate) fn notify(cell_id: u64) {
+    let callbacks: Vec<Callback> =
+        OBSERVERS.with(|observers| match observers.borrow().get(&cell_id) {
+            Some(callbacks) => callbacks.iter().map(|(_, c)| c.clone()).collect(),
+            None => vec![],
+        });
+    for callback in callbacks {
+        callback();
+    }
+}
+
+/// Invoke the callbacks subscribed to events of type `E` on
+/// `cell_id`. As with `notify`, callbacks may subscribe and
+/// unsubscribe.
+pub(crate) fn notify_event<E: 'static>(cell_id: u64, event: &E) {
+    if std::thread::panicking() {
+        return;
+    }
+    let callbacks: Vec<TypedCallback<E>> =
+        EVENT_OBSERVERS.with(|observers| match observers.borrow().get(&cell_id) {
+            Some(callbacks) => callbacks
+                .iter()
+                .filter_map(|(_, c)| c.downcast_ref::<TypedCallback<E>>().cloned())
+                .collect(),
+            None => vec![],
+        });
+    for callback in callbacks {
+        callback(event);
+    }
+}
+
+/// Drop all subscriptions for a cell that is going away.
+pub(crate) fn forget(cell_id: u64) {
+    // The tables may already be gone if this runs during thread
+    // teardown; in that case there is nothing to forget.
+    let _ = OBSERVERS.try_with(|observers| observers.borrow_mut().remove(&cell_id));
+    let _ = EVENT_OBSERVERS.try_with(|observers| observers.borrow_mut().remove(&cell_id));
+    let _ = hooks::HOOKS.try_with(|hooks| hooks.borrow_mut().remove(&cell_id));
+}
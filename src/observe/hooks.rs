@@ -0,0 +1,320 @@
+//! Element hooks: callbacks invoked with each element a collection
+//! gains or loses (see `MutVec::on_insert` and `MutVec::on_remove`).
+//! Push hooks (`MutVec::on_push`) are insert hooks that are only
+//! staged by pushes, and put hooks are those staged by the other
+//! insertions.
+//!
+//! Like the other callbacks, hooks run once the operation is complete
+//! and the thread lock has been released. An inserted element has
+//! been moved into the collection by then, so insert hooks *stage* a
+//! clone of it just before the insertion, and are then *fired* with
+//! that clone. Staging returns a `Staged` guard: if the insertion is
+//! rejected or panics, dropping the guard discards the clones. A
+//! removed element is owned by the operation, so remove hooks are
+//! fired with a reference to it directly.
+//!
+//! The hooks of a cell are stored type-erased: elements are passed as
+//! raw pointers to functions that were instantiated, when the hook
+//! was registered, with the element types of that cell. Cell ids are
+//! unique, so this is sound as long as the element types that a
+//! collection stages and removes match those it registers hooks
+//! with; hence the functions that take elements are `unsafe`.
+
+use super::*;
+
+/// Elements are `(A, B)` pairs: `(T, ())` for a vector and `(K, V)`
+/// for a map. The function receives the hook's data and pointers to
+/// `A` and `B`.
+type Erased = unsafe fn(&dyn Any, *const (), *const ());
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    Insert,
+    Push,
+    Put,
+    Remove,
+}
+
+#[derive(Clone)]
+pub(super) struct Hook {
+    kind: Kind,
+    data: Rc<dyn Any>,
+    stage: Option<Erased>,
+    discard: Option<fn(&dyn Any)>,
+    fire: Erased,
+}
+
+/// The data of an insert hook.
+struct InsertHook<A, B, F> {
+    f: F,
+
+    /// Clones of the elements being inserted, most recent last.
+    staged: RefCell<Vec<(A, B)>>,
+}
+
+thread_local! {
+    pub(super) static HOOKS: Table<Hook> = RefCell::new(HashMap::new());
+}
+
+fn add(cell_id: u64, hook: Hook) -> SubscriptionId {
+    let id = next_subscription();
+    HOOKS.with(|hooks| {
+        hooks
+            .borrow_mut()
+            .entry(cell_id)
+            .or_default()
+            .push((id, hook))
+    });
+    id
+}
+
+/// Register `f` to be invoked with clones of the elements inserted
+/// into `cell_id`.
+pub(crate) fn on_insert<A, B, F>(cell_id: u64, f: F) -> SubscriptionId
+where
+    A: Clone + 'static,
+    B: Clone + 'static,
+    F: Fn(&A, &B) + 'static,
+{
+    add_staged(cell_id, Kind::Insert, f)
+}
+
+/// Register `f` to be invoked with clones of the elements pushed onto
+/// `cell_id`.
+pub(crate) fn on_push<A, B, F>(cell_id: u64, f: F) -> SubscriptionId
+where
+    A: Clone + 'static,
+    B: Clone + 'static,
+    F: Fn(&A, &B) + 'static,
+{
+    add_staged(cell_id, Kind::Push, f)
+}
+
+/// Register `f` to be invoked with clones of the elements inserted
+/// into `cell_id` other than by a push.
+pub(crate) fn on_put<A, B, F>(cell_id: u64, f: F) -> SubscriptionId
+where
+    A: Clone + 'static,
+    B: Clone + 'static,
+    F: Fn(&A, &B) + 'static,
+{
+    add_staged(cell_id, Kind::Put, f)
+}
+
+/// Register `f` as a hook of `kind` that stages clones of elements.
+fn add_staged<A, B, F>(cell_id: u64, kind: Kind, f: F) -> SubscriptionId
+where
+    A: Clone + 'static,
+    B: Clone + 'static,
+    F: Fn(&A, &B) + 'static,
+{
+    unsafe fn stage<A: Clone + 'static, B: Clone + 'static, F: 'static>(
+        data: &dyn Any,
+        a: *const (),
+        b: *const (),
+    ) {
+        let hook = data.downcast_ref::<InsertHook<A, B, F>>().unwrap();
+        let entry = ((*(a as *const A)).clone(), (*(b as *const B)).clone());
+        hook.staged.borrow_mut().push(entry);
+    }
+
+    unsafe fn fire<A: 'static, B: 'static, F: Fn(&A, &B) + 'static>(
+        data: &dyn Any,
+        _: *const (),
+        _: *const (),
+    ) {
+        let hook = data.downcast_ref::<InsertHook<A, B, F>>().unwrap();
+        let entry = hook.staged.borrow_mut().pop();
+        if let Some((a, b)) = entry {
+            (hook.f)(&a, &b);
+        }
+    }
+
+    fn discard<A: 'static, B: 'static, F: 'static>(data: &dyn Any) {
+        let hook = data.downcast_ref::<InsertHook<A, B, F>>().unwrap();
+        hook.staged.borrow_mut().pop();
+    }
+
+    let data = InsertHook {
+        f,
+        staged: RefCell::new(Vec::<(A, B)>::new()),
+    };
+    add(
+        cell_id,
+        Hook {
+            kind,
+            data: Rc::new(data),
+            stage: Some(stage::<A, B, F>),
+            discard: Some(discard::<A, B, F>),
+            fire: fire::<A, B, F>,
+        },
+    )
+}
+
+/// Register `f` to be invoked with the elements removed from
+/// `cell_id`.
+pub(crate) fn on_remove<A, B, F>(cell_id: u64, f: F) -> SubscriptionId
+where
+    F: Fn(&A, &B) + 'static,
+{
+    unsafe fn fire<A, B, F: Fn(&A, &B) + 'static>(data: &dyn Any, a: *const (), b: *const ()) {
+        let f = data.downcast_ref::<F>().unwrap();
+        f(&*(a as *const A), &*(b as *const B));
+    }
+
+    add(
+        cell_id,
+        Hook {
+            kind: Kind::Remove,
+            data: Rc::new(f),
+            stage: None,
+            discard: None,
+            fire: fire::<A, B, F>,
+        },
+    )
+}
+
+/// True if any hooks are registered for `cell_id`; lets bulk
+/// operations skip their per-element work.
+pub(crate) fn any(cell_id: Option<u64>) -> bool {
+    match cell_id {
+        Some(id) => HOOKS.with(|hooks| hooks.borrow().contains_key(&id)),
+        None => false,
+    }
+}
+
+fn hooks(cell_id: Option<u64>, kinds: &[Kind]) -> Vec<Hook> {
+    let cell_id = match cell_id {
+        Some(id) if !std::thread::panicking() => id,
+        _ => return vec![],
+    };
+    HOOKS.with(|hooks| match hooks.borrow().get(&cell_id) {
+        Some(hooks) => hooks
+            .iter()
+            .filter(|(_, h)| kinds.contains(&h.kind))
+            .map(|(_, h)| h.clone())
+            .collect(),
+        None => vec![],
+    })
+}
+
+/// Insertions staged for the insert hooks of a cell, to be fired once
+/// they are complete. If this is dropped first (the insertion was
+/// rejected, or panicked), they are discarded, so that a later
+/// insertion does not fire them.
+#[must_use]
+pub(crate) struct Staged {
+    /// The hooks the insertions were staged with, each with the
+    /// number of insertions staged with it and not yet fired. (These
+    /// only differ if a `Clone` impl panicked while staging.)
+    hooks: Vec<(Hook, usize)>,
+}
+
+/// Stage `(a, b)`, which is about to be inserted into the cell
+/// `cell_id`, for the insert hooks.
+///
+/// Unsafe proof obligation:
+/// - the element types of `cell_id` must be `A` and `B`.
+pub(crate) unsafe fn stage_insert<A, B>(cell_id: Option<u64>, a: &A, b: &B) -> Staged {
+    stage(cell_id, &[Kind::Insert, Kind::Put], std::iter::once((a, b)))
+}
+
+/// Like `stage_insert`, for several insertions; they are fired in
+/// reverse order (as `Staged::fire` takes the most recently staged
+/// first).
+///
+/// Unsafe proof obligation:
+/// - the element types of `cell_id` must be `A` and `B`.
+pub(crate) unsafe fn stage_inserts<'e, A: 'e, B: 'e>(
+    cell_id: Option<u64>,
+    entries: impl Iterator<Item = (&'e A, &'e B)>,
+) -> Staged {
+    stage(cell_id, &[Kind::Insert, Kind::Put], entries)
+}
+
+/// Like `stage_insert`, for an insertion by a push, which the push
+/// hooks are invoked for as well.
+///
+/// Unsafe proof obligation:
+/// - the element types of `cell_id` must be `A` and `B`.
+pub(crate) unsafe fn stage_push<A, B>(cell_id: Option<u64>, a: &A, b: &B) -> Staged {
+    stage(
+        cell_id,
+        &[Kind::Insert, Kind::Push],
+        std::iter::once((a, b)),
+    )
+}
+
+/// Unsafe proof obligation:
+/// - the element types of `cell_id` must be `A` and `B`.
+unsafe fn stage<'e, A: 'e, B: 'e>(
+    cell_id: Option<u64>,
+    kinds: &[Kind],
+    entries: impl Iterator<Item = (&'e A, &'e B)>,
+) -> Staged {
+    let mut staged = Staged {
+        hooks: hooks(cell_id, kinds).into_iter().map(|h| (h, 0)).collect(),
+    };
+    if staged.hooks.is_empty() {
+        return staged;
+    }
+    for (a, b) in entries {
+        for (hook, count) in &mut staged.hooks {
+            // Unsafe proof obligation: the hook was registered for
+            // elements of type `A` and `B`, per the comment at the
+            // top of the module and our caller.
+            (hook.stage.unwrap())(
+                &*hook.data,
+                a as *const A as *const (),
+                b as *const B as *const (),
+            );
+            *count += 1;
+        }
+    }
+    staged
+}
+
+impl Staged {
+    /// Invoke the hooks with the staged insertions, most recent
+    /// first. When unwinding, they are discarded instead.
+    pub(crate) fn fire(mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        while self.hooks.iter().any(|&(_, count)| count > 0) {
+            for (hook, count) in &mut self.hooks {
+                if *count > 0 {
+                    *count -= 1;
+                    // Unsafe proof obligation: insert and push hooks
+                    // ignore the pointers.
+                    unsafe { (hook.fire)(&*hook.data, std::ptr::null(), std::ptr::null()) }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Staged {
+    fn drop(&mut self) {
+        for (hook, count) in &self.hooks {
+            for _ in 0..*count {
+                (hook.discard.unwrap())(&*hook.data);
+            }
+        }
+    }
+}
+
+/// Invoke the remove hooks of `cell_id` for `(a, b)`.
+///
+/// Unsafe proof obligation:
+/// - the element types of `cell_id` must be `A` and `B`.
+pub(crate) unsafe fn fire_remove<A, B>(cell_id: Option<u64>, a: &A, b: &B) {
+    for hook in hooks(cell_id, &[Kind::Remove]) {
+        // Unsafe proof obligation: as in `stage`.
+        (hook.fire)(
+            &*hook.data,
+            a as *const A as *const (),
+            b as *const B as *const (),
+        )
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(test)]
+
+use crate::{Mut, MutMap, MutVec};
+use std::rc::Rc;
+
+#[test]
+fn notified_after_mutation() {
+    let v = Rc::new(MutVec::new());
+    let seen = Rc::new(MutVec::new());
+
+    // The callback runs outside the lock, so it can read `v`.
+    let id = v.subscribe({
+        let v = v.clone();
+        let seen = seen.clone();
+        move || seen.push(v.len())
+    });
+
+    v.push(1);
+    v.push(2);
+    v.pop();
+    assert_eq!(seen.take(), vec![1, 2, 1]);
+
+    assert!(v.unsubscribe(id));
+    assert!(!v.unsubscribe(id));
+    v.push(3);
+    assert!(seen.is_empty());
+}
+
+#[test]
+fn map_and_cell() {
+    let count = Rc::new(Mut::new(0));
+    let m = MutMap::new();
+    let c = Mut::new(0);
+    m.subscribe(bump(&count));
+    c.subscribe(bump(&count));
+
+    m.insert(1, 2);
+    c.set(3);
+    assert_eq!(count.get(), 2);
+
+    // Reads do not notify.
+    m.get(&1);
+    c.get();
+    assert_eq!(count.get(), 2);
+}
+
+fn bump(count: &Rc<Mut<u32>>) -> impl Fn() {
+    let count = count.clone();
+    move || count.set(count.get() + 1)
+}
+
+#[test]
+fn set_hooks() {
+    let c = Rc::new(Mut::new(0));
+    let seen = Rc::new(MutVec::new());
+
+    // The hook runs outside the lock, so it can read `c`.
+    let id = c.on_set({
+        let c = c.clone();
+        let seen = seen.clone();
+        move |value: &u32| seen.push((*value, c.get()))
+    });
+
+    c.set(1);
+    assert_eq!(c.replace(2), 1);
+    assert_eq!(seen.take(), vec![(1, 1), (2, 2)]);
+
+    assert!(c.unsubscribe(id));
+    c.set(3);
+    assert!(seen.is_empty());
+}
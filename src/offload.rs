@@ -0,0 +1,65 @@
+//! Processing the contents of a collection on another thread. The
+//! collections are not `Sync`, so another thread cannot look at them
+//! in place; instead, `offload` moves the contents out, hands them to
+//! a worker thread, and puts the worker's result back.
+//!
+//! While the worker runs, the calling thread blocks (holding a read
+//! lock, as for a check-out), so nothing can observe the collection
+//! in its temporarily empty state.
+
+use crate::map::{MapStore, MutMap};
+use crate::vec::{ListChange, MutVec};
+use std::panic;
+use std::thread;
+
+mod test;
+
+/// Runs `process` on a scoped worker thread, propagating its panic
+/// (if any) to the caller.
+fn run_on_worker<C, R>(contents: C, process: impl FnOnce(C) -> (C, R) + Send) -> (C, R)
+where
+    C: Send,
+    R: Send,
+{
+    thread::scope(
+        |scope| match scope.spawn(move || process(contents)).join() {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    )
+}
+
+impl<T: Send> MutVec<T> {
+    /// Moves the elements to a worker thread, runs `process` on them
+    /// there, and then stores the vector it returns in `self`,
+    /// returning the rest of its result. If `process` panics, the
+    /// panic propagates to the caller and `self` is left empty.
+    #[track_caller]
+    pub fn offload<R: Send>(&self, process: impl FnOnce(Vec<T>) -> (Vec<T>, R) + Send) -> R {
+        let result = {
+            let mut data = self.data.check_out();
+            let (vec, result) = run_on_worker(std::mem::take(&mut *data), process);
+            *data = vec;
+            result
+        };
+        self.changed(ListChange::Reset);
+        result
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S>
+where
+    S: MapStore<K, V> + Send,
+{
+    /// Moves the map to a worker thread, runs `process` on it there,
+    /// and then stores the map it returns in `self`; see
+    /// `MutVec::offload`.
+    #[track_caller]
+    pub fn offload<R: Send>(&self, process: impl FnOnce(S) -> (S, R) + Send) -> R {
+        self.with_checked_out(|data| {
+            let (map, result) = run_on_worker(std::mem::take(data), process);
+            *data = map;
+            result
+        })
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn vec() {
+    let v = MutVec::from((1..=4).collect::<Vec<u64>>());
+    let sum = v.offload(|mut data| {
+        for n in &mut data {
+            *n *= 2;
+        }
+        let sum = data.iter().sum::<u64>();
+        (data, sum)
+    });
+    assert_eq!(sum, 20);
+    assert_eq!(v.into_inner(), vec![2, 4, 6, 8]);
+}
+
+#[test]
+fn map() {
+    let m: MutMap<&str, u32> = vec![("a", 1)].into_iter().collect();
+    let worker = m.offload(|mut data| {
+        data.insert("b", 2);
+        (data, thread::current().id())
+    });
+    assert_ne!(worker, thread::current().id());
+    assert_eq!(m.get("b"), Some(2));
+}
+
+#[test]
+fn panic_propagates() {
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        v.offload(|_| -> (Vec<i32>, ()) { panic!("worker failed") })
+    }));
+    assert!(result.is_err());
+    assert!(v.is_empty());
+    v.push(2);
+    assert_eq!(v.len(), 1);
+}
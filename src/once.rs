@@ -0,0 +1,128 @@
+use crate::mcell::MCell;
+use std::cell::Cell;
+
+mod test;
+
+/// A cell that can be written at most once. Initializers passed to
+/// `get_or_init` run while the thread holds a read lock, so they may
+/// freely read other mut-cells but cannot mutate them.
+pub struct MutOnce<T> {
+    data: MCell<Option<T>>,
+    initializing: Cell<bool>,
+}
+
+impl<T> MutOnce<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.data.borrow().is_some()
+    }
+
+    /// Load (a clone of) the value, if it has been set.
+    pub fn get(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().clone()
+    }
+
+    /// Set the value, returning `Err(value)` if it was already set.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.is_set() {
+            return Err(value);
+        }
+        self.data.set(Some(value));
+        Ok(())
+    }
+
+    /// Load the value, initializing it with `init` if it has not yet
+    /// been set.
+    ///
+    /// `init` executes with a read lock held. Calling `get_or_init`
+    /// on this same cell from within `init` panics.
+    #[track_caller]
+    pub fn get_or_init(&self, init: impl FnOnce() -> T) -> T
+    where
+        T: Clone,
+    {
+        if let Some(value) = self.get() {
+            return value;
+        }
+
+        if self.initializing.replace(true) {
+            panic!("reentrant initialization of `MutOnce`");
+        }
+        let value = {
+            let _reset = ResetOnDrop(&self.initializing);
+            let _lock = self.data.borrow();
+            init()
+        };
+        self.data.set(Some(value.clone()));
+        value
+    }
+
+    /// Take ownership of the value, if any, consuming the cell.
+    pub fn into_inner(self) -> Option<T> {
+        self.data.into_inner()
+    }
+}
+
+impl<T> Default for MutOnce<T> {
+    fn default() -> Self {
+        MutOnce {
+            data: MCell::new(None),
+            initializing: Cell::new(false),
+        }
+    }
+}
+
+impl<T> From<T> for MutOnce<T> {
+    fn from(value: T) -> Self {
+        MutOnce {
+            data: MCell::new(Some(value)),
+            initializing: Cell::new(false),
+        }
+    }
+}
+
+/// Clears the `initializing` flag even if the initializer panics.
+struct ResetOnDrop<'me>(&'me Cell<bool>);
+
+impl<'me> Drop for ResetOnDrop<'me> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+/// A value that is computed from `init` on first access. As with
+/// `MutOnce::get_or_init`, the initializer executes with a read lock
+/// held.
+pub struct MutLazy<T, F = fn() -> T> {
+    once: MutOnce<T>,
+    init: Cell<Option<F>>,
+}
+
+impl<T, F> MutLazy<T, F>
+where
+    F: FnOnce() -> T,
+{
+    pub fn new(init: F) -> Self {
+        MutLazy {
+            once: MutOnce::new(),
+            init: Cell::new(Some(init)),
+        }
+    }
+
+    /// Load the value, running the initializer if needed.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.once.get_or_init(|| match self.init.take() {
+            Some(init) => init(),
+            None => panic!("`MutLazy` initializer previously panicked"),
+        })
+    }
+}
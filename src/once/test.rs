@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use super::*;
+use crate::Mut;
+
+#[test]
+fn set_once() {
+    let c = MutOnce::new();
+    assert_eq!(c.get(), None);
+    assert_eq!(c.set(22), Ok(()));
+    assert_eq!(c.set(44), Err(44));
+    assert_eq!(c.get(), Some(22));
+    assert_eq!(c.get_or_init(|| 66), 22);
+}
+
+#[test]
+fn init_reads_other_cells() {
+    let m = Mut::new(22);
+    let c = MutOnce::new();
+    assert_eq!(c.get_or_init(|| m.get() * 2), 44);
+}
+
+#[test]
+#[should_panic(expected = "reentrant initialization")]
+fn reentrant_init() {
+    let c = MutOnce::new();
+    c.get_or_init(|| c.get_or_init(|| 22));
+}
+
+#[test]
+#[should_panic(expected = "lock is held")]
+fn init_cannot_mutate() {
+    let m = Mut::new(22);
+    let c = MutOnce::new();
+    c.get_or_init(|| m.set(44));
+}
+
+#[test]
+fn lazy() {
+    let m = Mut::new(22);
+    let l = MutLazy::new(|| m.get() + 1);
+    m.set(44);
+    assert_eq!(l.get(), 45);
+    m.set(66);
+    assert_eq!(l.get(), 45);
+}
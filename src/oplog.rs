@@ -0,0 +1,187 @@
+//! Event sourcing. Wrapping a collection in `Logged` appends each
+//! mutation made through the wrapper to an operation log; `replay`
+//! reconstructs the collection's state from such a log. The log can
+//! be persisted, shipped to another process, or used for crash
+//! recovery.
+//!
+//! As with `Recorded`, mutations made directly on the inner
+//! collection are not logged.
+
+use crate::history::{Journaled, MapOp, VecOp};
+use crate::map::MutMap;
+use crate::mcell::MCell;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use std::hash::Hash;
+use std::ops::Deref;
+use std::panic::Location;
+
+mod test;
+
+/// Construct a collection by applying `ops`, in order, to an empty
+/// one.
+pub fn replay<C>(ops: impl IntoIterator<Item = C::Op>) -> C
+where
+    C: Journaled + Default,
+{
+    let collection = C::default();
+    for op in ops {
+        collection.apply(op);
+    }
+    collection
+}
+
+/// An operation in the log of a `Logged` collection.
+#[derive(Clone, Debug)]
+pub struct LogEntry<Op> {
+    /// The position of the operation in the log.
+    pub step: usize,
+
+    pub op: Op,
+
+    /// Where the mutation was made.
+    pub location: &'static Location<'static>,
+}
+
+/// A collection whose mutations are appended to an operation log.
+/// Reads are available through `Deref`.
+pub struct Logged<C: Journaled> {
+    inner: C,
+    log: MCell<Vec<LogEntry<C::Op>>>,
+}
+
+impl<C: Journaled> Logged<C> {
+    /// Wrap `inner`, starting with an empty log. Replaying the log
+    /// reproduces `inner` only if `inner` starts out empty.
+    pub fn new(inner: C) -> Self {
+        Logged {
+            inner,
+            log: MCell::new(vec![]),
+        }
+    }
+
+    /// Reconstruct a collection from `ops`, keeping them as the
+    /// start of its log.
+    #[track_caller]
+    pub fn from_ops(ops: Vec<C::Op>) -> Self
+    where
+        C: Default,
+        C::Op: Clone,
+    {
+        let inner = replay(ops.iter().cloned());
+        let location = Location::caller();
+        let log = ops
+            .into_iter()
+            .enumerate()
+            .map(|(step, op)| LogEntry { step, op, location })
+            .collect();
+        Logged {
+            inner,
+            log: MCell::new(log),
+        }
+    }
+
+    /// Apply `op` to the inner collection and append it to the log.
+    #[track_caller]
+    pub fn record(&self, op: C::Op)
+    where
+        C::Op: Clone,
+    {
+        self.append(op.clone(), Location::caller());
+        self.inner.apply(op);
+    }
+
+    /// Append `op`, already applied (or about to be), to the log.
+    fn append(&self, op: C::Op, location: &'static Location<'static>) {
+        let mut log = self.log.check_out();
+        let step = log.len();
+        log.push(LogEntry { step, op, location });
+    }
+
+    /// A copy of the operations logged so far.
+    pub fn ops(&self) -> Vec<C::Op>
+    where
+        C::Op: Clone,
+    {
+        self.log.borrow().iter().map(|e| e.op.clone()).collect()
+    }
+
+    /// A copy of the log entries so far, with the locations of the
+    /// mutations. See also the `inspect` module.
+    pub fn entries(&self) -> Vec<LogEntry<C::Op>>
+    where
+        C::Op: Clone,
+    {
+        self.log.borrow().clone()
+    }
+
+    /// Take the operations logged so far, leaving the log empty
+    /// (e.g., once they have been persisted).
+    pub fn take_ops(&self) -> Vec<C::Op> {
+        self.log.take().into_iter().map(|e| e.op).collect()
+    }
+
+    /// Take ownership of the inner collection, discarding the log.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Journaled> Deref for Logged<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<T: Clone> Logged<Mut<T>> {
+    /// Store `value` into the cell.
+    #[track_caller]
+    pub fn set(&self, value: T) {
+        self.record(value);
+    }
+}
+
+impl<T: Clone> Logged<MutVec<T>> {
+    /// Push `value` onto the end of the vector.
+    #[track_caller]
+    pub fn push(&self, value: T) {
+        self.record(VecOp::Push(value));
+    }
+
+    /// Pop a value from the end of the vector, if any.
+    #[track_caller]
+    pub fn pop(&self) -> Option<T> {
+        let value = self.inner.get(self.inner.len().checked_sub(1)?);
+        self.record(VecOp::Pop);
+        value
+    }
+
+    /// Replace the contents of the vector with `v`.
+    #[track_caller]
+    pub fn replace(&self, v: Vec<T>) {
+        self.record(VecOp::Replace(v));
+    }
+}
+
+impl<K, V> Logged<MutMap<K, V>>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    /// Inserts `(key, value)` into the map.
+    #[track_caller]
+    pub fn insert(&self, key: K, value: V) {
+        self.record(MapOp::Insert(key, value));
+    }
+
+    /// Removes `key` from the map, returning its value. Nothing is
+    /// logged if the key was not present.
+    #[track_caller]
+    pub fn remove(&self, key: K) -> Option<V> {
+        let value = self.inner.remove_shared(&key)?;
+        self.append(MapOp::Remove(key), Location::caller());
+        Some(value)
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn replay_vec() {
+    let v = Logged::new(MutVec::new());
+    v.push(1);
+    v.push(2);
+    assert_eq!(v.pop(), Some(2));
+    v.push(3);
+
+    let ops = v.ops();
+    assert_eq!(
+        ops,
+        vec![VecOp::Push(1), VecOp::Push(2), VecOp::Pop, VecOp::Push(3)]
+    );
+    let copy: MutVec<u32> = replay(ops);
+    assert_eq!(copy.into_inner(), v.into_inner().into_inner());
+}
+
+#[test]
+fn resume_map() {
+    let m = Logged::new(MutMap::new());
+    m.insert("a", 1);
+    m.insert("b", 2);
+    let persisted = m.take_ops();
+    m.remove("a");
+    assert_eq!(m.take_ops(), vec![MapOp::Remove("a")]);
+
+    let restored: Logged<MutMap<&str, u32>> = Logged::from_ops(persisted);
+    assert_eq!(restored.get("a"), Some(1));
+    restored.remove("a");
+    assert_eq!(restored.ops().len(), 3);
+    assert_eq!(
+        restored.into_inner().into_inner(),
+        m.into_inner().into_inner()
+    );
+}
+
+#[test]
+fn remove_logs_only_effective_removals() {
+    let m = Logged::new(MutMap::new());
+    m.insert("a", 1);
+    assert_eq!(m.remove("b"), None);
+    assert_eq!(m.remove("a"), Some(1));
+    assert_eq!(m.remove("a"), None);
+    assert_eq!(m.ops(), vec![MapOp::Insert("a", 1), MapOp::Remove("a")]);
+    assert_eq!(m.entries()[1].step, 1);
+}
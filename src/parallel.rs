@@ -0,0 +1,42 @@
+//! Parallel iteration, enabled by the `rayon` feature. The
+//! collections are not `Sync`, so rayon's worker threads cannot read
+//! them in place; instead, `par_iter` clones the contents (under a
+//! read borrow) and iterates over the clones in parallel. Mutations
+//! made after the call are not seen by the iterator.
+
+use crate::map::MutMap;
+use crate::vec::MutVec;
+use rayon::vec::IntoIter;
+use std::hash::Hash;
+
+mod test;
+
+impl<T> MutVec<T> {
+    /// A parallel iterator over clones of the current elements.
+    pub fn par_iter(&self) -> IntoIter<T>
+    where
+        T: Clone + Send,
+    {
+        rayon::iter::IntoParallelIterator::into_par_iter(self.data.borrow().clone())
+    }
+}
+
+impl<K, V> MutMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// A parallel iterator over clones of the current entries.
+    pub fn par_iter(&self) -> IntoIter<(K, V)>
+    where
+        K: Clone + Send,
+        V: Clone + Send,
+    {
+        let entries: Vec<(K, V)> = self
+            .data
+            .borrow()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        rayon::iter::IntoParallelIterator::into_par_iter(entries)
+    }
+}
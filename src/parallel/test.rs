@@ -0,0 +1,25 @@
+#![cfg(test)]
+
+use super::*;
+use rayon::iter::ParallelIterator;
+
+#[test]
+fn par_iter() {
+    let v: MutVec<u64> = (1..=100).collect();
+    assert_eq!(v.par_iter().sum::<u64>(), 5050);
+
+    let m = MutMap::new();
+    m.insert("a", 1);
+    m.insert("b", 2);
+    let mut keys: Vec<_> = m.par_iter().map(|(k, v)| format!("{}{}", k, v)).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["a1", "b2"]);
+}
+
+#[test]
+fn snapshot() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let iter = v.par_iter();
+    v.push(4);
+    assert_eq!(iter.count(), 3);
+}
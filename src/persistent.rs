@@ -0,0 +1,212 @@
+//! Collections backed by the persistent data structures of the `im`
+//! crate (enabled with the `im` feature). Cloning these, or taking a
+//! `snapshot`, is O(1): the copies share structure, and only the
+//! parts touched by later mutations are copied.
+//!
+//! For maps, any `im` map can serve as the store of a `MutMap`; see
+//! `MutImMap`.
+
+use crate::map::{MapLookup, MapStore};
+use crate::mcell::MCell;
+use crate::observe;
+use crate::observe::SubscriptionId;
+use crate::MutMap;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+mod test;
+
+/// A `MutMap` backed by an `im::HashMap`, whose `Clone` is O(1).
+pub type MutImMap<K, V> = MutMap<K, V, im::HashMap<K, V>>;
+
+pub struct MutImVec<T: Clone> {
+    data: MCell<im::Vector<T>>,
+}
+
+impl<T: Clone> MutImVec<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// The equivalent of `self[index]` -- load the element at the
+    /// given index, panicking if there is no such element.
+    pub fn at(&self, index: usize) -> T {
+        self.get(index).unwrap()
+    }
+
+    /// Attempt to get the element at the given `index`, returning
+    /// `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let data = self.data.borrow();
+        Some(data.get(index)?.clone())
+    }
+
+    /// Push `value` onto the end of the vector. Copying shared
+    /// structure may clone elements, so this checks the vector out:
+    /// readers of *this* vector will see it empty in the meantime.
+    pub fn push(&self, value: T) {
+        self.data.check_out().push_back(value);
+    }
+
+    /// Pop a value from the end of the vector, if any.
+    pub fn pop(&self) -> Option<T> {
+        self.data.check_out().pop_back()
+    }
+
+    /// Replace the contents with `v`, returning the old contents.
+    pub fn replace(&self, v: im::Vector<T>) -> im::Vector<T> {
+        self.data.replace(v)
+    }
+
+    /// A frozen view of the current contents, in O(1).
+    pub fn snapshot(&self) -> im::Vector<T> {
+        self.data.borrow().clone()
+    }
+
+    /// Iterate over a snapshot of the current contents. Unlike
+    /// `MutVec::iter`, later mutations of `self` are not observed.
+    pub fn iter_snapshot(&self) -> im::vector::ConsumingIter<T> {
+        self.snapshot().into_iter()
+    }
+
+    /// Take ownership of our internal vector, consuming `self`.
+    pub fn into_inner(self) -> im::Vector<T> {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+}
+
+impl<T: Clone> Clone for MutImVec<T> {
+    fn clone(&self) -> Self {
+        MutImVec::from(self.snapshot())
+    }
+}
+
+impl<T: Clone> std::iter::FromIterator<T> for MutImVec<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        MutImVec::from(iter.into_iter().collect::<im::Vector<T>>())
+    }
+}
+
+impl<T: Clone> Default for MutImVec<T> {
+    fn default() -> Self {
+        Self::from(im::Vector::new())
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for MutImVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+impl<T: Clone> From<im::Vector<T>> for MutImVec<T> {
+    fn from(v: im::Vector<T>) -> Self {
+        MutImVec {
+            data: MCell::new(v),
+        }
+    }
+}
+
+impl<K, V, S> MapStore<K, V> for im::HashMap<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    S: BuildHasher + Default,
+{
+    fn len(&self) -> usize {
+        im::HashMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        im::HashMap::insert(self, key, value)
+    }
+}
+
+impl<K, V, S, Q> MapLookup<Q, V> for im::HashMap<K, V, S>
+where
+    K: Eq + Hash + Clone + Borrow<Q>,
+    V: Clone,
+    S: BuildHasher,
+    Q: ?Sized + Eq + Hash,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        im::HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        im::HashMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        im::HashMap::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        im::HashMap::remove_with_key(self, key)
+    }
+}
+
+impl<K, V> MapStore<K, V> for im::OrdMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn len(&self) -> usize {
+        im::OrdMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        im::OrdMap::insert(self, key, value)
+    }
+}
+
+impl<K, V, Q> MapLookup<Q, V> for im::OrdMap<K, V>
+where
+    K: Ord + Clone + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Ord,
+{
+    type Key = K;
+
+    fn get(&self, key: &Q) -> Option<&V> {
+        im::OrdMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &Q) -> Option<&mut V> {
+        im::OrdMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Option<V> {
+        im::OrdMap::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Q) -> Option<(K, V)> {
+        im::OrdMap::remove_with_key(self, key)
+    }
+}
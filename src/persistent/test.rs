@@ -0,0 +1,37 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn snapshot_is_unaffected() {
+    let v: MutImVec<u32> = (0..4).collect();
+    let snapshot = v.snapshot();
+    v.push(4);
+    assert_eq!(v.pop(), Some(4));
+    v.pop();
+    assert_eq!(snapshot.len(), 4);
+    assert_eq!(v.iter_snapshot().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(format!("{:?}", v.clone()), "[0, 1, 2]");
+}
+
+#[test]
+fn im_map_store() {
+    let mut m: MutImMap<String, u32> = MutMap::default();
+    m.insert("a".to_string(), 1);
+    let copy = m.clone();
+    m.insert("b".to_string(), 2);
+    assert_eq!(m.remove("a"), Some(1));
+    assert_eq!(m.len(), 1);
+    assert_eq!(copy.get("a"), Some(1));
+    assert_eq!(copy.get("b"), None);
+}
+
+#[test]
+fn ord_map_store() {
+    let m: MutMap<u32, u32, im::OrdMap<u32, u32>> = (0..5).map(|i| (4 - i, i)).collect();
+    assert_eq!(m.get(&0), Some(4));
+    assert_eq!(
+        m.into_inner().keys().copied().collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+}
@@ -0,0 +1,95 @@
+//! Optional poisoning, as with `std::sync::Mutex`. When a panic
+//! unwinds through an update of a cell (a `borrow_mut` or
+//! `check_out`-style operation, including the user code it runs, such
+//! as a `Hash` impl or a `with_element_mut` closure), the contents
+//! may be left half-modified. A cell with poisoning enabled records
+//! this, and any later access to it panics with `Poisoned` until
+//! `unpoison` is called. Updates that start while the thread is
+//! already panicking (say, to roll back a `transaction`) are not
+//! interrupted by that panic, so they do not poison the cell.
+//!
+//! Poisoning is off by default. Consuming the cell (`into_inner`) is
+//! always allowed, and so is checking and clearing the poison.
+
+use crate::error::Poisoned;
+use crate::map::MutMap;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+
+mod test;
+
+fn check(poisoned: bool) -> Result<(), Poisoned> {
+    if poisoned {
+        Err(Poisoned)
+    } else {
+        Ok(())
+    }
+}
+
+impl<T> Mut<T> {
+    /// Enable poisoning for this cell; see the `poison` module.
+    pub fn enable_poisoning(&self) {
+        self.data.enable_poisoning()
+    }
+
+    /// True if an update of this cell was interrupted by a panic
+    /// (and poisoning is enabled).
+    pub fn is_poisoned(&self) -> bool {
+        self.data.is_poisoned()
+    }
+
+    /// Like `is_poisoned`, but as a `Result`.
+    pub fn check_poisoned(&self) -> Result<(), Poisoned> {
+        check(self.is_poisoned())
+    }
+
+    /// Clear the poison, allowing the cell to be accessed again, and
+    /// return whether it was poisoned. Poisoning remains enabled.
+    pub fn unpoison(&self) -> bool {
+        self.data.unpoison()
+    }
+}
+
+impl<T> MutVec<T> {
+    /// Enable poisoning for this vector; see the `poison` module.
+    pub fn enable_poisoning(&self) {
+        self.data.enable_poisoning()
+    }
+
+    /// See `Mut::is_poisoned`.
+    pub fn is_poisoned(&self) -> bool {
+        self.data.is_poisoned()
+    }
+
+    /// See `Mut::check_poisoned`.
+    pub fn check_poisoned(&self) -> Result<(), Poisoned> {
+        check(self.is_poisoned())
+    }
+
+    /// See `Mut::unpoison`.
+    pub fn unpoison(&self) -> bool {
+        self.data.unpoison()
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S> {
+    /// Enable poisoning for this map; see the `poison` module.
+    pub fn enable_poisoning(&self) {
+        self.data.enable_poisoning()
+    }
+
+    /// See `Mut::is_poisoned`.
+    pub fn is_poisoned(&self) -> bool {
+        self.data.is_poisoned()
+    }
+
+    /// See `Mut::check_poisoned`.
+    pub fn check_poisoned(&self) -> Result<(), Poisoned> {
+        check(self.is_poisoned())
+    }
+
+    /// See `Mut::unpoison`.
+    pub fn unpoison(&self) -> bool {
+        self.data.unpoison()
+    }
+}
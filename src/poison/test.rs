@@ -0,0 +1,56 @@
+#![cfg(test)]
+
+use super::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn panic_during_update_poisons() {
+    let v = MutVec::from(vec![1, 2]);
+    v.enable_poisoning();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        v.with_element_mut(0, |x| {
+            *x = 10;
+            panic!("halfway");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(v.is_poisoned());
+    assert_eq!(v.check_poisoned(), Err(Poisoned));
+
+    let err = catch_unwind(AssertUnwindSafe(|| v.len())).unwrap_err();
+    assert_eq!(*err.downcast::<String>().unwrap(), Poisoned.to_string());
+
+    assert!(v.unpoison());
+    assert!(!v.unpoison());
+    assert_eq!(v.into_inner(), vec![10, 2]);
+}
+
+#[test]
+fn off_by_default() {
+    let m: MutMap<u32, u32> = MutMap::new();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        m.insert(1, 1);
+        crate::borrow_mut2(&m, &Mut::new(0), |_, _| panic!("oops"));
+    }));
+    assert!(result.is_err());
+    assert!(!m.is_poisoned());
+    assert_eq!(m.get(&1), Some(1));
+}
+
+#[test]
+fn only_panics_inside_an_update_poison() {
+    let cell = Mut::new(1);
+    cell.enable_poisoning();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _value = cell.get();
+        panic!("unrelated");
+    }));
+    assert!(result.is_err());
+    assert_eq!(cell.check_poisoned(), Ok(()));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        crate::borrow_mut2(&cell, &Mut::new(0), |_, _| panic!("oops"));
+    }));
+    assert!(result.is_err());
+    assert!(cell.is_poisoned());
+}
@@ -0,0 +1,30 @@
+//! The crate's commonly used types, traits, and macros, for glob
+//! import:
+//!
+//! ```
+//! use mutable::prelude::*;
+//!
+//! let v = MutVec::new();
+//! v.push(Mut::new(22));
+//! ```
+
+pub use crate::history::{History, Journaled, Recorded};
+pub use crate::multi::{borrow_mut2, borrow_mut3};
+pub use crate::oplog::Logged;
+pub use crate::revision::{DirtySet, Revision, Tracked};
+pub use crate::transaction::transaction;
+pub use crate::{mut_static, mutmap, mutvec, project};
+pub use crate::{
+    Computed, DoubleBufferedMut, EnumKey, FrozenMap, FrozenVec, IterMode, Lens, LimitPolicy, Mut,
+    MutAny, MutArcCell, MutArray, MutBTreeMap, MutBitSet, MutCounter, MutCowMap, MutCowVec,
+    MutDeque, MutEnumMap, MutGraph, MutGrid, MutIdGen, MutIndexSet, MutIntervalMap, MutLazy,
+    MutMap, MutMultiMap, MutOnce, MutQueue, MutRc, MutRegistry, MutRingBuffer, MutScopedMap,
+    MutSet, MutShardedMap, MutSlab, MutSmallMap, MutSortedVec, MutString, MutTree, MutValue,
+    MutVec, MutWeak, MutWeakMap, MutWorkQueue, SyncMut, SyncMutMap, SyncMutVec,
+};
+
+#[cfg(feature = "derive")]
+pub use crate::Mutable;
+
+#[cfg(feature = "im")]
+pub use crate::{MutImMap, MutImVec};
@@ -0,0 +1,65 @@
+//! Multi-line rendering of collections for logs and REPLs, via
+//! `MutVec::pretty` and `MutMap::pretty`. As with `Display`, the
+//! whole collection is rendered under a single borrow.
+
+use crate::{MutMap, MutVec};
+use std::fmt;
+use std::hash::Hash;
+
+mod test;
+
+const INDENT: &str = "    ";
+
+/// Displays a collection with one element per line.
+pub struct Pretty<'a, C> {
+    collection: &'a C,
+}
+
+impl<'a, C> Pretty<'a, C> {
+    pub(crate) fn new(collection: &'a C) -> Self {
+        Pretty { collection }
+    }
+}
+
+/// Write `item` on its own line, indenting any lines it spans.
+fn write_item(f: &mut fmt::Formatter<'_>, item: fmt::Arguments<'_>) -> fmt::Result {
+    let item = item.to_string();
+    writeln!(
+        f,
+        "{}{},",
+        INDENT,
+        item.replace('\n', &format!("\n{}", INDENT))
+    )
+}
+
+impl<T: fmt::Display> fmt::Display for Pretty<'_, MutVec<T>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.collection.data.borrow();
+        if data.is_empty() {
+            return write!(f, "[]");
+        }
+        writeln!(f, "[")?;
+        for value in data.iter() {
+            write_item(f, format_args!("{}", value))?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<K, V> fmt::Display for Pretty<'_, MutMap<K, V>>
+where
+    K: fmt::Display + Eq + Hash,
+    V: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.collection.data.borrow();
+        if data.is_empty() {
+            return write!(f, "{{}}");
+        }
+        writeln!(f, "{{")?;
+        for (key, value) in data.iter() {
+            write_item(f, format_args!("{}: {}", key, value))?;
+        }
+        write!(f, "}}")
+    }
+}
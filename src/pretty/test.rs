@@ -0,0 +1,26 @@
+#![cfg(test)]
+
+use super::*;
+use crate::Mut;
+
+#[test]
+fn display() {
+    let v = MutVec::from(vec![Mut::new(1), Mut::new(2)]);
+    assert_eq!(v.to_string(), "[1, 2]");
+    let m: MutMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    assert_eq!(m.to_string(), "{a: 1, b: 2}");
+    assert_eq!(MutVec::<u32>::new().to_string(), "[]");
+}
+
+#[test]
+fn pretty() {
+    let v = MutVec::from(vec![1, 2]);
+    assert_eq!(v.pretty().to_string(), "[\n    1,\n    2,\n]");
+    assert_eq!(MutVec::<u32>::new().pretty().to_string(), "[]");
+
+    let m: MutMap<&str, String> = vec![("a", v.pretty().to_string())].into_iter().collect();
+    assert_eq!(
+        m.pretty().to_string(),
+        "{\n    a: [\n        1,\n        2,\n    ],\n}"
+    );
+}
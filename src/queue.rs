@@ -0,0 +1,46 @@
+use crate::mcell::MCell;
+
+mod test;
+
+/// A buffer of pending items, meant for the pattern where items are
+/// pushed from anywhere and periodically drained, all at once, by a
+/// single consumer (e.g., deferred events in an event loop).
+pub struct MutQueue<T> {
+    data: MCell<Vec<T>>,
+}
+
+impl<T> MutQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Push `item` onto the end of the queue.
+    pub fn push(&self, item: T) {
+        self.data.borrow_mut().push(item);
+    }
+
+    /// Remove all pending items, in the order they were pushed. This
+    /// is O(1): the internal buffer is swapped for an empty one.
+    ///
+    /// Items pushed while the caller processes the result are left in
+    /// the queue for the next call to `drain_all`.
+    pub fn drain_all(&self) -> Vec<T> {
+        self.data.take()
+    }
+}
+
+impl<T> Default for MutQueue<T> {
+    fn default() -> Self {
+        MutQueue {
+            data: MCell::new(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn push_while_draining() {
+    let q = MutQueue::new();
+    q.push(1);
+    q.push(2);
+
+    // Handling an event may enqueue further events; those are seen by
+    // the next drain.
+    let mut seen = vec![];
+    for event in q.drain_all() {
+        seen.push(event);
+        q.push(event * 10);
+    }
+    assert_eq!(seen, vec![1, 2]);
+    assert_eq!(q.drain_all(), vec![10, 20]);
+    assert!(q.is_empty());
+}
@@ -0,0 +1,85 @@
+//! Escape hatches that bypass the thread lock, for FFI callbacks and
+//! audited hot loops.
+//!
+//! `as_ptr` returns a pointer to the contents of a cell or
+//! collection; `with_unchecked` runs a closure with a mutable
+//! reference to them, without acquiring the lock. Both leave it to
+//! the caller to uphold what the lock normally guarantees. For as long
+//! as the pointer is dereferenced or the closure runs, the caller
+//! must ensure that:
+//!
+//! * no other reference to the contents exists, whether held by a
+//!   guard (e.g., from `get_ref` or a `Lens`), by a closure the crate
+//!   is running (e.g., `with_element`, a `check_out`-style update, or
+//!   an `offload` worker), or by another `with_unchecked`;
+//! * the contents are not accessed through the safe API in the
+//!   meantime, as that would create such a reference (and, while an
+//!   update is in progress, find the contents temporarily replaced by
+//!   an empty value);
+//! * the pointer is not used on another thread, nor after the cell
+//!   is dropped or moved.
+//!
+//! Changes made this way are not reported: subscribers are not
+//! notified, the change revision and the `version` are not bumped (so
+//! `Computed` values and fingerprints may go stale), and element
+//! hooks do not run.
+
+use crate::map::MutMap;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+
+mod test;
+
+impl<T> Mut<T> {
+    /// A pointer to the value. See the `raw` module for the
+    /// obligations that come with dereferencing it.
+    pub fn as_ptr(&self) -> *mut T {
+        self.data.as_ptr()
+    }
+
+    /// Runs `f` with a mutable reference to the value, without
+    /// acquiring the lock.
+    ///
+    /// # Safety
+    ///
+    /// See the `raw` module.
+    pub unsafe fn with_unchecked<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut *self.as_ptr())
+    }
+}
+
+impl<T> MutVec<T> {
+    /// A pointer to the underlying vector. See the `raw` module for
+    /// the obligations that come with dereferencing it.
+    pub fn as_ptr(&self) -> *mut Vec<T> {
+        self.data.as_ptr()
+    }
+
+    /// Runs `f` with a mutable reference to the underlying vector,
+    /// without acquiring the lock.
+    ///
+    /// # Safety
+    ///
+    /// See the `raw` module.
+    pub unsafe fn with_unchecked<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        f(&mut *self.as_ptr())
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S> {
+    /// A pointer to the underlying map. See the `raw` module for the
+    /// obligations that come with dereferencing it.
+    pub fn as_ptr(&self) -> *mut S {
+        self.data.as_ptr()
+    }
+
+    /// Runs `f` with a mutable reference to the underlying map,
+    /// without acquiring the lock.
+    ///
+    /// # Safety
+    ///
+    /// See the `raw` module.
+    pub unsafe fn with_unchecked<R>(&self, f: impl FnOnce(&mut S) -> R) -> R {
+        f(&mut *self.as_ptr())
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn with_unchecked() {
+    let v = MutVec::from(vec![1, 2, 3]);
+    let sum: i32 = unsafe { v.with_unchecked(|data| data.iter().sum()) };
+    assert_eq!(sum, 6);
+    unsafe { v.with_unchecked(|data| data.push(4)) };
+    assert_eq!(v.len(), 4);
+
+    let m: MutMap<&str, u32> = MutMap::new();
+    unsafe { m.with_unchecked(|data| data.insert("a", 1)) };
+    assert_eq!(m.get("a"), Some(1));
+}
+
+#[test]
+fn as_ptr() {
+    let cell = Mut::new(22);
+    let ptr = cell.as_ptr();
+    // Safe: no other reference to the value exists while we write.
+    unsafe { *ptr = 44 };
+    assert_eq!(cell.get(), 44);
+    assert_eq!(cell.as_ptr(), ptr);
+}
+
+#[test]
+fn not_reported() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let cell = Mut::new(1);
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+    cell.subscribe(move || c.set(c.get() + 1));
+    unsafe { cell.with_unchecked(|x| *x += 1) };
+    assert_eq!(count.get(), 0);
+    assert_eq!(cell.get(), 2);
+}
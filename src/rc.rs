@@ -0,0 +1,124 @@
+use crate::mutbl::Mut;
+use std::fmt;
+use std::rc::Rc;
+use std::rc::Weak;
+
+mod test;
+
+/// A shared, reference-counted `Mut<T>` -- the equivalent of
+/// `Rc<Mut<T>>`, but with the cell operations available directly.
+pub struct MutRc<T> {
+    pub(crate) rc: Rc<Mut<T>>,
+}
+
+/// A weak reference to the cell in a `MutRc<T>`.
+pub struct MutWeak<T> {
+    weak: Weak<Mut<T>>,
+}
+
+impl<T> MutRc<T> {
+    pub fn new(value: T) -> Self {
+        MutRc {
+            rc: Rc::new(Mut::new(value)),
+        }
+    }
+
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.rc.get()
+    }
+
+    pub fn set(&self, new_value: T) {
+        self.rc.set(new_value)
+    }
+
+    pub fn replace(&self, new_value: T) -> T {
+        self.rc.replace(new_value)
+    }
+
+    pub fn downgrade(this: &Self) -> MutWeak<T> {
+        MutWeak {
+            weak: Rc::downgrade(&this.rc),
+        }
+    }
+
+    /// True if `a` and `b` refer to the same cell.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Rc::ptr_eq(&a.rc, &b.rc)
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        Rc::strong_count(&this.rc)
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        Rc::weak_count(&this.rc)
+    }
+}
+
+impl<T> MutWeak<T> {
+    /// A weak reference that never upgrades.
+    pub fn new() -> Self {
+        MutWeak { weak: Weak::new() }
+    }
+
+    pub fn upgrade(&self) -> Option<MutRc<T>> {
+        Some(MutRc {
+            rc: self.weak.upgrade()?,
+        })
+    }
+
+    /// True if `a` and `b` refer to the same cell (or are both
+    /// dangling references created by `MutWeak::new`).
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Weak::ptr_eq(&a.weak, &b.weak)
+    }
+}
+
+impl<T> Clone for MutRc<T> {
+    fn clone(&self) -> Self {
+        MutRc {
+            rc: self.rc.clone(),
+        }
+    }
+}
+
+impl<T> Clone for MutWeak<T> {
+    fn clone(&self) -> Self {
+        MutWeak {
+            weak: self.weak.clone(),
+        }
+    }
+}
+
+impl<T: Default> Default for MutRc<T> {
+    fn default() -> Self {
+        MutRc::new(T::default())
+    }
+}
+
+impl<T> Default for MutWeak<T> {
+    fn default() -> Self {
+        MutWeak::new()
+    }
+}
+
+impl<T> From<T> for MutRc<T> {
+    fn from(value: T) -> Self {
+        MutRc::new(value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.rc, f)
+    }
+}
+
+impl<T> fmt::Debug for MutWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(MutWeak)")
+    }
+}
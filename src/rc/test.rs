@@ -0,0 +1,25 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn shared_updates() {
+    let a = MutRc::new(22);
+    let b = a.clone();
+    b.set(44);
+    assert_eq!(a.get(), 44);
+    assert!(MutRc::ptr_eq(&a, &b));
+    assert!(!MutRc::ptr_eq(&a, &MutRc::new(44)));
+    assert_eq!(MutRc::strong_count(&a), 2);
+    assert_eq!(format!("{:?}", a), "Mut(44)");
+}
+
+#[test]
+fn weak_upgrade() {
+    let a = MutRc::new(String::from("hi"));
+    let w = MutRc::downgrade(&a);
+    assert_eq!(w.upgrade().unwrap().get(), "hi");
+    drop(a);
+    assert!(w.upgrade().is_none());
+    assert!(MutWeak::<u32>::new().upgrade().is_none());
+}
@@ -0,0 +1,75 @@
+use crate::mcell::MCell;
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+mod test;
+
+/// A map holding at most one value per type, useful for threading
+/// "context" objects through a program without a dedicated field for
+/// each one.
+pub struct MutRegistry {
+    data: MCell<HashMap<TypeId, Box<dyn Any>>>,
+}
+
+impl MutRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Stores `value` as the registered value for `T`, returning the
+    /// previous one (if any). During the insertion operation, all
+    /// mut-cells are locked and read-only. Attempts to read from
+    /// *this* registry during insertion will find it empty.
+    pub fn insert<T: 'static>(&self, value: T) -> Option<T> {
+        let old = self
+            .data
+            .check_out()
+            .insert(TypeId::of::<T>(), Box::new(value))?;
+        Some(*old.downcast::<T>().unwrap())
+    }
+
+    /// Removes the registered value for `T`, if any.
+    pub fn remove<T: 'static>(&self) -> Option<T> {
+        let old = self.data.check_out().remove(&TypeId::of::<T>())?;
+        Some(*old.downcast::<T>().unwrap())
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.data.borrow().contains_key(&TypeId::of::<T>())
+    }
+
+    /// Loads (a clone of) the registered value for `T`, if any.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: 'static + Clone,
+    {
+        self.with(|value: &T| value.clone())
+    }
+
+    /// Invokes `op` with a reference to the registered value for `T`,
+    /// returning `None` if there is no such value. While `op`
+    /// executes, all mut-cells are read-only.
+    #[track_caller]
+    pub fn with<T: 'static, R>(&self, op: impl FnOnce(&T) -> R) -> Option<R> {
+        let data = self.data.borrow();
+        let value = data.get(&TypeId::of::<T>())?;
+        Some(op(value.downcast_ref::<T>().unwrap()))
+    }
+}
+
+impl Default for MutRegistry {
+    fn default() -> Self {
+        MutRegistry {
+            data: MCell::new(HashMap::new()),
+        }
+    }
+}
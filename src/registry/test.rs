@@ -0,0 +1,38 @@
+#![cfg(test)]
+
+use super::*;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Config {
+    verbose: bool,
+}
+
+#[test]
+fn insert_get_remove() {
+    let r = MutRegistry::new();
+    assert_eq!(r.get::<Config>(), None);
+
+    assert_eq!(r.insert(Config { verbose: false }), None);
+    assert_eq!(r.insert(22_u32), None);
+    assert_eq!(
+        r.insert(Config { verbose: true }),
+        Some(Config { verbose: false })
+    );
+
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.get::<Config>(), Some(Config { verbose: true }));
+    assert_eq!(r.get::<u32>(), Some(22));
+    assert!(!r.contains::<u64>());
+
+    assert_eq!(r.remove::<u32>(), Some(22));
+    assert!(!r.contains::<u32>());
+}
+
+#[test]
+fn with_non_clone() {
+    let r = MutRegistry::new();
+    r.insert(vec![Rc::new(1_u32), Rc::new(2)]);
+    assert_eq!(r.with(|v: &Vec<Rc<u32>>| v.len()), Some(2));
+    assert_eq!(r.with(|v: &Vec<u32>| v.len()), None);
+}
@@ -0,0 +1,139 @@
+//! Cheap "did anything change?" queries, for cache invalidation.
+//!
+//! Every mutation of a tracked cell advances the thread's current
+//! `Revision`. A `DirtySet` groups the cells some computation depends
+//! on and reports which of them changed after a given revision.
+//!
+//! For a single collection, `version` is cheaper still: a per-cell
+//! count of mutations, which needs no tracking (nor an id), and stays
+//! with the cell if it is sent to another thread.
+//!
+//! ```
+//! use mutable::MutVec;
+//!
+//! let v = MutVec::new();
+//! let seen = v.version();
+//! v.push(1);
+//! assert_ne!(v.version(), seen);
+//! ```
+
+use crate::map::MutMap;
+use crate::mcell::track;
+use crate::mcell::MCell;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+
+mod test;
+
+/// A point in the thread's history of mutations to tracked cells.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Revision(u64);
+
+impl Revision {
+    /// The revision as of now.
+    pub fn current() -> Revision {
+        Revision(track::current_revision())
+    }
+}
+
+/// Identifies a cell (a `Mut`, `MutVec`, and so forth).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CellId(pub(crate) u64);
+
+/// Types built on a single cell, whose mutations can be tracked.
+pub trait Tracked {
+    /// The id of the underlying cell. This also enables change
+    /// tracking for the cell, if it was not already enabled.
+    fn cell_id(&self) -> CellId;
+}
+
+impl<T> Tracked for MCell<T> {
+    fn cell_id(&self) -> CellId {
+        CellId(self.id())
+    }
+}
+
+impl<T> Tracked for Mut<T> {
+    fn cell_id(&self) -> CellId {
+        self.data.cell_id()
+    }
+}
+
+impl<T> Tracked for MutVec<T> {
+    fn cell_id(&self) -> CellId {
+        self.data.cell_id()
+    }
+}
+
+impl<K, V, S> Tracked for MutMap<K, V, S> {
+    fn cell_id(&self) -> CellId {
+        self.data.cell_id()
+    }
+}
+
+impl<T> Mut<T> {
+    /// The number of mutations of `self` so far (wrapping on
+    /// overflow). It changes with every mutating operation, even one
+    /// that turns out to be a no-op, such as setting the same value.
+    pub fn version(&self) -> u64 {
+        self.data.version()
+    }
+}
+
+impl<T> MutVec<T> {
+    /// The number of mutations of `self` so far (wrapping on
+    /// overflow). It changes with every mutating operation, even one
+    /// that turns out to be a no-op, such as `pop` on an empty vector.
+    pub fn version(&self) -> u64 {
+        self.data.version()
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S> {
+    /// The number of mutations of `self` so far (wrapping on
+    /// overflow). It changes with every mutating operation, even one
+    /// that turns out to be a no-op, such as removing a missing key.
+    pub fn version(&self) -> u64 {
+        self.data.version()
+    }
+}
+
+/// A set of registered cells.
+#[derive(Default)]
+pub struct DirtySet {
+    cells: MutVec<CellId>,
+}
+
+impl DirtySet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `cell` to the set, returning its id. Registering a cell
+    /// twice has no effect.
+    pub fn register(&self, cell: &impl Tracked) -> CellId {
+        let id = cell.cell_id();
+        if !self.cells.iter().any(|c| c == id) {
+            self.cells.push(id);
+        }
+        id
+    }
+
+    /// The revision at which some registered cell last changed: this
+    /// advances on any mutation of a registered cell, and only then.
+    pub fn revision(&self) -> Revision {
+        self.cells
+            .iter()
+            .map(|CellId(id)| Revision(track::changed_at(id)))
+            .max()
+            .unwrap_or(Revision(0))
+    }
+
+    /// The registered cells that were mutated after `revision`.
+    pub fn changed_since(&self, revision: Revision) -> Vec<CellId> {
+        self.cells
+            .iter()
+            .filter(|&CellId(id)| Revision(track::changed_at(id)) > revision)
+            .collect()
+    }
+}
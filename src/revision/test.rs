@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn changed_since() {
+    let a = Mut::new(1);
+    let b = MutVec::<u32>::new();
+    let c = MutMap::<u32, u32>::new();
+    let unregistered = Mut::new(0);
+
+    let dirty = DirtySet::new();
+    let a_id = dirty.register(&a);
+    let b_id = dirty.register(&b);
+    let c_id = dirty.register(&c);
+    assert_eq!(dirty.register(&a), a_id);
+
+    let start = Revision::current();
+    assert!(dirty.changed_since(start).is_empty());
+
+    a.set(2);
+    c.insert(1, 1);
+    assert_eq!(dirty.changed_since(start), vec![a_id, c_id]);
+
+    let epoch = dirty.revision();
+    unregistered.set(1);
+    assert_eq!(dirty.revision(), epoch);
+    b.push(1);
+    assert!(dirty.revision() > epoch);
+    assert_eq!(dirty.changed_since(epoch), vec![b_id]);
+}
+
+#[test]
+fn version() {
+    let m = Mut::new(1);
+    let v = MutVec::new();
+    let mut map = MutMap::new();
+    assert_eq!((m.version(), v.version(), map.version()), (0, 0, 0));
+
+    m.set(1);
+    m.replace(2);
+    v.push(1);
+    v.with_element_mut(0, |x| *x += 1);
+    map.insert("a", 1);
+    map.remove("b");
+    assert_eq!((m.version(), v.version(), map.version()), (2, 2, 2));
+
+    // Reads do not change the version.
+    m.get();
+    v.iter().count();
+    map.get("a");
+    assert_eq!((m.version(), v.version(), map.version()), (2, 2, 2));
+
+    // The version does not depend on change tracking (nor an id).
+    let untracked = Mut::new(0);
+    untracked.set(1);
+    assert_eq!(untracked.version(), 1);
+}
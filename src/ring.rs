@@ -0,0 +1,124 @@
+use crate::mcell::MCell;
+use std::collections::VecDeque;
+
+mod test;
+
+/// A buffer holding at most `capacity` elements; pushing onto a full
+/// buffer evicts the oldest element.
+pub struct MutRingBuffer<T> {
+    data: MCell<VecDeque<T>>,
+    capacity: usize,
+}
+
+impl<T> MutRingBuffer<T> {
+    /// Create a buffer holding up to `capacity` elements. Panics if
+    /// `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "ring buffer capacity must be non-zero");
+        MutRingBuffer {
+            data: MCell::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    /// Push `value` as the newest element, returning the oldest
+    /// element if it had to be evicted to make room.
+    pub fn push(&self, value: T) -> Option<T> {
+        let mut data = self.data.borrow_mut();
+        let evicted = if data.len() == self.capacity {
+            data.pop_front()
+        } else {
+            None
+        };
+        data.push_back(value);
+        evicted
+    }
+
+    /// Get the element at `index`, where index 0 is the oldest
+    /// element.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        Some(data.get(index)?.clone())
+    }
+
+    /// The most recently pushed element, if any.
+    pub fn newest(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        Some(data.back()?.clone())
+    }
+
+    /// Remove all elements.
+    pub fn clear(&self) -> Vec<T> {
+        self.data.take().into()
+    }
+
+    /// Iterate over the elements from oldest to newest, cloning them as
+    /// we go.
+    ///
+    /// As with `MutVec::iter`, mutating `self` during iteration may
+    /// lead to surprising results (here, pushing shifts every element
+    /// down by one once the buffer is full) but is not undefined
+    /// behavior.
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        self.iter_recent(self.capacity)
+    }
+
+    /// Iterate over the `n` most recently pushed elements, from oldest
+    /// to newest.
+    pub fn iter_recent(&self, n: usize) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        let len = self.len();
+        Iter {
+            buffer: self,
+            index: len - n.min(len),
+        }
+    }
+}
+
+pub struct Iter<'iter, T>
+where
+    T: Clone,
+{
+    buffer: &'iter MutRingBuffer<T>,
+    index: usize,
+}
+
+impl<'iter, T> Iterator for Iter<'iter, T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.buffer.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
@@ -0,0 +1,26 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn push_evicts_oldest() {
+    let r = MutRingBuffer::new(3);
+    assert_eq!(r.push(1), None);
+    assert_eq!(r.push(2), None);
+    assert_eq!(r.push(3), None);
+    assert!(r.is_full());
+    assert_eq!(r.push(4), Some(1));
+    assert_eq!(r.iter().collect::<Vec<_>>(), vec![2, 3, 4]);
+    assert_eq!(r.newest(), Some(4));
+}
+
+#[test]
+fn iter_recent() {
+    let r = MutRingBuffer::new(4);
+    for i in 0..10 {
+        r.push(i);
+    }
+    assert_eq!(r.iter_recent(2).collect::<Vec<_>>(), vec![8, 9]);
+    assert_eq!(r.iter_recent(100).collect::<Vec<_>>(), vec![6, 7, 8, 9]);
+    assert_eq!(r.iter_recent(0).count(), 0);
+}
@@ -0,0 +1,122 @@
+use crate::mcell::{lock, MCell};
+use indexmap::Equivalent;
+use indexmap::IndexMap;
+use std::hash::Hash;
+
+mod test;
+
+/// A map made up of nested scopes, as in a symbol table: insertions go
+/// into the innermost scope, and lookups search from the innermost
+/// scope outward. The map starts out with a single outermost scope
+/// that can never be popped.
+pub struct MutScopedMap<K, V> {
+    scopes: MCell<Vec<IndexMap<K, V>>>,
+}
+
+impl<K, V> MutScopedMap<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of scopes, including the outermost one.
+    pub fn depth(&self) -> usize {
+        self.scopes.borrow().len()
+    }
+
+    /// Push a new innermost scope. The scope is popped (and its
+    /// entries dropped) when the returned guard is dropped.
+    #[track_caller]
+    pub fn push_scope(&self) -> ScopeGuard<'_, K, V> {
+        self.scopes.check_out().push(IndexMap::new());
+        ScopeGuard {
+            map: self,
+            depth: self.depth(),
+        }
+    }
+
+    /// Inserts `(key, value)` into the innermost scope, returning the
+    /// value it shadows *in that scope*, if any. During the insertion
+    /// all mut-cells are read-only and this map will appear empty.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut scopes = self.scopes.check_out();
+        scopes.last_mut().unwrap().insert(key, value)
+    }
+
+    /// Look up `key`, starting in the innermost scope.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        V: Clone,
+    {
+        let scopes = self.scopes.borrow();
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(key).cloned())
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let scopes = self.scopes.borrow();
+        scopes.iter().any(|scope| scope.contains_key(key))
+    }
+
+    /// True if `key` is bound in the innermost scope (as opposed to
+    /// some enclosing one).
+    pub fn is_local<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let scopes = self.scopes.borrow();
+        scopes.last().unwrap().contains_key(key)
+    }
+}
+
+impl<K, V> Default for MutScopedMap<K, V> {
+    fn default() -> Self {
+        MutScopedMap {
+            scopes: MCell::new(vec![IndexMap::new()]),
+        }
+    }
+}
+
+pub struct ScopeGuard<'map, K, V>
+where
+    K: Eq + Hash,
+{
+    map: &'map MutScopedMap<K, V>,
+    depth: usize,
+}
+
+impl<'map, K, V> Drop for ScopeGuard<'map, K, V>
+where
+    K: Eq + Hash,
+{
+    fn drop(&mut self) {
+        let scopes = &self.map.scopes;
+        if std::thread::panicking() {
+            // Panicking again would abort, so the scope is only
+            // popped if that can be done cleanly; otherwise it is left
+            // in place.
+            let clean = lock::check_write(Some(scopes.domain())).is_ok()
+                && !scopes.is_poisoned()
+                && scopes.borrow().len() == self.depth;
+            if !clean {
+                return;
+            }
+        }
+        let popped = {
+            let mut scopes = scopes.check_out();
+            debug_assert_eq!(scopes.len(), self.depth, "scopes popped out of order");
+            scopes.pop()
+        };
+
+        // Drop the entries only once the map is restored.
+        drop(popped);
+    }
+}
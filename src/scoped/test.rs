@@ -0,0 +1,50 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn shadowing() {
+    let m = MutScopedMap::new();
+    m.insert("x", 1);
+    m.insert("y", 2);
+    {
+        let _scope = m.push_scope();
+        m.insert("x", 10);
+        assert_eq!(m.get("x"), Some(10));
+        assert_eq!(m.get("y"), Some(2));
+        assert!(m.is_local("x"));
+        assert!(!m.is_local("y"));
+        assert_eq!(m.depth(), 2);
+    }
+    assert_eq!(m.get("x"), Some(1));
+    assert_eq!(m.depth(), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "out of order")]
+fn out_of_order() {
+    let m: MutScopedMap<u32, u32> = MutScopedMap::new();
+    let a = m.push_scope();
+    let _b = m.push_scope();
+    drop(a);
+}
+
+#[test]
+fn unwinding_while_locked() {
+    use crate::MutVec;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let m: MutScopedMap<u32, u32> = MutScopedMap::new();
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // Dropped after the scope guard, so the scope cannot be
+        // popped: it is left in place rather than aborting.
+        let _borrow;
+        let _scope = m.push_scope();
+        _borrow = v.get_ref(0);
+        panic!("oops");
+    }));
+    assert!(result.is_err());
+    assert_eq!(m.depth(), 2);
+}
@@ -0,0 +1,60 @@
+//! Moving cells between threads.
+//!
+//! `Mut<T>`, `MutVec<T>`, and `MutMap<K, V, S>` are `Send` whenever
+//! their contents are, and never `Sync`. Sending is sound because the
+//! lock that guards a cell belongs to the thread, not to the cell:
+//! the guards that hold a thread's lock borrow the cell and are not
+//! `Send` themselves, so a cell can only change threads while no
+//! access to it is in progress. Cell ids are unique across threads,
+//! so a sent cell cannot be mistaken for another one on its new
+//! thread.
+//!
+//! What does *not* move with a cell is the state that the sending
+//! thread keeps about it: `subscribe` callbacks, dirty tracking (see
+//! `revision`), and, with the `debug` feature, its registry entry.
+//! That state stays behind, inert, until the cell is dropped. Wrap the
+//! cell in a `Handoff` to discard it up front instead:
+//!
+//! ```
+//! use mutable::send::Handoff;
+//! use mutable::MutVec;
+//!
+//! let v = MutVec::from(vec![1, 2]);
+//! let handoff = Handoff::new(v);
+//! let v = std::thread::spawn(move || {
+//!     let v = handoff.into_inner();
+//!     v.push(3);
+//!     v
+//! })
+//! .join()
+//! .unwrap();
+//! assert_eq!(v.into_inner(), vec![1, 2, 3]);
+//! ```
+
+use crate::multi::Lockable;
+
+mod test;
+
+/// A cell in transit to another thread, with the state that the
+/// sending thread kept about it discarded. Only the outermost cell is
+/// affected: cells nested within its contents keep their
+/// subscriptions on the sending thread.
+pub struct Handoff<C: Lockable> {
+    value: C,
+}
+
+impl<C: Lockable> Handoff<C> {
+    /// Prepares `value` to be sent. Any callbacks subscribed to it are
+    /// dropped.
+    pub fn new(value: C) -> Self {
+        value.mcell().0.detach();
+        Handoff { value }
+    }
+
+    /// Takes the cell out on the receiving thread.
+    #[track_caller]
+    pub fn into_inner(self) -> C {
+        self.value.mcell().0.attach();
+        self.value
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{Mut, MutMap, MutVec};
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn send_when_contents_are() {
+    assert_send::<Mut<String>>();
+    assert_send::<MutVec<Mut<u32>>>();
+    assert_send::<MutMap<String, MutVec<u8>>>();
+    assert_send::<Handoff<MutVec<u32>>>();
+}
+
+#[test]
+fn plain_send() {
+    let m = MutMap::new();
+    m.insert("a", 1);
+    let m = std::thread::spawn(move || {
+        m.insert("b", 2);
+        m
+    })
+    .join()
+    .unwrap();
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn handoff_drops_subscriptions() {
+    let count = Rc::new(Cell::new(0));
+    let v = Mut::new(1);
+    let c = count.clone();
+    v.subscribe(move || c.set(c.get() + 1));
+    v.set(2);
+    assert_eq!(count.get(), 1);
+
+    // Dropping the subscriptions releases the callback's `Rc`.
+    let v = Handoff::new(v).into_inner();
+    assert_eq!(Rc::strong_count(&count), 1);
+    v.set(3);
+    assert_eq!(count.get(), 1);
+}
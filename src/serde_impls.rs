@@ -0,0 +1,199 @@
+//! `Serialize`/`Deserialize` impls, enabled by the `serde` feature.
+//! Serialization writes out a snapshot of the current contents,
+//! taken under a read borrow; deserialization produces a fresh cell.
+
+use crate::deque::MutDeque;
+use crate::map::{MapStore, MutMap};
+use crate::mcell::MCell;
+use crate::mutbl::Mut;
+use crate::set::MutSet;
+use crate::string::MutString;
+use crate::value::MutValue;
+use crate::vec::MutVec;
+use indexmap::{IndexMap, IndexSet};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::Hash;
+
+mod test;
+
+impl<T: Serialize> Serialize for MCell<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<T: Serialize> Serialize for Mut<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Mut<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Mut::new(T::deserialize(deserializer)?))
+    }
+}
+
+impl<T: Serialize> Serialize for MutVec<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MutVec<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MutVec::from(Vec::deserialize(deserializer)?))
+    }
+}
+
+impl<K, V, S> Serialize for MutMap<K, V, S>
+where
+    S: MapStore<K, V> + Serialize,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de, K, V, S> Deserialize<'de> for MutMap<K, V, S>
+where
+    S: MapStore<K, V> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MutMap::from(S::deserialize(deserializer)?))
+    }
+}
+
+impl<T> Serialize for MutSet<T>
+where
+    T: Serialize + Eq + Hash,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for MutSet<T>
+where
+    T: Deserialize<'de> + Eq + Hash,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MutSet::from(IndexSet::deserialize(deserializer)?))
+    }
+}
+
+impl<T: Serialize> Serialize for MutDeque<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MutDeque<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MutDeque::from(VecDeque::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for MutString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MutString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MutString::from(String::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for MutValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MutValue::Null => serializer.serialize_unit(),
+            MutValue::Bool(b) => serializer.serialize_bool(*b),
+            // Write integral numbers as integers, so that they survive
+            // a round trip through formats that distinguish the two.
+            MutValue::Number(n) if n.fract() == 0.0 && n.abs() < (1u64 << 53) as f64 => {
+                serializer.serialize_i64(*n as i64)
+            }
+            MutValue::Number(n) => serializer.serialize_f64(*n),
+            MutValue::String(s) => serializer.serialize_str(s),
+            MutValue::Array(v) => v.serialize(serializer),
+            MutValue::Object(m) => m.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MutValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = MutValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "any value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<MutValue, E> {
+        Ok(MutValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<MutValue, E> {
+        Ok(MutValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<MutValue, D::Error> {
+        MutValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<MutValue, E> {
+        Ok(MutValue::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<MutValue, E> {
+        Ok(MutValue::Number(n as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<MutValue, E> {
+        Ok(MutValue::Number(n as f64))
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<MutValue, E> {
+        Ok(MutValue::Number(n))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<MutValue, E> {
+        Ok(MutValue::String(s.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<MutValue, E> {
+        Ok(MutValue::String(s))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<MutValue, A::Error> {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            vec.push(element);
+        }
+        Ok(MutValue::Array(MutVec::from(vec)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<MutValue, A::Error> {
+        let mut entries = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            entries.insert(key, value);
+        }
+        Ok(MutValue::Object(MutMap::from(entries)))
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn round_trip() {
+    let m = MutMap::new();
+    m.insert("b".to_string(), MutVec::from(vec![1, 2]));
+    m.insert("a".to_string(), MutVec::new());
+
+    let json = serde_json::to_string(&m).unwrap();
+    assert_eq!(json, r#"{"b":[1,2],"a":[]}"#);
+
+    let m2: MutMap<String, MutVec<u32>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(m2.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+    assert_eq!(m2.get("b").unwrap().at(1), 2);
+}
+
+#[test]
+fn nested_mut() {
+    let v = MutVec::from(vec![Mut::new(1), Mut::new(2)]);
+    let json = serde_json::to_string(&v).unwrap();
+    assert_eq!(json, "[1,2]");
+
+    let m: Mut<Vec<u32>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(m.get(), vec![1, 2]);
+}
+
+#[test]
+fn mut_value() {
+    let json = r#"{"a":{"b":[1,2.5,null]},"ok":true,"name":"x"}"#;
+    let v: MutValue = serde_json::from_str(json).unwrap();
+    assert_eq!(v.get_path("a.b[1]").unwrap().as_f64(), Some(2.5));
+    v.set_path("a.b[2]", "y".into()).unwrap();
+    assert_eq!(
+        serde_json::to_string(&v).unwrap(),
+        r#"{"a":{"b":[1,2.5,"y"]},"ok":true,"name":"x"}"#
+    );
+}
+
+#[test]
+fn other_stores_and_collections() {
+    use crate::map::MutBTreeMap;
+
+    let m: MutBTreeMap<u32, String> = serde_json::from_str(r#"{"2":"b","1":"a"}"#).unwrap();
+    assert_eq!(serde_json::to_string(&m).unwrap(), r#"{"1":"a","2":"b"}"#);
+
+    let set: MutSet<u32> = serde_json::from_str("[3,1,3]").unwrap();
+    assert_eq!(serde_json::to_string(&set).unwrap(), "[3,1]");
+
+    let deque: MutDeque<u32> = serde_json::from_str("[1,2]").unwrap();
+    deque.push_front(0);
+    assert_eq!(serde_json::to_string(&deque).unwrap(), "[0,1,2]");
+
+    let s: MutString = serde_json::from_str(r#""hi""#).unwrap();
+    s.push('!');
+    assert_eq!(serde_json::to_string(&s).unwrap(), r#""hi!""#);
+}
@@ -0,0 +1,256 @@
+use crate::iter_check::MutationCheck;
+use crate::mcell::MCell;
+use crate::observe;
+use crate::observe::SubscriptionId;
+use indexmap::{Equivalent, IndexSet};
+use std::fmt;
+use std::hash::Hash;
+
+mod test;
+
+/// A set of mutable data, kept in insertion order.
+pub struct MutSet<T> {
+    pub(crate) data: MCell<IndexSet<T>>,
+}
+
+impl<T: Eq + Hash> MutSet<T> {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Adds `value` to the set, returning false if it was already
+    /// present. During the insertion operation, all mut-cells are
+    /// locked and read-only. Attempts to read from *this* set during
+    /// insertion will encounter an empty set.
+    pub fn insert(&self, value: T) -> bool {
+        self.data.check_out().insert(value)
+    }
+
+    /// Removes `value` from the set, returning false if it was not
+    /// present. The last element takes its place in the iteration
+    /// order. As with `insert`, all mut-cells are locked and read-only
+    /// during the removal, and reads of *this* set find it empty.
+    pub fn remove<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.data.check_out().swap_remove(value)
+    }
+
+    /// Removes `value` from the set, shifting the later elements
+    /// down to preserve the iteration order (which takes time
+    /// proportional to their number). Returns false if it was not
+    /// present. Locks as `remove` does.
+    pub fn shift_remove<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.data.check_out().shift_remove(value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.data.borrow().contains(value)
+    }
+
+    /// The element at position `index` in the iteration order, if
+    /// any.
+    pub fn get_index(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().get_index(index).cloned()
+    }
+
+    /// The position of `value` in the iteration order, if present.
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.data.borrow().get_index_of(value)
+    }
+
+    /// Keeps only the elements for which `keep` returns true. While
+    /// `keep` runs, all mut-cells are locked and read-only; attempts
+    /// to read from *this* set will find it empty.
+    #[track_caller]
+    pub fn retain(&self, keep: impl FnMut(&T) -> bool) {
+        self.data.check_out().retain(keep);
+    }
+
+    /// Take ownership of our internal set, replacing it with an empty
+    /// one.
+    pub fn take(&self) -> IndexSet<T> {
+        self.data.take()
+    }
+
+    /// Iterate over the elements in `self`, in insertion order,
+    /// cloning them as we go.
+    ///
+    /// As with `MutVec::iter`, `self` may be mutated during the
+    /// iteration, which may lead to surprising results but is not
+    /// undefined behavior. In debug builds, such a mutation logs a
+    /// warning.
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            set: self,
+            index: 0,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+
+    /// The elements of `self` followed by those of `other` that are
+    /// not in `self`. Both sets are read under one borrow.
+    pub fn union(&self, other: &MutSet<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let (a, b) = (self.data.borrow(), other.data.borrow());
+        a.union(&b).cloned().collect()
+    }
+
+    /// The elements of `self` that are also in `other`.
+    pub fn intersection(&self, other: &MutSet<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let (a, b) = (self.data.borrow(), other.data.borrow());
+        a.intersection(&b).cloned().collect()
+    }
+
+    /// The elements of `self` that are not in `other`.
+    pub fn difference(&self, other: &MutSet<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let (a, b) = (self.data.borrow(), other.data.borrow());
+        a.difference(&b).cloned().collect()
+    }
+
+    /// True if every element of `self` is in `other`.
+    pub fn is_subset(&self, other: &MutSet<T>) -> bool {
+        let (a, b) = (self.data.borrow(), other.data.borrow());
+        a.is_subset(&b)
+    }
+
+    /// Adds the elements of `other` to `self`. While the set is
+    /// modified, all mut-cells are locked and read-only.
+    pub fn union_with(&self, other: &MutSet<T>)
+    where
+        T: Clone,
+    {
+        if self.ptr_eq(other) {
+            return;
+        }
+        let mut data = self.data.check_out();
+        data.extend(other.data.borrow().iter().cloned());
+    }
+
+    /// Removes the elements of `self` that are not in `other`. While
+    /// the set is modified, all mut-cells are locked and read-only.
+    pub fn intersect_with(&self, other: &MutSet<T>) {
+        if self.ptr_eq(other) {
+            return;
+        }
+        let mut data = self.data.check_out();
+        let other = other.data.borrow();
+        data.retain(|value| other.contains(value));
+    }
+
+    // `union_with` and `intersect_with` check out `self`, so reading
+    // `other` would find it empty if it were the same set.
+    fn ptr_eq(&self, other: &MutSet<T>) -> bool {
+        std::ptr::eq(self, other)
+    }
+
+    /// Take ownership of our internal set, consuming `self`.
+    pub fn into_inner(self) -> IndexSet<T> {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+}
+
+impl<T: Clone> Clone for MutSet<T> {
+    fn clone(&self) -> Self {
+        MutSet::from(self.data.borrow().clone())
+    }
+}
+
+/// `MutSet` is kept in insertion order and supports access by
+/// position (`get_index`, `get_index_of`); this name says so.
+pub type MutIndexSet<T> = MutSet<T>;
+
+impl<T> Default for MutSet<T> {
+    #[track_caller]
+    fn default() -> Self {
+        MutSet::from(IndexSet::new())
+    }
+}
+
+impl<T: Eq + Hash> std::iter::FromIterator<T> for MutSet<T> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        MutSet::from(iter.into_iter().collect::<IndexSet<T>>())
+    }
+}
+
+impl<T> From<IndexSet<T>> for MutSet<T> {
+    #[track_caller]
+    fn from(set: IndexSet<T>) -> Self {
+        MutSet {
+            data: MCell::new(set),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+pub struct Iter<'iter, T> {
+    set: &'iter MutSet<T>,
+    index: usize,
+    check: MutationCheck,
+}
+
+impl<'iter, T> Iterator for Iter<'iter, T>
+where
+    T: Eq + Hash + Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.check.check();
+        let value = self.set.get_index(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
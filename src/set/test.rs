@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn basics() {
+    let set = MutSet::new();
+    assert!(set.insert("a"));
+    assert!(!set.insert("a"));
+    assert!(set.contains("a"));
+    assert!(set.remove("a"));
+    assert!(set.is_empty());
+}
+
+#[test]
+fn algebra() {
+    let a: MutSet<u32> = vec![1, 2, 3].into_iter().collect();
+    let b: MutSet<u32> = vec![3, 4].into_iter().collect();
+    assert_eq!(a.union(&b), vec![1, 2, 3, 4]);
+    assert_eq!(a.intersection(&b), vec![3]);
+    assert_eq!(a.difference(&b), vec![1, 2]);
+    assert!(!b.is_subset(&a));
+
+    a.union_with(&b);
+    assert!(b.is_subset(&a));
+    assert_eq!(a.len(), 4);
+
+    let c: MutSet<u32> = vec![2, 4, 9].into_iter().collect();
+    a.intersect_with(&c);
+    assert_eq!(a.into_inner().into_iter().collect::<Vec<_>>(), vec![2, 4]);
+}
+
+#[test]
+fn with_self() {
+    let a: MutSet<u32> = vec![1, 2].into_iter().collect();
+    a.union_with(&a);
+    a.intersect_with(&a);
+    assert_eq!(a.union(&a), vec![1, 2]);
+    assert!(a.is_subset(&a));
+}
+
+#[test]
+fn iteration_and_updates() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let set: MutSet<u32> = (1..=5).collect();
+    let changes = Rc::new(Cell::new(0));
+    let c = changes.clone();
+    set.subscribe(move || c.set(c.get() + 1));
+
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    set.retain(|x| x % 2 == 1);
+    assert_eq!(set.get_index(1), Some(3));
+
+    // Removal moves the last element into the vacated position.
+    set.remove(&1);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![5, 3]);
+
+    let copy = set.clone();
+    assert_eq!(set.take().len(), 2);
+    assert!(set.is_empty());
+    assert_eq!(copy.len(), 2);
+    assert_eq!(changes.get(), 3);
+}
+
+#[test]
+fn reads_during_retain_find_empty_set() {
+    let set: MutSet<u32> = (1..=3).collect();
+    set.retain(|x| {
+        assert!(set.is_empty());
+        *x != 2
+    });
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 3]);
+}
+
+#[test]
+fn index_access() {
+    let set: MutIndexSet<&str> = vec!["a", "b", "c", "d"].into_iter().collect();
+    assert_eq!(set.get_index_of("c"), Some(2));
+    assert!(set.shift_remove("a"));
+    assert!(!set.shift_remove("a"));
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec!["b", "c", "d"]);
+    assert_eq!(set.get_index(0), Some("b"));
+    assert_eq!(set.get_index_of("a"), None);
+}
@@ -0,0 +1,151 @@
+use crate::map::MutMap;
+use indexmap::Equivalent;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+mod test;
+
+const DEFAULT_SHARDS: usize = 16;
+
+/// A map whose entries are split by hash across several internal
+/// `MutMap`s ("shards"). Inserting into a `MutMap` checks the whole
+/// map out, so that readers of the map find it empty for the
+/// duration; here, only one shard is checked out at a time, and
+/// readers of the others are unaffected.
+pub struct MutShardedMap<K, V> {
+    shards: Vec<MutMap<K, V>>,
+    hasher: RandomState,
+}
+
+impl<K, V> MutShardedMap<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a map with `shards` internal shards. Panics if `shards`
+    /// is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "a sharded map needs at least one shard");
+        MutShardedMap {
+            shards: (0..shards).map(|_| MutMap::new()).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|s| s.is_empty())
+    }
+
+    /// The number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard<Q>(&self, key: &Q) -> &MutMap<K, V>
+    where
+        Q: ?Sized + Hash,
+    {
+        let hash = self.hasher.hash_one(key);
+        &self.shards[(hash % self.shards.len() as u64) as usize]
+    }
+
+    /// Inserts `(key, value)` into the map. During the insertion
+    /// operation, all mut-cells are locked and read-only. Attempts to
+    /// read from the shard holding `key` during insertion will find
+    /// it empty; other shards read as normal.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).insert(key, value)
+    }
+
+    /// Removes `key` from the map. As with `insert`, only the shard
+    /// holding `key` is checked out during the operation.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shard(key).remove_shared(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        V: Clone,
+    {
+        self.shard(key).get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shard(key).with_borrowed(|map| map.contains_key(key))
+    }
+
+    /// Iterate over the entries in `self`, shard by shard, cloning
+    /// them as we go. As with `MutMap::iter`, it is possible to
+    /// mutate `self` during the iteration.
+    pub fn iter(&self) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            map: self,
+            shard: 0,
+            index: 0,
+        }
+    }
+}
+
+impl<K, V> Default for MutShardedMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for MutShardedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for shard in &self.shards {
+            map.entries(shard.data.borrow().iter());
+        }
+        map.finish()
+    }
+}
+
+pub struct Iter<'iter, K, V> {
+    map: &'iter MutShardedMap<K, V>,
+    shard: usize,
+    index: usize,
+}
+
+impl<'iter, K, V> Iterator for Iter<'iter, K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        loop {
+            let shard = self.map.shards.get(self.shard)?;
+            if let Some(entry) = shard.get_index(self.index) {
+                self.index += 1;
+                return Some(entry);
+            }
+            self.shard += 1;
+            self.index = 0;
+        }
+    }
+}
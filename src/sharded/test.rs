@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn basic() {
+    let m = MutShardedMap::with_shards(4);
+    for i in 0..100 {
+        assert_eq!(m.insert(i, i * 2), None);
+    }
+    assert_eq!(m.len(), 100);
+    assert_eq!(m.get(&7), Some(14));
+    assert_eq!(m.remove(&7), Some(14));
+    assert!(!m.contains_key(&7));
+    assert_eq!(m.shard_count(), 4);
+
+    let mut entries: Vec<_> = m.iter().collect();
+    entries.sort();
+    assert_eq!(entries.len(), 99);
+    assert_eq!(entries[7], (8, 16));
+}
+
+#[test]
+fn other_shards_readable_during_insert() {
+    let m = MutShardedMap::with_shards(8);
+    for i in 0..64 {
+        m.insert(i, i);
+    }
+
+    // While the shard holding `a` is checked out (as it is during an
+    // insertion of `a`), a key in another shard can still be read.
+    let a = 0;
+    let b = (1..64)
+        .find(|b| !std::ptr::eq(m.shard(&a), m.shard(b)))
+        .unwrap();
+    m.shard(&a).with_checked_out(|_| {
+        assert_eq!(m.get(&b), Some(b));
+        assert_eq!(m.get(&a), None);
+    });
+    assert_eq!(m.get(&a), Some(a));
+}
@@ -0,0 +1,168 @@
+//! A slab: storage for values addressed by small integer keys.
+
+use crate::iter_check::MutationCheck;
+use crate::mcell::MCell;
+use std::fmt;
+
+mod test;
+
+/// Values stored under `usize` keys that `insert` hands out. The key
+/// of a removed value is reused by a later insertion, so the keys
+/// stay dense and lookups are a plain index, with no hashing.
+pub struct MutSlab<T> {
+    data: MCell<Slab<T>>,
+}
+
+struct Slab<T> {
+    /// Indexed by key; `None` for free slots.
+    slots: Vec<Option<T>>,
+
+    /// The free slots, most recently freed last.
+    free: Vec<usize>,
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Slab {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> Slab<T> {
+    fn get(&self, key: usize) -> Option<&T> {
+        self.slots.get(key)?.as_ref()
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+}
+
+impl<T> MutSlab<T> {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Store `value`, returning its key: the most recently freed
+    /// one, if any.
+    pub fn insert(&self, value: T) -> usize {
+        let mut data = self.data.borrow_mut();
+        match data.free.pop() {
+            Some(key) => {
+                data.slots[key] = Some(value);
+                key
+            }
+            None => {
+                data.slots.push(Some(value));
+                data.slots.len() - 1
+            }
+        }
+    }
+
+    /// Remove the value stored under `key`, if any, freeing the key
+    /// for reuse.
+    pub fn remove(&self, key: usize) -> Option<T> {
+        let mut data = self.data.borrow_mut();
+        let value = data.slots.get_mut(key)?.take()?;
+        data.free.push(key);
+        Some(value)
+    }
+
+    /// Replace the value stored under `key`, returning the old one,
+    /// or `None` (leaving the slab unchanged) if there is no such
+    /// value.
+    pub fn replace(&self, key: usize, value: T) -> Option<T> {
+        let mut data = self.data.borrow_mut();
+        let slot = data.slots.get_mut(key)?.as_mut()?;
+        Some(std::mem::replace(slot, value))
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.data.borrow().get(key).is_some()
+    }
+
+    pub fn get(&self, key: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().get(key).cloned()
+    }
+
+    /// Runs `f` with a reference to the value stored under `key`, if
+    /// any. All mut-cells are read-only while `f` runs.
+    #[track_caller]
+    pub fn with<R>(&self, key: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        Some(f(self.data.borrow().get(key)?))
+    }
+
+    /// Iterate over the keys and values in key order, cloning the
+    /// values as we go. The slab is borrowed only for each step, so
+    /// it may be modified during the iteration; in debug builds,
+    /// doing so logs a warning.
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            slab: self,
+            key: 0,
+            check: MutationCheck::new(&self.data),
+        }
+    }
+}
+
+impl<T> Default for MutSlab<T> {
+    #[track_caller]
+    fn default() -> Self {
+        MutSlab {
+            data: MCell::new(Slab::default()),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutSlab<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        let entries = data
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(key, slot)| Some((key, slot.as_ref()?)));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+pub struct Iter<'iter, T> {
+    slab: &'iter MutSlab<T>,
+    key: usize,
+    check: MutationCheck,
+}
+
+impl<'iter, T: Clone> Iterator for Iter<'iter, T> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<(usize, T)> {
+        self.check.check();
+        let data = self.slab.data.borrow();
+        while self.key < data.slots.len() {
+            let key = self.key;
+            self.key += 1;
+            if let Some(value) = &data.slots[key] {
+                return Some((key, value.clone()));
+            }
+        }
+        None
+    }
+}
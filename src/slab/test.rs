@@ -0,0 +1,38 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn keys_are_reused() {
+    let slab = MutSlab::new();
+    let a = slab.insert("a");
+    let b = slab.insert("b");
+    let c = slab.insert("c");
+    assert_eq!((a, b, c), (0, 1, 2));
+
+    assert_eq!(slab.remove(b), Some("b"));
+    assert_eq!(slab.remove(b), None);
+    assert!(!slab.contains(b));
+    assert_eq!(slab.len(), 2);
+
+    assert_eq!(slab.insert("d"), b);
+    assert_eq!(slab.get(b), Some("d"));
+    assert_eq!(slab.insert("e"), 3);
+}
+
+#[test]
+fn iter_and_update() {
+    let slab = MutSlab::new();
+    for name in ["x", "y", "z"] {
+        slab.insert(name.to_string());
+    }
+    slab.remove(0);
+    assert_eq!(slab.replace(1, "Y".to_string()), Some("y".to_string()));
+    assert_eq!(slab.replace(0, "X".to_string()), None);
+    assert_eq!(slab.with(2, |s| s.len()), Some(1));
+    assert_eq!(
+        slab.iter().collect::<Vec<_>>(),
+        vec![(1, "Y".to_string()), (2, "z".to_string())]
+    );
+    assert_eq!(format!("{:?}", slab), r#"{1: "Y", 2: "z"}"#);
+}
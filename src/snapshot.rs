@@ -0,0 +1,173 @@
+//! Binary snapshots of `MutVec` and `MutMap`, enabled by the `bincode`
+//! feature. A snapshot is the bincode encoding of the contents behind
+//! a small header: the magic bytes `MUTB`, the snapshot format
+//! version, and a version number chosen by the application, so that a
+//! program can recognize (and migrate) checkpoints written by older
+//! versions of itself.
+
+use crate::map::MutMap;
+use crate::vec::MutVec;
+use bincode::Options;
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::hash::Hash;
+
+mod test;
+
+const MAGIC: &[u8; 4] = b"MUTB";
+
+/// The version of the snapshot format written by this crate.
+pub const FORMAT_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 4 + 2 + 4;
+
+/// The header of a snapshot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The snapshot format version (see `FORMAT_VERSION`).
+    pub format: u16,
+
+    /// The version passed to `to_bytes` by the application.
+    pub version: u32,
+}
+
+/// The ways in which reading a snapshot can fail.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The data does not start with a snapshot header.
+    NotASnapshot,
+
+    /// The snapshot was written with a newer format than this crate
+    /// understands.
+    UnsupportedFormat(u16),
+
+    /// The snapshot was written by a version of the application that
+    /// the caller does not accept.
+    UnsupportedVersion(u32),
+
+    /// The contents could not be encoded or decoded.
+    Encoding(bincode::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedFormat(v) => write!(f, "unsupported snapshot format {}", v),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {}", v),
+            SnapshotError::Encoding(e) => write!(f, "bad snapshot contents: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Encoding(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(e: bincode::Error) -> Self {
+        SnapshotError::Encoding(e)
+    }
+}
+
+/// Reads the header of `bytes`, without decoding the contents.
+pub fn read_header(bytes: &[u8]) -> Result<Header, SnapshotError> {
+    if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+        return Err(SnapshotError::NotASnapshot);
+    }
+    let format = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let version = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]);
+    Ok(Header { format, version })
+}
+
+fn encode(contents: &impl Serialize, version: u32) -> Result<Vec<u8>, SnapshotError> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&version.to_le_bytes());
+    options().serialize_into(&mut bytes, contents)?;
+    Ok(bytes)
+}
+
+/// The bincode options of the contents: the fixed-width integers of
+/// `bincode::serialize`, which format 1 was written with. As a
+/// snapshot may come from anywhere, `decode` also limits reading to
+/// the bytes actually present (which bounds what a corrupted length
+/// prefix can make it allocate) and rejects trailing bytes.
+fn options() -> impl Options {
+    bincode::options().with_fixint_encoding()
+}
+
+fn decode<T: DeserializeOwned>(
+    bytes: &[u8],
+    accept: impl FnOnce(u32) -> bool,
+) -> Result<T, SnapshotError> {
+    let header = read_header(bytes)?;
+    if header.format > FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedFormat(header.format));
+    }
+    if !accept(header.version) {
+        return Err(SnapshotError::UnsupportedVersion(header.version));
+    }
+    let contents = &bytes[HEADER_LEN..];
+    Ok(options()
+        .with_limit(contents.len() as u64)
+        .reject_trailing_bytes()
+        .deserialize(contents)?)
+}
+
+impl<T: Serialize> MutVec<T> {
+    /// Writes a snapshot of the current contents, tagged with the
+    /// application's `version`.
+    pub fn to_bytes(&self, version: u32) -> Result<Vec<u8>, SnapshotError> {
+        encode(&self.data, version)
+    }
+}
+
+impl<T: DeserializeOwned> MutVec<T> {
+    /// Reads a snapshot written by `to_bytes`, if `accept` returns
+    /// true for the version it was tagged with. (Use `read_header` to
+    /// inspect the version first, if an older snapshot must be
+    /// decoded as a different type.)
+    #[track_caller]
+    pub fn from_bytes(
+        bytes: &[u8],
+        accept: impl FnOnce(u32) -> bool,
+    ) -> Result<Self, SnapshotError> {
+        Ok(MutVec::from(decode::<Vec<T>>(bytes, accept)?))
+    }
+}
+
+impl<K, V> MutMap<K, V>
+where
+    K: Serialize + Eq + Hash,
+    V: Serialize,
+{
+    /// Writes a snapshot of the current contents, tagged with the
+    /// application's `version`.
+    pub fn to_bytes(&self, version: u32) -> Result<Vec<u8>, SnapshotError> {
+        encode(&self.data, version)
+    }
+}
+
+impl<K, V> MutMap<K, V>
+where
+    K: DeserializeOwned + Eq + Hash,
+    V: DeserializeOwned,
+{
+    /// Reads a snapshot written by `to_bytes`; see `MutVec::from_bytes`.
+    #[track_caller]
+    pub fn from_bytes(
+        bytes: &[u8],
+        accept: impl FnOnce(u32) -> bool,
+    ) -> Result<Self, SnapshotError> {
+        Ok(MutMap::from(decode::<IndexMap<K, V>>(bytes, accept)?))
+    }
+}
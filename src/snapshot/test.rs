@@ -0,0 +1,76 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn vec_round_trip() {
+    let v = MutVec::from(vec![1u32, 2, 3]);
+    let bytes = v.to_bytes(7).unwrap();
+    assert_eq!(
+        read_header(&bytes).unwrap(),
+        Header {
+            format: FORMAT_VERSION,
+            version: 7
+        }
+    );
+
+    let v2: MutVec<u32> = MutVec::from_bytes(&bytes, |v| v == 7).unwrap();
+    assert_eq!(v2.into_inner(), vec![1, 2, 3]);
+}
+
+#[test]
+fn map_round_trip() {
+    let m = MutMap::new();
+    m.insert("b".to_string(), 2u8);
+    m.insert("a".to_string(), 1u8);
+    let bytes = m.to_bytes(1).unwrap();
+
+    let m2: MutMap<String, u8> = MutMap::from_bytes(&bytes, |_| true).unwrap();
+    assert_eq!(m2.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+}
+
+#[test]
+fn rejected() {
+    let bytes = MutVec::from(vec![1u32]).to_bytes(2).unwrap();
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(&bytes, |v| v < 2),
+        Err(SnapshotError::UnsupportedVersion(2))
+    ));
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(b"junk", |_| true),
+        Err(SnapshotError::NotASnapshot)
+    ));
+
+    let mut future = bytes.clone();
+    future[4] = 99;
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(&future, |_| true),
+        Err(SnapshotError::UnsupportedFormat(99))
+    ));
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(truncated, |_| true),
+        Err(SnapshotError::Encoding(_))
+    ));
+}
+
+#[test]
+fn corrupted() {
+    let bytes = MutVec::from(vec![1u32, 2]).to_bytes(0).unwrap();
+
+    // A length prefix claiming far more elements than are present.
+    let mut huge = bytes.clone();
+    huge[HEADER_LEN..HEADER_LEN + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(&huge, |_| true),
+        Err(SnapshotError::Encoding(_))
+    ));
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(matches!(
+        MutVec::<u32>::from_bytes(&trailing, |_| true),
+        Err(SnapshotError::Encoding(_))
+    ));
+}
@@ -0,0 +1,174 @@
+use crate::mcell::MCell;
+use std::ops::Bound;
+use std::ops::RangeBounds;
+
+mod test;
+
+/// A vector kept in sorted order with no duplicates, usable as a
+/// cheap ordered set.
+///
+/// Comparisons are always performed under a read lock (never while
+/// the vector is being modified), so `Ord` impls may read mut-cells.
+pub struct MutSortedVec<T: Ord> {
+    data: MCell<Vec<T>>,
+}
+
+impl<T: Ord> MutSortedVec<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Insert `value` at its sorted position. Returns false (and drops
+    /// `value`) if an equal element is already present.
+    pub fn insert(&self, value: T) -> bool {
+        let search = self.data.borrow().binary_search(&value);
+        match search {
+            Ok(_) => false,
+            Err(index) => {
+                self.data.borrow_mut().insert(index, value);
+                true
+            }
+        }
+    }
+
+    /// Remove the element equal to `value`, returning it.
+    pub fn remove(&self, value: &T) -> Option<T> {
+        let index = self.data.borrow().binary_search(value).ok()?;
+        Some(self.data.borrow_mut().remove(index))
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.data.borrow().binary_search(value).is_ok()
+    }
+
+    /// Get the element with the given `index` in sorted order.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        Some(data.get(index)?.clone())
+    }
+
+    pub fn first(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        Some(data.last()?.clone())
+    }
+
+    /// Clone out the elements that fall within `bounds`, in order.
+    pub fn range(&self, bounds: impl RangeBounds<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        let start = match bounds.start_bound() {
+            Bound::Included(s) => data.partition_point(|v| v < s),
+            Bound::Excluded(s) => data.partition_point(|v| v <= s),
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(e) => data.partition_point(|v| v <= e),
+            Bound::Excluded(e) => data.partition_point(|v| v < e),
+            Bound::Unbounded => data.len(),
+        };
+        if start >= end {
+            return vec![];
+        }
+        data[start..end].to_vec()
+    }
+
+    /// Iterate over the elements in sorted order, cloning them as we
+    /// go.
+    ///
+    /// As with `MutVec::iter`, mutating `self` during this iteration
+    /// may lead to surprising results but is not undefined behavior.
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            vec: self,
+            index: 0,
+        }
+    }
+
+    /// Take ownership of the sorted elements, leaving `self` empty.
+    pub fn take(&self) -> Vec<T> {
+        self.data.take()
+    }
+}
+
+impl<T: Ord> Default for MutSortedVec<T> {
+    fn default() -> Self {
+        MutSortedVec {
+            data: MCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for MutSortedVec<T> {
+    fn from(mut v: Vec<T>) -> Self {
+        v.sort();
+        v.dedup();
+        MutSortedVec {
+            data: MCell::new(v),
+        }
+    }
+}
+
+impl<T: Ord> std::iter::FromIterator<T> for MutSortedVec<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let v: Vec<T> = iter.into_iter().collect();
+        MutSortedVec::from(v)
+    }
+}
+
+impl<T: Ord + Clone> Clone for MutSortedVec<T> {
+    fn clone(&self) -> Self {
+        MutSortedVec {
+            data: MCell::new(self.data.borrow().clone()),
+        }
+    }
+}
+
+pub struct Iter<'iter, T>
+where
+    T: Ord + Clone,
+{
+    vec: &'iter MutSortedVec<T>,
+    index: usize,
+}
+
+impl<'iter, T> Iterator for Iter<'iter, T>
+where
+    T: Ord + Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.vec.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
@@ -0,0 +1,28 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn insert_keeps_order() {
+    let v = MutSortedVec::new();
+    assert!(v.insert(5));
+    assert!(v.insert(1));
+    assert!(v.insert(3));
+    assert!(!v.insert(3));
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 3, 5]);
+    assert!(v.contains(&3));
+    assert_eq!(v.remove(&3), Some(3));
+    assert_eq!(v.remove(&3), None);
+    assert_eq!((v.first(), v.last()), (Some(1), Some(5)));
+}
+
+#[test]
+fn range() {
+    let v: MutSortedVec<u32> = vec![9, 1, 7, 3, 5, 3].into_iter().collect();
+    assert_eq!(v.len(), 5);
+    assert_eq!(v.range(3..7), vec![3, 5]);
+    assert_eq!(v.range(3..=7), vec![3, 5, 7]);
+    assert_eq!(v.range(..4), vec![1, 3]);
+    assert_eq!(v.range(6..), vec![7, 9]);
+    assert_eq!(v.range(4..5), Vec::<u32>::new());
+}
@@ -0,0 +1,78 @@
+//! Declaring thread-local globals built from the crate's cells; see
+//! `mut_static!`.
+
+use std::rc::Rc;
+use std::thread::LocalKey;
+
+mod test;
+
+/// Declares thread-local globals holding mut-cells. Each static is
+/// initialized with the given expression or, if there is none, with
+/// `Default::default()`:
+///
+/// ```
+/// use mutable::{mut_static, MutMap, MutVec};
+///
+/// mut_static! {
+///     static REGISTRY: MutMap<String, u32>;
+///     pub static LOG: MutVec<&'static str> = MutVec::from(vec!["start"]);
+/// }
+///
+/// REGISTRY.with(|r| r.insert("a".to_string(), 1));
+/// assert_eq!(REGISTRY.with(|r| r.get("a")), Some(1));
+///
+/// let log = LOG.handle();
+/// log.push("registered");
+/// assert_eq!(log.len(), 2);
+/// ```
+///
+/// Every thread gets its own instance (the statics are built on
+/// `thread_local!`), which is accessed with the `MutStatic` methods:
+/// `with`, as for a `thread_local!`, or `handle`, which returns the
+/// instance itself.
+#[macro_export]
+macro_rules! mut_static {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty; $($rest:tt)*) => {
+        $crate::mut_static! {
+            $(#[$attr])* $vis static $name: $ty = <$ty as ::std::default::Default>::default();
+            $($rest)*
+        }
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::statics::MutStatic<$ty> = $crate::statics::MutStatic::new({
+            ::std::thread_local! {
+                static INSTANCE: ::std::rc::Rc<$ty> = ::std::rc::Rc::new($init);
+            }
+            &INSTANCE
+        });
+        $crate::mut_static! { $($rest)* }
+    };
+}
+
+/// A thread-local global declared with `mut_static!`.
+pub struct MutStatic<T: 'static> {
+    key: &'static LocalKey<Rc<T>>,
+}
+
+impl<T: 'static> MutStatic<T> {
+    #[doc(hidden)]
+    pub const fn new(key: &'static LocalKey<Rc<T>>) -> Self {
+        MutStatic { key }
+    }
+
+    /// Runs `op` with this thread's instance, initializing it first
+    /// if this is the first access on the thread. Panics if the
+    /// thread's instance has already been destroyed (i.e., during
+    /// thread shutdown), as `LocalKey::with` does.
+    pub fn with<R>(&'static self, op: impl FnOnce(&T) -> R) -> R {
+        self.key.with(|instance| op(instance))
+    }
+
+    /// A handle to this thread's instance, initializing it first if
+    /// this is the first access on the thread. Panics as `with` does.
+    pub fn handle(&'static self) -> Rc<T> {
+        self.key.with(Rc::clone)
+    }
+}
@@ -0,0 +1,28 @@
+#![cfg(test)]
+
+use crate::{mut_static, Mut, MutMap};
+
+mut_static! {
+    /// Counts calls.
+    static CALLS: Mut<u32>;
+    static NAMES: MutMap<u32, String> = (0..2).map(|i| (i, i.to_string())).collect();
+}
+
+#[test]
+fn per_thread_instances() {
+    CALLS.with(|c| c.set(c.get() + 1));
+    assert_eq!(CALLS.with(Mut::get), 1);
+    assert_eq!(NAMES.with(|n| n.get(&1)), Some("1".to_string()));
+
+    std::thread::spawn(|| assert_eq!(CALLS.with(Mut::get), 0))
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn handle() {
+    let names = NAMES.handle();
+    names.insert(2, "2".to_string());
+    assert_eq!(NAMES.with(|n| n.len()), 3);
+    assert!(std::rc::Rc::ptr_eq(&names, &NAMES.handle()));
+}
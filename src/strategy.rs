@@ -0,0 +1,75 @@
+//! proptest support, enabled by the `proptest` feature: strategies
+//! for generating the crate's collections, and `Arbitrary` impls
+//! built on them.
+
+use crate::map::MutMap;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use proptest::arbitrary::{any_with, Arbitrary};
+use proptest::collection::{self, SizeRange};
+use proptest::strategy::{BoxedStrategy, Strategy};
+use std::hash::Hash;
+
+mod test;
+
+/// Generates a `MutVec` whose elements come from `element` and whose
+/// length falls within `size`.
+pub fn mut_vec<S>(element: S, size: impl Into<SizeRange>) -> impl Strategy<Value = MutVec<S::Value>>
+where
+    S: Strategy,
+{
+    collection::vec(element, size).prop_map(MutVec::from)
+}
+
+/// Generates a `MutMap` whose keys and values come from `key` and
+/// `value`. Up to `size` entries are generated; duplicate keys are
+/// merged, so the map may come out smaller.
+pub fn mut_map<K, V>(
+    key: K,
+    value: V,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = MutMap<K::Value, V::Value>>
+where
+    K: Strategy,
+    K::Value: Eq + Hash,
+    V: Strategy,
+{
+    collection::vec((key, value), size).prop_map(|entries| entries.into_iter().collect())
+}
+
+impl<T> Arbitrary for Mut<T>
+where
+    T: Arbitrary + 'static,
+{
+    type Parameters = T::Parameters;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        any_with::<T>(args).prop_map(Mut::new).boxed()
+    }
+}
+
+impl<T> Arbitrary for MutVec<T>
+where
+    T: Arbitrary + 'static,
+{
+    type Parameters = (SizeRange, T::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        mut_vec(any_with::<T>(args), size).boxed()
+    }
+}
+
+impl<K, V> Arbitrary for MutMap<K, V>
+where
+    K: Arbitrary + Eq + Hash + 'static,
+    V: Arbitrary + 'static,
+{
+    type Parameters = (SizeRange, K::Parameters, V::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, key_args, value_args): Self::Parameters) -> Self::Strategy {
+        mut_map(any_with::<K>(key_args), any_with::<V>(value_args), size).boxed()
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg(test)]
+
+use super::*;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn vec_len_in_range(v in mut_vec(0..10_u32, 2..5)) {
+        prop_assert!((2..5).contains(&v.len()));
+        prop_assert!(v.iter().all(|x| x < 10));
+    }
+
+    #[test]
+    fn map_keys_distinct(m in mut_map(0..4_u8, any::<bool>(), 0..10)) {
+        prop_assert!(m.len() <= 4);
+    }
+
+    #[test]
+    fn arbitrary_round_trip(v in any::<MutVec<Mut<u16>>>()) {
+        let copy: Vec<u16> = v.take().into_iter().map(Mut::into_inner).collect();
+        prop_assert!(copy.len() < 100);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::mcell::MCell;
+use crate::observe;
+use crate::observe::SubscriptionId;
+use std::fmt;
+
+mod test;
+
+/// A string of mutable text, for accumulating text through a shared
+/// handle (e.g., a log that several components append to). Its
+/// contents are available with `to_string` (via `Display`) or, without
+/// copying, with `with_str`.
+pub struct MutString {
+    pub(crate) data: MCell<String>,
+}
+
+impl MutString {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The length in bytes.
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Append `s` to the end.
+    pub fn push_str(&self, s: &str) {
+        self.data.borrow_mut().push_str(s);
+    }
+
+    /// Append `c` to the end.
+    pub fn push(&self, c: char) {
+        self.data.borrow_mut().push(c);
+    }
+
+    pub fn clear(&self) {
+        self.data.borrow_mut().clear();
+    }
+
+    /// Shorten the string to `new_len` bytes; does nothing if it is
+    /// already shorter. Panics if `new_len` is not on a character
+    /// boundary.
+    pub fn truncate(&self, new_len: usize) {
+        self.data.borrow_mut().truncate(new_len);
+    }
+
+    /// Runs `f` with the contents, without copying them. All
+    /// mut-cells are read-only while `f` runs.
+    #[track_caller]
+    pub fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(&self.data.borrow())
+    }
+
+    /// Replace the contents, returning the old ones.
+    pub fn replace(&self, s: String) -> String {
+        self.data.replace(s)
+    }
+
+    /// Take ownership of the contents, consuming `self`.
+    pub fn into_inner(self) -> String {
+        self.data.into_inner()
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Remove a callback registered with `subscribe`, returning false
+    /// if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+}
+
+impl Clone for MutString {
+    fn clone(&self) -> Self {
+        MutString::from(self.data.borrow().clone())
+    }
+}
+
+impl Default for MutString {
+    #[track_caller]
+    fn default() -> Self {
+        MutString::from(String::new())
+    }
+}
+
+impl From<String> for MutString {
+    #[track_caller]
+    fn from(s: String) -> Self {
+        MutString {
+            data: MCell::new(s),
+        }
+    }
+}
+
+impl From<&str> for MutString {
+    #[track_caller]
+    fn from(s: &str) -> Self {
+        MutString::from(s.to_string())
+    }
+}
+
+/// Writing appends, so `write!` works on a `&MutString`.
+impl fmt::Write for &MutString {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for MutString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.data.borrow(), f)
+    }
+}
+
+impl fmt::Display for MutString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.data.borrow())
+    }
+}
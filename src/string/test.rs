@@ -0,0 +1,31 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn accumulate() {
+    let s = MutString::new();
+    s.push_str("hello");
+    s.push(',');
+    s.push_str(" world");
+    assert_eq!(s.len(), 12);
+    assert_eq!(s.to_string(), "hello, world");
+
+    s.truncate(5);
+    assert_eq!(s.with_str(|text| text.to_uppercase()), "HELLO");
+    s.clear();
+    assert!(s.is_empty());
+}
+
+#[test]
+fn shared_writer() {
+    use std::fmt::Write;
+    use std::rc::Rc;
+
+    let log = Rc::new(MutString::from("log:"));
+    let writer = log.clone();
+    write!(&*writer, " {}", 1).unwrap();
+    write!(&*writer, " {}", 2).unwrap();
+    assert_eq!(log.to_string(), "log: 1 2");
+    assert_eq!(format!("{:?}", log), r#""log: 1 2""#);
+}
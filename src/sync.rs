@@ -0,0 +1,465 @@
+//! Thread-safe counterparts of `Mut`, `MutVec`, and `MutMap`. They
+//! have the same methods -- getters that clone, mutation through
+//! `&self` -- but are `Sync`, each guarding its contents with a
+//! `RwLock` instead of the thread lock. Code written against the
+//! single-threaded types can move to threads by switching types:
+//!
+//! ```
+//! use mutable::sync::SyncMutVec;
+//! use std::sync::Arc;
+//!
+//! let v = Arc::new(SyncMutVec::new());
+//! let threads: Vec<_> = (0..4)
+//!     .map(|i| {
+//!         let v = v.clone();
+//!         std::thread::spawn(move || v.push(i))
+//!     })
+//!     .collect();
+//! for thread in threads {
+//!     thread.join().unwrap();
+//! }
+//! assert_eq!(v.len(), 4);
+//! ```
+//!
+//! Each cell is locked on its own, so there is no `ModifyWhileLocked`
+//! violation (nor `subscribe`, dirty tracking, or the other per-thread
+//! features). In exchange, user code that runs under a cell's lock --
+//! a `Clone`, `Hash`, or `Eq` impl, or a `with_element_mut` closure --
+//! must not access that same cell, or it deadlocks. As with
+//! `MCell`, a panic under the lock does not poison the cell: the
+//! partial modifications are left behind.
+
+use crate::error::{IndexError, KeyError};
+use crate::map::{MapLookup, MapStore};
+use indexmap::IndexMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+mod test;
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn into_inner<T>(lock: RwLock<T>) -> T {
+    lock.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The thread-safe counterpart of `Mut`.
+pub struct SyncMut<T> {
+    data: RwLock<T>,
+}
+
+impl<T> SyncMut<T> {
+    pub fn new(value: T) -> Self {
+        SyncMut {
+            data: RwLock::new(value),
+        }
+    }
+
+    pub fn replace(&self, new_value: T) -> T {
+        std::mem::replace(&mut *write(&self.data), new_value)
+    }
+
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        read(&self.data).clone()
+    }
+
+    pub fn set(&self, new_value: T) {
+        // The old value is dropped after the lock is released.
+        self.replace(new_value);
+    }
+
+    /// Take ownership of the value, consuming the cell.
+    pub fn into_inner(self) -> T {
+        into_inner(self.data)
+    }
+}
+
+impl<T: Default> Default for SyncMut<T> {
+    fn default() -> Self {
+        SyncMut::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SyncMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SyncMut").field(&*read(&self.data)).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for SyncMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        read(&self.data).fmt(f)
+    }
+}
+
+/// The thread-safe counterpart of `MutVec`.
+pub struct SyncMutVec<T> {
+    data: RwLock<Vec<T>>,
+}
+
+impl<T> SyncMutVec<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        read(&self.data).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        read(&self.data).is_empty()
+    }
+
+    /// The equivalent of `self[index]` -- load the element at the
+    /// given index, panicking if there is no such element.
+    pub fn at(&self, index: usize) -> T
+    where
+        T: Clone,
+    {
+        self.get(index).unwrap()
+    }
+
+    /// Attempt to get the element at the given `index`, returning
+    /// `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        read(&self.data).get(index).cloned()
+    }
+
+    /// Like `at`, but returns an error (rather than panicking) if
+    /// `index` is out of bounds.
+    pub fn try_at(&self, index: usize) -> Result<T, IndexError>
+    where
+        T: Clone,
+    {
+        let data = read(&self.data);
+        match data.get(index) {
+            Some(value) => Ok(value.clone()),
+            None => Err(IndexError {
+                index,
+                len: data.len(),
+            }),
+        }
+    }
+
+    /// Runs `f` on the element at `index` (under the read lock), or
+    /// returns `None` if it is out of bounds.
+    pub fn with_element<R>(&self, index: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        read(&self.data).get(index).map(f)
+    }
+
+    /// Runs `f` on the element at `index` (under the write lock), or
+    /// returns `None` if it is out of bounds.
+    pub fn with_element_mut<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        write(&self.data).get_mut(index).map(f)
+    }
+
+    /// Push a value onto the end of the vector.
+    pub fn push(&self, value: T) {
+        write(&self.data).push(value)
+    }
+
+    /// Pop a value from the end of the vector, if any.
+    pub fn pop(&self) -> Option<T> {
+        write(&self.data).pop()
+    }
+
+    /// Removes the elements whose indices fall within `range`, in one
+    /// operation, and returns them. Panics if the range is out of
+    /// bounds.
+    pub fn remove_range(&self, range: Range<usize>) -> Vec<T> {
+        write(&self.data).drain(range).collect()
+    }
+
+    /// Store `value` at `index`, returning the old value. Panics if
+    /// `index` is out of bounds.
+    pub fn replace_at(&self, index: usize, value: T) -> T {
+        std::mem::replace(&mut write(&self.data)[index], value)
+    }
+
+    /// Replace the contents with `v`, returning the old contents.
+    pub fn replace(&self, v: Vec<T>) -> Vec<T> {
+        std::mem::replace(&mut *write(&self.data), v)
+    }
+
+    /// Take the contents, leaving the vector empty.
+    pub fn take(&self) -> Vec<T> {
+        self.replace(Vec::new())
+    }
+
+    pub fn min(&self) -> Option<T>
+    where
+        T: Ord + Clone,
+    {
+        read(&self.data).iter().min().cloned()
+    }
+
+    pub fn max(&self) -> Option<T>
+    where
+        T: Ord + Clone,
+    {
+        read(&self.data).iter().max().cloned()
+    }
+
+    /// Iterate over the elements in `self`, cloning them as we go.
+    /// The lock is only held while each element is cloned, so other
+    /// threads (or the loop body) may modify `self` during the
+    /// iteration; the iterator then continues from the same index.
+    pub fn iter(&self) -> VecIter<'_, T>
+    where
+        T: Clone,
+    {
+        VecIter {
+            vec: self,
+            index: 0,
+        }
+    }
+
+    /// Take ownership of the contents, consuming the vector.
+    pub fn into_inner(self) -> Vec<T> {
+        into_inner(self.data)
+    }
+}
+
+impl<T: Clone> Clone for SyncMutVec<T> {
+    fn clone(&self) -> Self {
+        SyncMutVec::from(read(&self.data).clone())
+    }
+}
+
+impl<A> std::iter::FromIterator<A> for SyncMutVec<A> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A>,
+    {
+        let v: Vec<A> = iter.into_iter().collect();
+        SyncMutVec::from(v)
+    }
+}
+
+impl<T> Default for SyncMutVec<T> {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SyncMutVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(read(&self.data).iter()).finish()
+    }
+}
+
+impl<T> From<Vec<T>> for SyncMutVec<T> {
+    fn from(v: Vec<T>) -> SyncMutVec<T> {
+        SyncMutVec {
+            data: RwLock::new(v),
+        }
+    }
+}
+
+pub struct VecIter<'iter, T>
+where
+    T: Clone,
+{
+    vec: &'iter SyncMutVec<T>,
+    index: usize,
+}
+
+impl<'iter, T> Iterator for VecIter<'iter, T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.vec.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+}
+
+/// The thread-safe counterpart of `MutMap`. As with `MutMap`, the
+/// entries are kept in an `IndexMap` by default, and the index-based
+/// methods are only available with that store.
+pub struct SyncMutMap<K, V, S = IndexMap<K, V>> {
+    data: RwLock<S>,
+    entries: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V, S> SyncMutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    pub fn len(&self) -> usize {
+        read(&self.data).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        read(&self.data).is_empty()
+    }
+
+    /// Inserts `(key, value)` into the map, returning the old value
+    /// for `key`, if any.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        write(&self.data).insert(key, value)
+    }
+
+    /// Removes `key` from the map, returning its value, if any.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V, Key = K>,
+    {
+        write(&self.data).remove(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V>,
+        V: Clone,
+    {
+        read(&self.data).get(key).cloned()
+    }
+
+    /// Like `get`, but returns an error recording the missing key.
+    pub fn try_get<Q>(&self, key: &Q) -> Result<V, KeyError<K>>
+    where
+        Q: ?Sized + ToOwned<Owned = K>,
+        S: MapLookup<Q, V>,
+        V: Clone,
+    {
+        self.get(key).ok_or_else(|| KeyError {
+            key: key.to_owned(),
+        })
+    }
+
+    /// Take ownership of the store, consuming the map.
+    pub fn into_inner(self) -> S {
+        into_inner(self.data)
+    }
+}
+
+impl<K, V> SyncMutMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// Creates an empty map backed by an `IndexMap`. Maps with other
+    /// stores can be created with `Default` or `From`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let data = read(&self.data);
+        let (key, value) = data.get_index(index)?;
+        Some((key.clone(), value.clone()))
+    }
+
+    /// Iterate over the entries in `self`, cloning them as we go. As
+    /// with `SyncMutVec::iter`, the lock is only held while each
+    /// entry is cloned.
+    pub fn iter(&self) -> MapIter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        MapIter {
+            map: self,
+            index: 0,
+        }
+    }
+}
+
+impl<K, V, S> Clone for SyncMutMap<K, V, S>
+where
+    S: MapStore<K, V> + Clone,
+{
+    fn clone(&self) -> Self {
+        SyncMutMap::from(read(&self.data).clone())
+    }
+}
+
+impl<K, V, S> std::iter::FromIterator<(K, V)> for SyncMutMap<K, V, S>
+where
+    S: MapStore<K, V> + std::iter::FromIterator<(K, V)>,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let v: S = iter.into_iter().collect();
+        SyncMutMap::from(v)
+    }
+}
+
+impl<K, V, S> Default for SyncMutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    fn default() -> Self {
+        Self::from(S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for SyncMutMap<K, V, S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        read(&self.data).fmt(f)
+    }
+}
+
+impl<K, V, S> From<S> for SyncMutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    fn from(v: S) -> SyncMutMap<K, V, S> {
+        SyncMutMap {
+            data: RwLock::new(v),
+            entries: PhantomData,
+        }
+    }
+}
+
+pub struct MapIter<'iter, K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    map: &'iter SyncMutMap<K, V>,
+    index: usize,
+}
+
+impl<'iter, K, V> Iterator for MapIter<'iter, K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.map.get_index(self.index)?;
+        self.index += 1;
+        Some((key, value))
+    }
+}
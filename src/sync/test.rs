@@ -0,0 +1,116 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{Mut, MutVec};
+use std::sync::Arc;
+use std::thread;
+
+fn assert_sync<T: Send + Sync>() {}
+
+#[test]
+fn is_sync() {
+    assert_sync::<SyncMut<String>>();
+    assert_sync::<SyncMutVec<String>>();
+    assert_sync::<SyncMutMap<String, u32>>();
+}
+
+#[test]
+fn mut_across_threads() {
+    let m = Arc::new(SyncMut::new(0));
+    let threads: Vec<_> = (1..=4)
+        .map(|i| {
+            let m = m.clone();
+            thread::spawn(move || {
+                let old = m.replace(i);
+                assert!(old <= 4);
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!((1..=4).contains(&m.get()));
+    m.set(22);
+    assert_eq!(format!("{:?} {}", m, m), "SyncMut(22) 22");
+}
+
+#[test]
+fn vec_operations() {
+    let v = SyncMutVec::from(vec![3, 1, 2]);
+    v.push(4);
+    assert_eq!(v.len(), 4);
+    assert_eq!(v.at(0), 3);
+    assert_eq!(v.get(4), None);
+    assert_eq!(v.try_at(4), Err(IndexError { index: 4, len: 4 }));
+    assert_eq!(v.with_element(1, |x| x * 10), Some(10));
+    v.with_element_mut(1, |x| *x = 5);
+    assert_eq!(v.replace_at(2, 6), 2);
+    assert_eq!((v.min(), v.max()), (Some(3), Some(6)));
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![3, 5, 6, 4]);
+    assert_eq!(v.remove_range(0..2), vec![3, 5]);
+    assert_eq!(v.pop(), Some(4));
+    assert_eq!(format!("{:?}", v.clone()), "[6]");
+    assert_eq!(v.take(), vec![6]);
+    assert!(v.is_empty());
+}
+
+#[test]
+fn map_operations() {
+    let m: SyncMutMap<String, u32> = SyncMutMap::new();
+    assert_eq!(m.insert("a".to_string(), 1), None);
+    assert_eq!(m.insert("b".to_string(), 2), None);
+    assert_eq!(m.insert("a".to_string(), 3), Some(1));
+    assert_eq!(m.get("a"), Some(3));
+    assert_eq!(m.try_get("c").unwrap_err().key, "c");
+    assert_eq!(m.get_index(1), Some(("b".to_string(), 2)));
+    assert_eq!(
+        m.iter().collect::<Vec<_>>(),
+        vec![("a".to_string(), 3), ("b".to_string(), 2)]
+    );
+    assert_eq!(m.remove("a"), Some(3));
+    assert_eq!(m.len(), 1);
+
+    let h: SyncMutMap<u32, u32, std::collections::HashMap<u32, u32>> =
+        vec![(1, 2)].into_iter().collect();
+    assert_eq!(h.get(&1), Some(2));
+}
+
+#[test]
+fn map_across_threads() {
+    let m = Arc::new(SyncMutMap::new());
+    let threads: Vec<_> = (0..8)
+        .map(|i| {
+            let m = m.clone();
+            thread::spawn(move || {
+                m.insert(i, i * i);
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(m.len(), 8);
+    assert_eq!(m.get(&3), Some(9));
+}
+
+#[test]
+fn same_code_as_single_threaded() {
+    // Mutating one cell while another is borrowed is allowed, as each
+    // cell has its own lock.
+    let a = SyncMutVec::from(vec![1]);
+    let b = SyncMutVec::new();
+    a.with_element(0, |x| b.push(*x));
+    assert_eq!(b.into_inner(), vec![1]);
+
+    // The same methods as on the single-threaded types.
+    fn total(v: &MutVec<u32>, m: &Mut<u32>) -> u32 {
+        v.iter().sum::<u32>() + m.get()
+    }
+    fn sync_total(v: &SyncMutVec<u32>, m: &SyncMut<u32>) -> u32 {
+        v.iter().sum::<u32>() + m.get()
+    }
+    assert_eq!(
+        total(&MutVec::from(vec![1, 2]), &Mut::new(3)),
+        sync_total(&SyncMutVec::from(vec![1, 2]), &SyncMut::new(3))
+    );
+}
@@ -0,0 +1,55 @@
+//! Support for async code on single-threaded executors. All tasks on
+//! a thread share its lock, so a task that holds a guard (say, a
+//! borrow of some cell) across an `.await` leaves the lock held while
+//! *other* tasks run, and their accesses fail with errors that have
+//! nothing to do with them.
+//!
+//! Giving each task its own lock would not be sound: the suspended
+//! guard still refers to the cell's data, and another task could
+//! modify it. Instead, wrapping a task's future with `scoped` checks
+//! the lock each time the task yields, and reports
+//! `Violation::HeldAcrossAwait` (see the `violation` module) against
+//! the offending task. Executors with their own poll loop can perform
+//! the same check with `is_locked`.
+
+use crate::mcell::lock;
+use crate::violation::{self, Violation};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+mod test;
+
+/// True if the current thread holds a lock on the mut-cells -- e.g.,
+/// because some task is suspended while holding a guard.
+pub fn is_locked() -> bool {
+    lock::is_locked()
+}
+
+/// Wrap `future` so that suspending while holding a lock is reported
+/// as a violation of the task rather than of whoever next accesses a
+/// mut-cell.
+pub fn scoped<F: Future>(future: F) -> LockScoped<F> {
+    LockScoped { future }
+}
+
+/// The future returned by `scoped`.
+pub struct LockScoped<F> {
+    future: F,
+}
+
+impl<F: Future> Future for LockScoped<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: `future` is structurally pinned; we never move it
+        // out of `self`.
+        let future = unsafe { self.map_unchecked_mut(|s| &mut s.future) };
+        let was_locked = lock::is_locked();
+        let result = future.poll(cx);
+        if result.is_pending() && !was_locked && lock::is_locked() {
+            violation::report(Violation::HeldAcrossAwait, None, lock::held_at(None));
+        }
+        result
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+use super::*;
+use crate::MutVec;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::task::Waker;
+
+/// A future that is pending the first time it is polled.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+fn poll<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn yield_without_lock() {
+    let v = MutVec::from(vec![1, 2]);
+    let mut task = Box::pin(scoped(async {
+        let first = v.data.borrow()[0];
+        YieldOnce(false).await;
+        v.push(first);
+    }));
+    assert!(poll(task.as_mut()).is_pending());
+    assert!(!is_locked());
+    assert!(poll(task.as_mut()).is_ready());
+    assert_eq!(v.len(), 3);
+}
+
+#[test]
+fn yield_with_lock() {
+    let v = MutVec::from(vec![1, 2]);
+    let mut task = Box::pin(scoped(async {
+        let data = v.data.borrow();
+        YieldOnce(false).await;
+        data.len()
+    }));
+    let err = catch_unwind(AssertUnwindSafe(|| poll(task.as_mut()))).unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("lock held across a suspension point"));
+    #[cfg(debug_assertions)]
+    assert!(message.contains(file!()), "{}", message);
+    assert!(is_locked());
+    drop(task);
+    assert!(!is_locked());
+}
@@ -0,0 +1,4 @@
+//! Utilities for testing code built on this crate, enabled by the
+//! `testing` feature.
+
+pub mod model;
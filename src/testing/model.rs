@@ -0,0 +1,397 @@
+//! Model-based testing: run random sequences of operations against
+//! a collection under test and against a plain std collection (the
+//! "model"), and check that the two are observationally equivalent.
+//!
+//! This pins down the semantics of mutation during iteration. The
+//! iterators of `MutVec` and `MutMap` are index-based: each call to
+//! `next` yields (a clone of) the element at the current index, if
+//! any, and then advances the index. Elements pushed during
+//! iteration are therefore visited, and removing an element before
+//! the current index causes the next element to be skipped.
+//!
+//! The traits here are implemented for `MutVec` and `MutMap`, and can
+//! be implemented for wrappers around them, so that the same
+//! harness can check those wrappers.
+
+use crate::map::MutMap;
+use crate::vec::MutVec;
+use indexmap::IndexMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+mod test;
+
+/// A small, deterministic pseudo-random number generator
+/// (xorshift64*), so that failures can be reproduced from a seed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state must never be zero (xorshift would only ever
+        // produce zeros), so the one seed that would make it zero is
+        // mapped to another constant.
+        let state = match seed ^ 0x9E37_79B9_7F4A_7C15 {
+            0 => 0x2545_F491_4F6C_DD1D,
+            state => state,
+        };
+        Rng { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number in `0..n`. Panics if `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A sequence under test.
+pub trait VecUnderTest<T> {
+    fn push(&self, value: T);
+    fn pop(&self) -> Option<T>;
+    fn get(&self, index: usize) -> Option<T>;
+    fn len(&self) -> usize;
+    fn iter(&self) -> Box<dyn Iterator<Item = T> + '_>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> VecUnderTest<T> for MutVec<T> {
+    fn push(&self, value: T) {
+        MutVec::push(self, value)
+    }
+
+    fn pop(&self) -> Option<T> {
+        MutVec::pop(self)
+    }
+
+    fn get(&self, index: usize) -> Option<T> {
+        MutVec::get(self, index)
+    }
+
+    fn len(&self) -> usize {
+        MutVec::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = T> + '_> {
+        Box::new(MutVec::iter(self))
+    }
+}
+
+/// A map under test. Removal is expected to have `swap_remove`
+/// semantics (the last entry takes the place of the removed one).
+pub trait MapUnderTest<K, V> {
+    fn insert(&self, key: K, value: V) -> Option<V>;
+    fn remove(&self, key: &K) -> Option<V>;
+    fn get(&self, key: &K) -> Option<V>;
+    fn len(&self) -> usize;
+    fn iter(&self) -> Box<dyn Iterator<Item = (K, V)> + '_>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V> MapUnderTest<K, V> for MutMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn insert(&self, key: K, value: V) -> Option<V> {
+        MutMap::insert(self, key, value)
+    }
+
+    fn remove(&self, key: &K) -> Option<V> {
+        self.remove_shared(key)
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        MutMap::get(self, key)
+    }
+
+    fn len(&self) -> usize {
+        MutMap::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (K, V)> + '_> {
+        Box::new(MutMap::iter(self))
+    }
+}
+
+/// An operation on a sequence.
+#[derive(Clone, Debug)]
+pub enum VecOp<T> {
+    Push(T),
+    Pop,
+    Get(usize),
+    Len,
+    /// Iterate to the end, performing the given operations (one per
+    /// step) between calls to `next`.
+    Iterate(Vec<VecOp<T>>),
+}
+
+/// An operation on a map.
+#[derive(Clone, Debug)]
+pub enum MapOp<K, V> {
+    Insert(K, V),
+    Remove(K),
+    Get(K),
+    Len,
+    /// Iterate to the end, performing the given operations (one per
+    /// step) between calls to `next`.
+    Iterate(Vec<MapOp<K, V>>),
+}
+
+/// Generate `n` random operations on a sequence, drawing pushed
+/// values from `value`.
+pub fn vec_ops<T>(rng: &mut Rng, n: usize, value: &mut impl FnMut(&mut Rng) -> T) -> Vec<VecOp<T>> {
+    (0..n).map(|_| vec_op(rng, value, true)).collect()
+}
+
+fn vec_op<T>(rng: &mut Rng, value: &mut impl FnMut(&mut Rng) -> T, nest: bool) -> VecOp<T> {
+    match rng.below(if nest { 10 } else { 9 }) {
+        0..=3 => VecOp::Push(value(rng)),
+        4 | 5 => VecOp::Pop,
+        6 | 7 => VecOp::Get(rng.below(16)),
+        8 => VecOp::Len,
+        _ => {
+            let steps = rng.below(8);
+            VecOp::Iterate((0..steps).map(|_| vec_op(rng, value, false)).collect())
+        }
+    }
+}
+
+/// Generate `n` random operations on a map, drawing keys and values
+/// from `key` and `value`. Keys should be drawn from a small domain
+/// so that removals and lookups find something.
+pub fn map_ops<K, V>(
+    rng: &mut Rng,
+    n: usize,
+    key: &mut impl FnMut(&mut Rng) -> K,
+    value: &mut impl FnMut(&mut Rng) -> V,
+) -> Vec<MapOp<K, V>> {
+    (0..n).map(|_| map_op(rng, key, value, true)).collect()
+}
+
+fn map_op<K, V>(
+    rng: &mut Rng,
+    key: &mut impl FnMut(&mut Rng) -> K,
+    value: &mut impl FnMut(&mut Rng) -> V,
+    nest: bool,
+) -> MapOp<K, V> {
+    match rng.below(if nest { 10 } else { 9 }) {
+        0..=3 => {
+            let k = key(rng);
+            MapOp::Insert(k, value(rng))
+        }
+        4 | 5 => MapOp::Remove(key(rng)),
+        6 | 7 => MapOp::Get(key(rng)),
+        8 => MapOp::Len,
+        _ => {
+            let steps = rng.below(8);
+            MapOp::Iterate((0..steps).map(|_| map_op(rng, key, value, false)).collect())
+        }
+    }
+}
+
+/// Describes where the collection under test and the model disagreed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the (top-level) operation that diverged.
+    pub op_index: usize,
+    pub message: String,
+}
+
+fn check<R: PartialEq + Debug>(
+    op_index: usize,
+    what: &str,
+    actual: R,
+    expected: R,
+) -> Result<(), Divergence> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Divergence {
+            op_index,
+            message: format!("{}: got {:?}, model has {:?}", what, actual, expected),
+        })
+    }
+}
+
+/// Run `ops` against `sut` and a `Vec`, which should start out empty.
+pub fn run_vec_ops<T, S>(sut: &S, ops: &[VecOp<T>]) -> Result<(), Divergence>
+where
+    T: Clone + PartialEq + Debug,
+    S: VecUnderTest<T> + ?Sized,
+{
+    let mut model: Vec<T> = vec![];
+    for (op_index, op) in ops.iter().enumerate() {
+        match op {
+            VecOp::Iterate(steps) => {
+                let mut iter = sut.iter();
+                let mut index = 0;
+                let mut steps = steps.iter();
+                loop {
+                    let expected = model.get(index).cloned();
+                    check(op_index, "iterator `next`", iter.next(), expected.clone())?;
+                    if expected.is_none() {
+                        break;
+                    }
+                    index += 1;
+                    if let Some(step) = steps.next() {
+                        apply_vec_op(op_index, sut, &mut model, step)?;
+                    }
+                }
+            }
+            _ => apply_vec_op(op_index, sut, &mut model, op)?,
+        }
+    }
+    check(ops.len(), "final length", sut.len(), model.len())
+}
+
+fn apply_vec_op<T, S>(
+    op_index: usize,
+    sut: &S,
+    model: &mut Vec<T>,
+    op: &VecOp<T>,
+) -> Result<(), Divergence>
+where
+    T: Clone + PartialEq + Debug,
+    S: VecUnderTest<T> + ?Sized,
+{
+    match op {
+        VecOp::Push(value) => {
+            sut.push(value.clone());
+            model.push(value.clone());
+            Ok(())
+        }
+        VecOp::Pop => check(op_index, "pop", sut.pop(), model.pop()),
+        VecOp::Get(index) => check(op_index, "get", sut.get(*index), model.get(*index).cloned()),
+        VecOp::Len => check(op_index, "len", sut.len(), model.len()),
+        VecOp::Iterate(_) => panic!("nested iteration is not supported"),
+    }
+}
+
+/// Run `ops` against `sut` and an `IndexMap`, which should start out
+/// empty.
+pub fn run_map_ops<K, V, S>(sut: &S, ops: &[MapOp<K, V>]) -> Result<(), Divergence>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: Clone + PartialEq + Debug,
+    S: MapUnderTest<K, V> + ?Sized,
+{
+    let mut model: IndexMap<K, V> = IndexMap::new();
+    for (op_index, op) in ops.iter().enumerate() {
+        match op {
+            MapOp::Iterate(steps) => {
+                let mut iter = sut.iter();
+                let mut index = 0;
+                let mut steps = steps.iter();
+                loop {
+                    let expected = model.get_index(index).map(|(k, v)| (k.clone(), v.clone()));
+                    check(op_index, "iterator `next`", iter.next(), expected.clone())?;
+                    if expected.is_none() {
+                        break;
+                    }
+                    index += 1;
+                    if let Some(step) = steps.next() {
+                        apply_map_op(op_index, sut, &mut model, step)?;
+                    }
+                }
+            }
+            _ => apply_map_op(op_index, sut, &mut model, op)?,
+        }
+    }
+    check(ops.len(), "final length", sut.len(), model.len())
+}
+
+fn apply_map_op<K, V, S>(
+    op_index: usize,
+    sut: &S,
+    model: &mut IndexMap<K, V>,
+    op: &MapOp<K, V>,
+) -> Result<(), Divergence>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: Clone + PartialEq + Debug,
+    S: MapUnderTest<K, V> + ?Sized,
+{
+    match op {
+        MapOp::Insert(key, value) => check(
+            op_index,
+            "insert",
+            sut.insert(key.clone(), value.clone()),
+            model.insert(key.clone(), value.clone()),
+        ),
+        MapOp::Remove(key) => check(op_index, "remove", sut.remove(key), model.swap_remove(key)),
+        MapOp::Get(key) => check(op_index, "get", sut.get(key), model.get(key).cloned()),
+        MapOp::Len => check(op_index, "len", sut.len(), model.len()),
+        MapOp::Iterate(_) => panic!("nested iteration is not supported"),
+    }
+}
+
+/// Check `num_cases` random sequences of `ops_per_case` operations
+/// against fresh collections from `new`, panicking on the first
+/// divergence (reporting the seed needed to reproduce it).
+pub fn check_vec<T, S>(
+    seed: u64,
+    num_cases: usize,
+    ops_per_case: usize,
+    new: impl Fn() -> S,
+    mut value: impl FnMut(&mut Rng) -> T,
+) where
+    T: Clone + PartialEq + Debug,
+    S: VecUnderTest<T>,
+{
+    for case in 0..num_cases as u64 {
+        let mut rng = Rng::new(seed.wrapping_add(case));
+        let ops = vec_ops(&mut rng, ops_per_case, &mut value);
+        if let Err(d) = run_vec_ops(&new(), &ops) {
+            panic!(
+                "seed {}: operation {} ({:?}): {}",
+                seed.wrapping_add(case),
+                d.op_index,
+                ops.get(d.op_index),
+                d.message
+            );
+        }
+    }
+}
+
+/// The map counterpart of `check_vec`.
+pub fn check_map<K, V, S>(
+    seed: u64,
+    num_cases: usize,
+    ops_per_case: usize,
+    new: impl Fn() -> S,
+    mut key: impl FnMut(&mut Rng) -> K,
+    mut value: impl FnMut(&mut Rng) -> V,
+) where
+    K: Eq + Hash + Clone + Debug,
+    V: Clone + PartialEq + Debug,
+    S: MapUnderTest<K, V>,
+{
+    for case in 0..num_cases as u64 {
+        let mut rng = Rng::new(seed.wrapping_add(case));
+        let ops = map_ops(&mut rng, ops_per_case, &mut key, &mut value);
+        if let Err(d) = run_map_ops(&new(), &ops) {
+            panic!(
+                "seed {}: operation {} ({:?}): {}",
+                seed.wrapping_add(case),
+                d.op_index,
+                ops.get(d.op_index),
+                d.message
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn mut_vec_matches_vec() {
+    check_vec(0, 200, 50, MutVec::new, |rng| rng.below(100) as u32);
+}
+
+#[test]
+fn mut_map_matches_index_map() {
+    check_map(
+        0,
+        200,
+        50,
+        MutMap::new,
+        |rng| rng.below(8) as u8,
+        |rng| rng.next_u64(),
+    );
+}
+
+/// A deliberately broken wrapper, to check that divergences are
+/// caught.
+struct DropsEveryThird(MutVec<u32>);
+
+impl VecUnderTest<u32> for DropsEveryThird {
+    fn push(&self, value: u32) {
+        if !value.is_multiple_of(3) {
+            self.0.push(value);
+        }
+    }
+
+    fn pop(&self) -> Option<u32> {
+        self.0.pop()
+    }
+
+    fn get(&self, index: usize) -> Option<u32> {
+        self.0.get(index)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+        Box::new(self.0.iter())
+    }
+}
+
+#[test]
+fn divergence_detected() {
+    let ops = vec![VecOp::Push(1), VecOp::Push(3), VecOp::Len];
+    let err = run_vec_ops(&DropsEveryThird(MutVec::new()), &ops).unwrap_err();
+    assert_eq!(err.op_index, 2);
+}
+
+#[test]
+fn push_during_iteration_is_visited() {
+    let ops = vec![
+        VecOp::Push(1),
+        VecOp::Iterate(vec![VecOp::Push(2), VecOp::Pop, VecOp::Pop]),
+    ];
+    assert_eq!(run_vec_ops(&MutVec::new(), &ops), Ok(()));
+}
+
+#[test]
+fn rng_never_stuck_at_zero() {
+    let mut rng = Rng::new(0x9E37_79B9_7F4A_7C15);
+    assert!((0..4).any(|_| rng.next_u64() != 0));
+}
@@ -0,0 +1,165 @@
+//! Traits capturing the API shared by the crate's sequence and map
+//! types, so that code can be written generically over (e.g.)
+//! `MutVec<T>` and `Vec<T>`.
+//!
+//! These traits are deliberately not part of the prelude: with
+//! `MutSequence` in scope, `vec.get(i)` on a `Vec<T>` resolves to the
+//! trait method (which clones) rather than the slice method.
+
+use crate::map::{MapLookup, MapStore};
+use crate::{MutMap, MutVec};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+mod test;
+
+/// A growable sequence of `T`.
+pub trait MutSequence<T> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push(&mut self, value: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns a clone of the element at `index`, if any.
+    fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone;
+}
+
+/// A map from `K` to `V`.
+pub trait MutMapLike<K, V> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    /// Returns a clone of the value for `key`, if any.
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone;
+}
+
+impl<T> MutSequence<T> for MutVec<T> {
+    fn len(&self) -> usize {
+        MutVec::len(self)
+    }
+
+    fn push(&mut self, value: T) {
+        MutVec::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        MutVec::pop(self)
+    }
+
+    fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        MutVec::get(self, index)
+    }
+}
+
+impl<T> MutSequence<T> for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn push(&mut self, value: T) {
+        Vec::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn get(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        <[T]>::get(self, index).cloned()
+    }
+}
+
+impl<K, V, S> MutMapLike<K, V> for MutMap<K, V, S>
+where
+    S: MapStore<K, V> + MapLookup<K, V, Key = K>,
+{
+    fn len(&self) -> usize {
+        MutMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        MutMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        MutMap::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        MutMap::get(self, key)
+    }
+}
+
+impl<K, V> MutMapLike<K, V> for IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn len(&self) -> usize {
+        IndexMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        IndexMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        IndexMap::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        IndexMap::get(self, key).cloned()
+    }
+}
+
+impl<K, V> MutMapLike<K, V> for HashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        HashMap::get(self, key).cloned()
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(test)]
+
+use super::*;
+
+fn fill<S: MutSequence<u32> + Default>() -> S {
+    let mut s = S::default();
+    for i in 0..4 {
+        s.push(i);
+    }
+    s
+}
+
+fn fill_map<M: MutMapLike<u32, String> + Default>() -> M {
+    let mut m = M::default();
+    for i in 0..4 {
+        m.insert(i, i.to_string());
+    }
+    m
+}
+
+#[test]
+fn sequences_agree() {
+    let mut v: Vec<u32> = fill();
+    let mut mv: MutVec<u32> = fill();
+    assert_eq!(MutSequence::len(&v), MutSequence::len(&mv));
+    assert_eq!(MutSequence::get(&v, 2), MutSequence::get(&mv, 2));
+    assert_eq!(MutSequence::get(&v, 9), None);
+    assert_eq!(MutSequence::pop(&mut v), MutSequence::pop(&mut mv));
+    assert_eq!(v, mv.into_inner());
+}
+
+#[test]
+fn maps_agree() {
+    let mut h: HashMap<u32, String> = fill_map();
+    let mut i: IndexMap<u32, String> = fill_map();
+    let mut m: MutMap<u32, String> = fill_map();
+    assert_eq!(MutMapLike::get(&h, &1), Some("1".to_string()));
+    assert_eq!(MutMapLike::get(&i, &1), MutMapLike::get(&m, &1));
+    assert_eq!(MutMapLike::remove(&mut h, &3), Some("3".to_string()));
+    assert_eq!(
+        MutMapLike::remove(&mut i, &3),
+        MutMapLike::remove(&mut m, &3)
+    );
+    assert!(!MutMapLike::is_empty(&m));
+    assert_eq!(MutMapLike::len(&h), MutMapLike::len(&m));
+    assert_eq!(i, m.into_inner());
+}
@@ -0,0 +1,233 @@
+//! Transactions over several collections. Within `transaction`, the
+//! mutations made through the `Transaction` handle are journaled, and
+//! if the closure returns `Err` (or panics) they are undone, most
+//! recent first:
+//!
+//! ```
+//! use mutable::{transaction, MutMap};
+//!
+//! let stock = MutMap::new();
+//! stock.insert("apples", 3);
+//! let orders = MutMap::new();
+//! let result = transaction(|tx| {
+//!     tx.insert(&orders, 1, "apples");
+//!     let left = stock.get("apples").unwrap();
+//!     if left < 5 {
+//!         return Err("out of stock");
+//!     }
+//!     tx.insert(&stock, "apples", left - 5);
+//!     Ok(())
+//! });
+//! assert_eq!(result, Err("out of stock"));
+//! assert!(orders.is_empty());
+//! ```
+//!
+//! Collections are not snapshotted automatically, as there is no way
+//! to tell which ones the closure touches. Mutations made directly on
+//! a collection (bypassing the handle) are rolled back only if the
+//! collection was registered with `Transaction::snapshot` beforehand.
+
+use crate::history::{History, Journaled, Replay};
+use crate::map::{MapStore, MutMap};
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+use std::ops::Deref;
+
+mod test;
+
+/// Run `op` as a transaction: mutations performed through the
+/// `Transaction` handle take effect immediately, but are rolled back
+/// (most recent first) if `op` returns `Err` or panics.
+///
+/// Mutations made directly on the collections, bypassing the handle,
+/// are not rolled back -- unless the collection was registered with
+/// `Transaction::snapshot` first, in which case it is restored as a
+/// whole. Nothing is snapshotted automatically: a collection that is
+/// neither mutated through the handle nor snapshotted is left as the
+/// closure left it.
+///
+/// A collection poisoned by the panic (see the `poison` module) is
+/// only rolled back if it was snapshotted: restoring the snapshot
+/// clears the poison. The operations journaled for it otherwise are
+/// skipped, and it stays poisoned.
+pub fn transaction<'a, R, E>(op: impl FnOnce(&Transaction<'a>) -> Result<R, E>) -> Result<R, E> {
+    let tx = Transaction {
+        history: History::new(),
+    };
+    let result = op(&tx);
+    if result.is_ok() {
+        // Commit: forget how to undo.
+        tx.history.clear_history();
+    }
+    result
+}
+
+/// Handle for mutating collections within a `transaction`. If the
+/// handle is dropped without committing, the mutations are rolled
+/// back.
+///
+/// The mutations are journaled in a `History`, whose methods (`set`,
+/// `push`, `insert`, and so forth) are available through `Deref`. So
+/// are `checkpoint` and `undo`, to revert part of the transaction
+/// (a snapshot is restored when its step is undone, and is not
+/// redone).
+pub struct Transaction<'a> {
+    history: History<'a>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Apply `op` to `collection`, journaling its inverse. The same as
+    /// `History::record`.
+    pub fn apply<C>(&self, collection: &'a C, op: C::Op)
+    where
+        C: Journaled,
+        C::Op: 'a,
+    {
+        self.history.record(collection, op);
+    }
+
+    /// Save the contents of `collection`, to be restored on rollback.
+    /// This covers every mutation made afterwards, including those
+    /// made directly on the collection (or by code that knows nothing
+    /// of the transaction), at the cost of a clone.
+    ///
+    /// ```
+    /// # use mutable::{transaction, MutVec};
+    /// let v = MutVec::from(vec![1]);
+    /// let result: Result<(), &str> = transaction(|tx| {
+    ///     tx.snapshot(&v);
+    ///     v.push(2);
+    ///     Err("changed my mind")
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(v.into_inner(), vec![1]);
+    /// ```
+    pub fn snapshot<C>(&self, collection: &'a C)
+    where
+        C: Restorable,
+        C::Saved: 'a,
+    {
+        let saved = collection.save();
+        self.history.journal(Box::new(Snapshot {
+            collection,
+            version: collection.version(),
+            saved,
+        }));
+    }
+}
+
+impl<'a> Deref for Transaction<'a> {
+    type Target = History<'a>;
+
+    fn deref(&self) -> &History<'a> {
+        &self.history
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        self.history.rollback();
+    }
+}
+
+/// A snapshot, journaled as an operation that restores it (and has
+/// no inverse).
+struct Snapshot<'a, C: Restorable> {
+    collection: &'a C,
+
+    /// The version of the collection when it was saved: if it is
+    /// unchanged, there is nothing to restore.
+    version: u64,
+
+    saved: C::Saved,
+}
+
+impl<'a, C: Restorable> Replay<'a> for Snapshot<'a, C> {
+    fn replay(self: Box<Self>) -> Option<Box<dyn Replay<'a> + 'a>> {
+        if self.collection.version() != self.version {
+            self.collection.restore(self.saved);
+        }
+        None
+    }
+
+    /// Restoring clears the poison, so it always goes ahead.
+    fn is_poisoned(&self) -> bool {
+        false
+    }
+}
+
+/// A collection whose contents can be saved and later restored as a
+/// whole; see `Transaction::snapshot`.
+pub trait Restorable {
+    /// The saved contents.
+    type Saved;
+
+    fn save(&self) -> Self::Saved;
+
+    /// The number of mutations so far (e.g., `MutVec::version`), so
+    /// that a collection that has not changed since it was saved is
+    /// not restored.
+    fn version(&self) -> u64;
+
+    /// Replace the contents with `saved`, clearing any poison (see the
+    /// `poison` module), as none of the old contents remain.
+    fn restore(&self, saved: Self::Saved);
+}
+
+impl<T: Clone> Restorable for Mut<T> {
+    type Saved = T;
+
+    fn save(&self) -> T {
+        self.get()
+    }
+
+    fn version(&self) -> u64 {
+        Mut::version(self)
+    }
+
+    fn restore(&self, saved: T) {
+        self.unpoison();
+        self.set(saved)
+    }
+}
+
+/// Restoring is reported as a `ListChange::Reset`.
+impl<T: Clone> Restorable for MutVec<T> {
+    type Saved = Vec<T>;
+
+    fn save(&self) -> Vec<T> {
+        self.data.borrow().clone()
+    }
+
+    fn version(&self) -> u64 {
+        MutVec::version(self)
+    }
+
+    fn restore(&self, saved: Vec<T>) {
+        self.unpoison();
+        self.replace(saved);
+    }
+}
+
+/// Restoring is reported to the element hooks (and so to `watch`) as
+/// the removal of every entry, then the insertion of the saved ones.
+impl<K, V, S> Restorable for MutMap<K, V, S>
+where
+    S: MapStore<K, V> + Clone,
+    for<'a> &'a S: IntoIterator<Item = (&'a K, &'a V)>,
+{
+    type Saved = S;
+
+    fn save(&self) -> S {
+        self.with_borrowed(S::clone)
+    }
+
+    fn version(&self) -> u64 {
+        MutMap::version(self)
+    }
+
+    fn restore(&self, saved: S) {
+        self.unpoison();
+        self.replace_store(saved)
+    }
+}
@@ -0,0 +1,196 @@
+#![cfg(test)]
+
+use super::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[test]
+fn commit_and_rollback() {
+    let accounts = MutMap::new();
+    accounts.insert("alice", 10);
+    accounts.insert("bob", 0);
+    let log = MutVec::new();
+
+    let transfer = |amount: u32| {
+        transaction(|tx| {
+            let from = accounts.get("alice").unwrap();
+            tx.insert(&accounts, "bob", accounts.get("bob").unwrap() + amount);
+            tx.push(&log, amount);
+            if from < amount {
+                return Err("insufficient funds");
+            }
+            tx.insert(&accounts, "alice", from - amount);
+            Ok(())
+        })
+    };
+
+    assert_eq!(transfer(7), Ok(()));
+    assert_eq!(transfer(7), Err("insufficient funds"));
+    assert_eq!(accounts.get("alice"), Some(3));
+    assert_eq!(accounts.get("bob"), Some(7));
+    assert_eq!(log.take(), vec![7]);
+}
+
+#[test]
+fn rollback_on_panic() {
+    let m = Mut::new(1);
+    let v = MutVec::from(vec![1, 2]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        transaction::<(), ()>(|tx| {
+            tx.set(&m, 2);
+            assert_eq!(tx.pop(&v), Some(2));
+            panic!("oops")
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(m.get(), 1);
+    assert_eq!(v.take(), vec![1, 2]);
+}
+
+#[test]
+fn snapshot_restores_direct_mutations() {
+    let m = Mut::new(1);
+    let v = MutVec::from(vec![1, 2]);
+    let map = MutMap::new();
+    map.insert("a", 1);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        transaction::<(), ()>(|tx| {
+            tx.snapshot(&m);
+            tx.snapshot(&v);
+            tx.snapshot(&map);
+            m.set(2);
+            v.push(3);
+            tx.push(&v, 4);
+            map.insert("b", 2);
+            map.insert("a", 3);
+            panic!("oops")
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(m.get(), 1);
+    assert_eq!(v.take(), vec![1, 2]);
+    assert_eq!(
+        map.into_inner().into_iter().collect::<Vec<_>>(),
+        vec![("a", 1)]
+    );
+}
+
+#[test]
+fn snapshot_is_forgotten_on_commit() {
+    let v = MutVec::from(vec![1]);
+    let result: Result<(), ()> = transaction(|tx| {
+        tx.snapshot(&v);
+        v.push(2);
+        Ok(())
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(v.take(), vec![1, 2]);
+}
+
+#[test]
+fn rollback_of_poisoned_collections() {
+    let v = MutVec::from(vec![1]);
+    v.enable_poisoning();
+    let w = MutVec::from(vec![1]);
+    w.enable_poisoning();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        transaction::<(), ()>(|tx| {
+            tx.snapshot(&v);
+            v.push(2);
+            tx.push(&w, 2);
+            v.with_element_mut(0, |_| panic!("oops"));
+            w.with_element_mut(0, |_| panic!("oops"));
+            Ok(())
+        })
+    }));
+    assert!(result.is_err());
+
+    // The snapshot replaced the poisoned contents...
+    assert!(!v.is_poisoned());
+    assert_eq!(v.take(), vec![1]);
+
+    // ... but `w` was never reached by the panic.
+    assert!(!w.is_poisoned());
+    assert_eq!(w.take(), vec![1]);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        transaction::<(), ()>(|tx| {
+            tx.push(&w, 2);
+            w.with_element_mut(0, |_| panic!("oops"));
+            Ok(())
+        })
+    }));
+    assert!(result.is_err());
+
+    // Without a snapshot, the journaled `push` is not undone.
+    assert!(w.is_poisoned());
+    w.unpoison();
+    assert_eq!(w.take(), vec![2]);
+}
+
+#[test]
+fn undo_part_of_a_transaction() {
+    let v = MutVec::from(vec![1]);
+    let result: Result<(), ()> = transaction(|tx| {
+        tx.push(&v, 2);
+        tx.checkpoint();
+        tx.snapshot(&v);
+        v.push(3);
+        tx.push(&v, 4);
+        assert!(tx.undo());
+        assert_eq!(v.iter().collect::<Vec<_>>(), vec![1, 2]);
+        tx.push(&v, 5);
+        Ok(())
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(v.take(), vec![1, 2, 5]);
+}
+
+#[test]
+fn rollback_is_reported_to_watch() {
+    use crate::watch::MapEvent;
+
+    let m = MutMap::new();
+    m.insert("a", 1);
+    let changes = m.watch();
+    let result: Result<(), ()> = transaction(|tx| {
+        tx.insert(&m, "b", 2);
+        tx.snapshot(&m);
+        m.insert("c", 3);
+        Err(())
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        changes.drain(),
+        vec![
+            MapEvent::Inserted("b", 2),
+            MapEvent::Inserted("c", 3),
+            // Restoring the snapshot...
+            MapEvent::Removed("a"),
+            MapEvent::Removed("b"),
+            MapEvent::Removed("c"),
+            MapEvent::Inserted("a", 1),
+            MapEvent::Inserted("b", 2),
+            // ... and undoing the journaled insertion.
+            MapEvent::Removed("b"),
+        ]
+    );
+    assert_eq!(m.iter().collect::<Vec<_>>(), vec![("a", 1)]);
+}
+
+#[test]
+fn inside_a_borrow() {
+    let v = MutVec::from(vec![1]);
+    let m = Mut::new(1);
+    v.with_element(0, |_| {
+        assert_eq!(transaction(|_| Ok::<_, ()>(())), Ok(()));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            transaction::<(), ()>(|tx| {
+                tx.snapshot(&m);
+                panic!("oops")
+            })
+        }));
+        assert!(result.is_err());
+    });
+    assert_eq!(m.get(), 1);
+}
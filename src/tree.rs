@@ -0,0 +1,273 @@
+//! A tree (more precisely, a forest) of mutable data.
+
+use crate::mcell::MCell;
+use std::fmt;
+
+mod test;
+
+/// Identifies a node of a `MutTree`. Ids are never reused, so the id
+/// of a removed node stays invalid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+/// A forest of nodes, each holding a `T`, stored in a single cell.
+/// Nodes are referred to by `NodeId` rather than by reference, so the
+/// tree can be edited while it is being walked: the iterators
+/// (`ancestors`, `descendants`) only borrow the tree for each step.
+///
+/// The methods that take a `NodeId` panic if it does not refer to a
+/// node of the tree, unless noted otherwise.
+pub struct MutTree<T> {
+    data: MCell<Nodes<T>>,
+}
+
+struct Nodes<T> {
+    /// Indexed by `NodeId`; `None` for removed nodes.
+    nodes: Vec<Option<Node<T>>>,
+    len: usize,
+}
+
+struct Node<T> {
+    value: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl<T> Default for Nodes<T> {
+    fn default() -> Self {
+        Nodes {
+            nodes: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Nodes<T> {
+    fn node(&self, id: NodeId) -> &Node<T> {
+        self.get(id).expect("invalid node id")
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
+        self.nodes
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .expect("invalid node id")
+    }
+
+    fn get(&self, id: NodeId) -> Option<&Node<T>> {
+        self.nodes.get(id.0)?.as_ref()
+    }
+
+    fn add(&mut self, value: T, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        if let Some(parent) = parent {
+            self.node_mut(parent).children.push(id);
+        }
+        self.nodes.push(Some(Node {
+            value,
+            parent,
+            children: vec![],
+        }));
+        self.len += 1;
+        id
+    }
+
+    fn unlink(&mut self, id: NodeId) {
+        if let Some(parent) = self.node_mut(id).parent.take() {
+            self.node_mut(parent).children.retain(|&c| c != id);
+        }
+    }
+
+    fn is_ancestor(&self, ancestor: NodeId, mut id: NodeId) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+            match self.node(id).parent {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+impl<T> MutTree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.data.borrow().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True if `id` refers to a node of the tree.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.data.borrow().get(id).is_some()
+    }
+
+    /// Add a node without a parent.
+    pub fn add_root(&self, value: T) -> NodeId {
+        self.data.check_out().add(value, None)
+    }
+
+    /// Add a node as the last child of `parent`.
+    pub fn add_child(&self, parent: NodeId, value: T) -> NodeId {
+        self.data.check_out().add(value, Some(parent))
+    }
+
+    /// Detach `id` (with its descendants) from its parent, making it a
+    /// root.
+    pub fn detach(&self, id: NodeId) {
+        self.data.check_out().unlink(id);
+    }
+
+    /// Move `id` (with its descendants) to be the last child of
+    /// `parent`. Panics if `parent` is `id` or one of its descendants.
+    pub fn reparent(&self, id: NodeId, parent: NodeId) {
+        let mut data = self.data.check_out();
+        assert!(
+            !data.is_ancestor(id, parent),
+            "cannot move a node beneath itself"
+        );
+        data.unlink(id);
+        data.node_mut(id).parent = Some(parent);
+        data.node_mut(parent).children.push(id);
+    }
+
+    /// Remove `id` and its descendants, returning the value of `id`.
+    /// During the removal, all mut-cells are locked and read-only;
+    /// attempts to read *this* tree will find it empty.
+    pub fn remove(&self, id: NodeId) -> T {
+        let mut data = self.data.check_out();
+        data.unlink(id);
+        let mut stack = data.node(id).children.clone();
+        while let Some(child) = stack.pop() {
+            let node = data.nodes[child.0].take().unwrap();
+            stack.extend(node.children);
+            data.len -= 1;
+        }
+        data.len -= 1;
+        data.nodes[id.0].take().unwrap().value
+    }
+
+    /// Load (a clone of) the value of `id`, or `None` if `id` is
+    /// invalid.
+    pub fn get(&self, id: NodeId) -> Option<T>
+    where
+        T: Clone,
+    {
+        Some(self.data.borrow().get(id)?.value.clone())
+    }
+
+    /// Store `value` in `id`, returning the old value.
+    pub fn replace(&self, id: NodeId, value: T) -> T {
+        std::mem::replace(&mut self.data.check_out().node_mut(id).value, value)
+    }
+
+    /// Runs `op` with a reference to the value of `id`. All mut-cells
+    /// are read-only while `op` runs.
+    #[track_caller]
+    pub fn with<R>(&self, id: NodeId, op: impl FnOnce(&T) -> R) -> R {
+        op(&self.data.borrow().node(id).value)
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.data.borrow().node(id).parent
+    }
+
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        self.data.borrow().node(id).children.clone()
+    }
+
+    /// The nodes without a parent, in the order they were added.
+    pub fn roots(&self) -> Vec<NodeId> {
+        let data = self.data.borrow();
+        (0..data.nodes.len())
+            .map(NodeId)
+            .filter(|&id| data.get(id).is_some_and(|n| n.parent.is_none()))
+            .collect()
+    }
+
+    /// Iterate over the ancestors of `id`, starting with its parent.
+    /// The tree may be modified during the iteration: each step reads
+    /// the current parent of the node last returned, stopping if that
+    /// node has been removed.
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_, T> {
+        Ancestors {
+            tree: self,
+            next: self.parent(id),
+        }
+    }
+
+    /// Iterate over `id` and its descendants, in pre-order. The tree
+    /// may be modified during the iteration: the children of each
+    /// node are read when the iteration reaches it, and removed nodes
+    /// are skipped.
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_, T> {
+        assert!(self.contains(id), "invalid node id");
+        Descendants {
+            tree: self,
+            stack: vec![id],
+        }
+    }
+}
+
+impl<T> Default for MutTree<T> {
+    fn default() -> Self {
+        MutTree {
+            data: MCell::new(Nodes::default()),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_map()
+            .entries(data.nodes.iter().enumerate().filter_map(|(i, node)| {
+                let node = node.as_ref()?;
+                Some(((NodeId(i), node.parent), &node.value))
+            }))
+            .finish()
+    }
+}
+
+pub struct Ancestors<'iter, T> {
+    tree: &'iter MutTree<T>,
+    next: Option<NodeId>,
+}
+
+impl<'iter, T> Iterator for Ancestors<'iter, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        self.next = self.tree.data.borrow().get(id).and_then(|n| n.parent);
+        Some(id)
+    }
+}
+
+pub struct Descendants<'iter, T> {
+    tree: &'iter MutTree<T>,
+    stack: Vec<NodeId>,
+}
+
+impl<'iter, T> Iterator for Descendants<'iter, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let data = self.tree.data.borrow();
+        while let Some(id) = self.stack.pop() {
+            if let Some(node) = data.get(id) {
+                self.stack.extend(node.children.iter().rev());
+                return Some(id);
+            }
+        }
+        None
+    }
+}
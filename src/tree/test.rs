@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use super::*;
+
+fn sample() -> (MutTree<&'static str>, [NodeId; 4]) {
+    let tree = MutTree::new();
+    let root = tree.add_root("root");
+    let a = tree.add_child(root, "a");
+    let b = tree.add_child(root, "b");
+    let a1 = tree.add_child(a, "a1");
+    (tree, [root, a, b, a1])
+}
+
+#[test]
+fn structure() {
+    let (tree, [root, a, b, a1]) = sample();
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.children(root), vec![a, b]);
+    assert_eq!(tree.parent(a1), Some(a));
+    assert_eq!(tree.ancestors(a1).collect::<Vec<_>>(), vec![a, root]);
+    assert_eq!(
+        tree.descendants(root).collect::<Vec<_>>(),
+        vec![root, a, a1, b]
+    );
+    assert_eq!(tree.get(a1), Some("a1"));
+}
+
+#[test]
+fn detach_and_reparent() {
+    let (tree, [root, a, b, a1]) = sample();
+    tree.detach(a);
+    assert_eq!(tree.roots(), vec![root, a]);
+    assert_eq!(tree.children(root), vec![b]);
+
+    tree.reparent(a, b);
+    assert_eq!(tree.ancestors(a1).collect::<Vec<_>>(), vec![a, b, root]);
+    assert_eq!(tree.roots(), vec![root]);
+}
+
+#[test]
+#[should_panic(expected = "cannot move a node beneath itself")]
+fn reparent_cycle() {
+    let (tree, [root, _, _, a1]) = sample();
+    tree.reparent(root, a1);
+}
+
+#[test]
+fn remove_subtree() {
+    let (tree, [root, a, b, a1]) = sample();
+    assert_eq!(tree.remove(a), "a");
+    assert_eq!(tree.len(), 2);
+    assert!(!tree.contains(a1));
+    assert_eq!(tree.children(root), vec![b]);
+    assert_eq!(tree.get(a), None);
+}
+
+#[test]
+fn edit_while_walking() {
+    let (tree, [root, ..]) = sample();
+    for id in tree.descendants(root) {
+        // The visitor only has `&tree`, but can still edit it.
+        if tree.children(id).is_empty() && tree.with(id, |v| v.len() < 3) {
+            tree.add_child(id, "leaf");
+        }
+    }
+    assert_eq!(tree.len(), 6);
+    assert_eq!(tree.descendants(root).count(), 6);
+}
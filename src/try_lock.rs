@@ -0,0 +1,130 @@
+//! Operations that return a `LockError` instead of panicking when
+//! the thread lock is not available -- for libraries that may be
+//! called back from inside a borrow (or a check-out) and would
+//! rather degrade gracefully than crash.
+//!
+//! `try_read` and `try_modify` work with any operation on any cell:
+//! they check that the lock is available and, if so, run the
+//! operation. The common cases also have methods, named with a
+//! `_locked` suffix (`Mut::get_locked`, `MutVec::push_locked`,
+//! `MutMap::insert_locked`, ...), which only check the lock of the
+//! cell's own domain (see the `domain` module). The insertions return
+//! an `InsertError`, which also covers a full collection: they never
+//! panic, whatever the `LimitPolicy`.
+//!
+//! Only the availability of the lock *before* the operation is
+//! checked. A violation committed by user code that the operation
+//! runs (a `Clone` impl that mutates a cell, say) still panics.
+
+use crate::error::{InsertError, LockError};
+use crate::map::{MapLookup, MapStore, MutMap};
+use crate::mcell::lock;
+use crate::mutbl::Mut;
+use crate::vec::MutVec;
+
+mod test;
+
+/// Runs `op`, which reads from mut-cells, if the thread lock (of
+/// every domain) allows reading; otherwise returns
+/// `LockError::WriteLocked` (or, at the reader limit,
+/// `LockError::TooManyReaders`).
+///
+/// ```
+/// # use mutable::{try_read, LockError, MutMap};
+/// let m = MutMap::new();
+/// m.insert("a", 1);
+/// assert_eq!(try_read(|| m.get("a")), Ok(Some(1)));
+/// ```
+pub fn try_read<R>(op: impl FnOnce() -> R) -> Result<R, LockError> {
+    lock::check_read(None)?;
+    Ok(op())
+}
+
+/// Runs `op`, which modifies mut-cells, if the thread lock (of
+/// every domain) allows modification; otherwise returns the kind
+/// of lock that is held.
+///
+/// ```
+/// # use mutable::{try_modify, LockError, MutVec};
+/// let v = MutVec::from(vec![1]);
+/// v.with_element(0, |_| {
+///     assert_eq!(try_modify(|| v.push(2)), Err(LockError::ReadLocked));
+/// });
+/// assert_eq!(try_modify(|| v.push(2)), Ok(()));
+/// ```
+pub fn try_modify<R>(op: impl FnOnce() -> R) -> Result<R, LockError> {
+    lock::check_write(None)?;
+    Ok(op())
+}
+
+impl<T> Mut<T> {
+    /// Like `get`, but returns an error if the value cannot be read
+    /// right now.
+    pub fn get_locked(&self) -> Result<T, LockError>
+    where
+        T: Clone,
+    {
+        lock::check_read(Some(self.data.domain()))?;
+        Ok(self.get())
+    }
+
+    /// Like `set`, but returns an error (dropping `new_value`) if the
+    /// value cannot be modified right now.
+    pub fn set_locked(&self, new_value: T) -> Result<(), LockError> {
+        self.replace_locked(new_value).map(drop)
+    }
+
+    /// Like `replace`, but returns an error (dropping `new_value`) if
+    /// the value cannot be modified right now.
+    pub fn replace_locked(&self, new_value: T) -> Result<T, LockError> {
+        lock::check_write(Some(self.data.domain()))?;
+        Ok(self.replace(new_value))
+    }
+}
+
+impl<T> MutVec<T> {
+    /// Like `get`, but returns an error if the vector cannot be read
+    /// right now.
+    pub fn get_locked(&self, index: usize) -> Result<Option<T>, LockError>
+    where
+        T: Clone,
+    {
+        lock::check_read(Some(self.data.domain()))?;
+        Ok(self.get(index))
+    }
+
+    /// Like `push`, but returns an error (dropping `value`) if the
+    /// vector cannot be modified right now, or (returning `value`) if
+    /// it is at its maximum length and the policy (see `set_max_len`)
+    /// is not `EvictOldest`.
+    pub fn push_locked(&self, value: T) -> Result<(), InsertError<T>> {
+        lock::check_write(Some(self.data.domain()))?;
+        Ok(self.push_or_reject(value)?)
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S>
+where
+    S: MapStore<K, V>,
+{
+    /// Like `get`, but returns an error if the map cannot be read
+    /// right now.
+    pub fn get_locked<Q>(&self, key: &Q) -> Result<Option<V>, LockError>
+    where
+        Q: ?Sized,
+        S: MapLookup<Q, V>,
+        V: Clone,
+    {
+        lock::check_read(Some(self.data.domain()))?;
+        Ok(self.get(key))
+    }
+
+    /// Like `insert`, but returns an error (dropping the entry) if the
+    /// map cannot be modified right now, or (returning the entry) if
+    /// a new key would exceed its maximum length and the policy (see
+    /// `set_max_len`) is not `EvictOldest`.
+    pub fn insert_locked(&self, key: K, value: V) -> Result<Option<V>, InsertError<(K, V)>> {
+        lock::check_write(Some(self.data.domain()))?;
+        Ok(self.insert_or_reject(key, value)?)
+    }
+}
@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+use super::*;
+use crate::error::CapacityError;
+use crate::{LimitPolicy, MutMap};
+
+#[test]
+fn read_locked() {
+    let cell = Mut::new(1);
+    let v = MutVec::from(vec![10]);
+    v.with_element(0, |_| {
+        assert_eq!(cell.get_locked(), Ok(1));
+        assert_eq!(v.get_locked(0), Ok(Some(10)));
+        assert_eq!(cell.set_locked(2), Err(LockError::ReadLocked));
+        assert_eq!(try_modify(|| v.push(11)), Err(LockError::ReadLocked));
+    });
+    assert_eq!(cell.replace_locked(2), Ok(1));
+    assert_eq!(v.len(), 1);
+}
+
+#[test]
+fn write_locked() {
+    let cell = Mut::new(1);
+    let m: MutMap<u32, u32> = MutMap::new();
+    let a = Mut::new(0);
+    let b = Mut::new(0);
+    crate::borrow_mut2(&a, &b, |_, _| {
+        assert_eq!(cell.get_locked(), Err(LockError::WriteLocked));
+        assert_eq!(try_read(|| m.len()), Err(LockError::WriteLocked));
+        assert_eq!(try_modify(|| m.insert(1, 1)), Err(LockError::WriteLocked));
+    });
+    assert_eq!(try_modify(|| m.insert(1, 1)), Ok(None));
+    assert_eq!(
+        LockError::WriteLocked.to_string(),
+        "mutable data is locked for writing"
+    );
+}
+
+#[test]
+fn too_many_readers() {
+    let cell = Mut::new(1);
+    let v = MutVec::from(vec![10]);
+    lock::set_readers(lock::MAX_READERS);
+    assert_eq!(cell.get_locked(), Err(LockError::TooManyReaders));
+    assert_eq!(v.get_locked(0), Err(LockError::TooManyReaders));
+    assert_eq!(try_read(|| v.len()), Err(LockError::TooManyReaders));
+    lock::set_readers(0);
+    assert_eq!(cell.get_locked(), Ok(1));
+}
+
+#[test]
+fn collections() {
+    let v = MutVec::from(vec![10]);
+    let m = MutMap::new();
+    assert_eq!(m.insert_locked("a", 1), Ok(None));
+    v.with_element(0, |_| {
+        assert_eq!(
+            v.push_locked(11),
+            Err(InsertError::Locked(LockError::ReadLocked))
+        );
+        assert_eq!(m.get_locked("a"), Ok(Some(1)));
+        assert_eq!(
+            m.insert_locked("a", 2),
+            Err(InsertError::Locked(LockError::ReadLocked))
+        );
+    });
+    let a = Mut::new(0);
+    let b = Mut::new(0);
+    crate::borrow_mut2(&a, &b, |_, _| {
+        assert_eq!(m.get_locked("a"), Err(LockError::WriteLocked));
+    });
+    assert_eq!(v.push_locked(11), Ok(()));
+    assert_eq!(m.insert_locked("a", 2), Ok(Some(1)));
+    assert_eq!(v.len(), 2);
+    assert_eq!(m.get_locked("z"), Ok(None));
+}
+
+#[test]
+fn full_collections() {
+    let v = MutVec::from(vec![10]);
+    let m = MutMap::new();
+    m.insert("a", 1);
+    for &policy in &[LimitPolicy::Error, LimitPolicy::Panic] {
+        v.set_max_len(1, policy);
+        m.set_max_len(1, policy);
+        assert_eq!(
+            v.push_locked(11),
+            Err(InsertError::Capacity(CapacityError {
+                value: 11,
+                max_len: 1
+            }))
+        );
+        assert_eq!(
+            m.insert_locked("b", 2),
+            Err(InsertError::Capacity(CapacityError {
+                value: ("b", 2),
+                max_len: 1
+            }))
+        );
+        assert_eq!(m.insert_locked("a", 2), Ok(Some(1)));
+        m.insert("a", 1);
+    }
+    v.set_max_len(1, LimitPolicy::EvictOldest);
+    assert_eq!(v.push_locked(11), Ok(()));
+    assert_eq!(v.into_inner(), vec![11]);
+    assert_eq!(m.len(), 1);
+    assert_eq!(
+        InsertError::<u8>::Locked(LockError::ReadLocked).to_string(),
+        "mutable data is locked for reading"
+    );
+}
@@ -0,0 +1,36 @@
+//! Catching panics.
+//!
+//! The cells are `UnwindSafe` and `RefUnwindSafe` whenever their
+//! contents are, so closures that use them can be passed to
+//! `std::panic::catch_unwind` directly: when a panic unwinds through
+//! a borrow or check-out, its guard releases the thread lock, and a
+//! checked-out collection is put back into its cell. What is *not*
+//! undone is whatever a closure passed to the crate (such as the one
+//! given to `MutVec::with_element_mut`) wrote before it panicked.
+
+use crate::error::GuardOutlived;
+use crate::mcell::lock;
+use std::panic::{self, UnwindSafe};
+
+mod test;
+
+/// Runs `f`, catching any panic, like `std::panic::catch_unwind`.
+/// Additionally verifies that the thread lock is back in the state it
+/// was in before `f` ran, so that the thread can go on to use its
+/// cells (e.g., to serve the next request).
+///
+/// If a lock guard created by `f` is still alive when `f` returns or
+/// unwinds (or was leaked with `mem::forget`), the result is dropped
+/// and a `GuardOutlived` error is returned instead. The lock is not
+/// reset: it cannot be released while a guard might still give
+/// access to a cell, so later accesses fail as they would while the
+/// guard is in use.
+pub fn catch_unwind_restore<R>(f: impl FnOnce() -> R + UnwindSafe) -> std::thread::Result<R> {
+    let before = lock::state();
+    let result = panic::catch_unwind(f);
+    if lock::state() == before {
+        return result;
+    }
+    drop(result);
+    Err(Box::new(GuardOutlived))
+}
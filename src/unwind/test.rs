@@ -0,0 +1,59 @@
+#![cfg(test)]
+
+use super::*;
+use crate::{Mut, MutMap, MutSet, MutVec};
+use std::panic::RefUnwindSafe;
+
+fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+
+#[test]
+fn cells_are_unwind_safe() {
+    assert_unwind_safe::<Mut<u32>>();
+    assert_unwind_safe::<MutVec<String>>();
+    assert_unwind_safe::<MutMap<String, MutVec<u8>>>();
+    assert_unwind_safe::<MutSet<u8>>();
+}
+
+#[test]
+fn restores_lock() {
+    let v = MutVec::from(vec![1, 2]);
+    let result = catch_unwind_restore(|| {
+        v.with_element_mut(0, |n| {
+            *n = 10;
+            panic!("oops");
+        })
+    });
+    assert!(result.is_err());
+
+    // The partial write survives, and the thread is usable again.
+    v.push(3);
+    assert_eq!(v.into_inner(), vec![10, 2, 3]);
+}
+
+#[test]
+fn ok() {
+    let m = Mut::new(1);
+    assert_eq!(catch_unwind_restore(|| m.get() + 1).unwrap(), 2);
+}
+
+#[test]
+fn leaked_guard() {
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind_restore(|| std::mem::forget(v.get_ref(0)));
+    assert!(result.unwrap_err().is::<GuardOutlived>());
+
+    // The lock is still held.
+    assert!(v.with_element(0, |_| ()).is_some());
+    assert!(crate::try_modify(|| ()).is_err());
+}
+
+#[test]
+fn returned_guard() {
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind_restore(|| v.get_ref(0));
+    assert!(result.unwrap_err().is::<GuardOutlived>());
+
+    // The guard was dropped with the result.
+    v.push(2);
+    assert_eq!(v.len(), 2);
+}
@@ -0,0 +1,305 @@
+//! `MutValue`, a dynamically typed document (like a JSON value) whose
+//! arrays and objects are mut-cells.
+
+use crate::map::MutMap;
+use crate::vec::MutVec;
+use std::fmt;
+
+mod test;
+
+/// A dynamically typed value. Arrays and objects are a `MutVec` and a
+/// `MutMap`, so a document can be shared and edited in place; paths
+/// such as `"a.b[2]"` address nested values (see `get_path`).
+#[derive(Clone, Debug, Default)]
+pub enum MutValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(MutVec<MutValue>),
+    Object(MutMap<String, MutValue>),
+}
+
+/// The ways in which `set_path` can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// The path could not be parsed.
+    Syntax,
+
+    /// The value at this prefix of the path does not exist, or is not
+    /// an array or object as the path requires.
+    NotFound(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Syntax => write!(f, "malformed path"),
+            PathError::NotFound(prefix) => write!(f, "no array or object at `{}`", prefix),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Segment<'p> {
+    Key(&'p str),
+    Index(usize),
+}
+
+impl MutValue {
+    /// A new, empty array.
+    #[track_caller]
+    pub fn array() -> Self {
+        MutValue::Array(MutVec::new())
+    }
+
+    /// A new, empty object.
+    #[track_caller]
+    pub fn object() -> Self {
+        MutValue::Object(MutMap::new())
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, MutValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            MutValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MutValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MutValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of the value at `path`, if there is one. A path
+    /// is a sequence of object keys separated by `.`, with array
+    /// indices in brackets: `"a.b[2]"` is element 2 of the array at key
+    /// `b` of the object at key `a`. The empty path is `self`.
+    ///
+    /// Note that copying an array or object copies its contents into
+    /// fresh cells.
+    pub fn get_path(&self, path: &str) -> Option<MutValue> {
+        let segments = parse_path(path)?;
+        self.with_path(&segments, Clone::clone)
+    }
+
+    fn with_path<R>(&self, segments: &[Segment<'_>], op: impl FnOnce(&MutValue) -> R) -> Option<R> {
+        let (first, rest) = match segments.split_first() {
+            None => return Some(op(self)),
+            Some(pair) => pair,
+        };
+        match (first, self) {
+            (Segment::Key(key), MutValue::Object(map)) => {
+                map.with_borrowed(|map| map.get(*key)?.with_path(rest, op))
+            }
+            (Segment::Index(index), MutValue::Array(vec)) => {
+                vec.data.borrow().get(*index)?.with_path(rest, op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Stores `value` at `path` (see `get_path`), returning the value
+    /// it replaced, if any. The final segment may name a new key of an
+    /// object, or the index one past the end of an array (which
+    /// pushes); every other segment must already exist, and the path
+    /// may not be empty.
+    ///
+    /// The change is made by checking out `self`, so subscribers to
+    /// its cell are notified; subscribers to the nested cells along the
+    /// path are not.
+    pub fn set_path(&self, path: &str, value: MutValue) -> Result<Option<MutValue>, PathError> {
+        let segments = parse_path(path).ok_or(PathError::Syntax)?;
+        if segments.is_empty() {
+            return Err(PathError::Syntax);
+        }
+        self.set_segments(path, &segments, 0, value)
+    }
+
+    fn set_segments(
+        &self,
+        path: &str,
+        segments: &[Segment<'_>],
+        depth: usize,
+        value: MutValue,
+    ) -> Result<Option<MutValue>, PathError> {
+        match self {
+            MutValue::Object(map) => map.with_checked_out(|map| {
+                set_in(path, segments, depth, value, |segment| match segment {
+                    Segment::Key(key) => Some(Slot::Entry(map, key)),
+                    Segment::Index(_) => None,
+                })
+            }),
+            MutValue::Array(vec) => {
+                let mut vec = vec.data.check_out();
+                set_in(path, segments, depth, value, |segment| match segment {
+                    Segment::Index(index) => Some(Slot::Element(&mut vec, index)),
+                    Segment::Key(_) => None,
+                })
+            }
+            _ => Err(not_found(path, segments, depth)),
+        }
+    }
+
+    /// Like `set_segments`, but with exclusive access to `self`, so
+    /// that nested cells can be modified without locking.
+    fn set_segments_mut(
+        &mut self,
+        path: &str,
+        segments: &[Segment<'_>],
+        depth: usize,
+        value: MutValue,
+    ) -> Result<Option<MutValue>, PathError> {
+        match self {
+            MutValue::Object(map) => {
+                let map = map.data.get_mut();
+                set_in(path, segments, depth, value, |segment| match segment {
+                    Segment::Key(key) => Some(Slot::Entry(map, key)),
+                    Segment::Index(_) => None,
+                })
+            }
+            MutValue::Array(vec) => {
+                let vec = vec.data.get_mut();
+                set_in(path, segments, depth, value, |segment| match segment {
+                    Segment::Index(index) => Some(Slot::Element(vec, index)),
+                    Segment::Key(_) => None,
+                })
+            }
+            _ => Err(not_found(path, segments, depth)),
+        }
+    }
+}
+
+/// The place within an array or object named by one path segment.
+enum Slot<'a, 'p> {
+    Entry(&'a mut indexmap::IndexMap<String, MutValue>, &'p str),
+    Element(&'a mut Vec<MutValue>, usize),
+}
+
+/// Stores `value` at `segments[depth..]`, relative to the container
+/// that `slot` looks into.
+fn set_in<'a, 'p>(
+    path: &str,
+    segments: &[Segment<'p>],
+    depth: usize,
+    value: MutValue,
+    slot: impl FnOnce(Segment<'p>) -> Option<Slot<'a, 'p>>,
+) -> Result<Option<MutValue>, PathError> {
+    let last = depth + 1 == segments.len();
+    let slot = slot(segments[depth]).ok_or_else(|| not_found(path, segments, depth))?;
+    match slot {
+        Slot::Entry(map, key) if last => Ok(map.insert(key.to_string(), value)),
+        Slot::Element(vec, index) if last && index == vec.len() => {
+            vec.push(value);
+            Ok(None)
+        }
+        Slot::Entry(map, key) => match map.get_mut(key) {
+            Some(child) => child.set_segments_mut(path, segments, depth + 1, value),
+            None => Err(not_found(path, segments, depth + 1)),
+        },
+        Slot::Element(vec, index) => match vec.get_mut(index) {
+            Some(child) if last => Ok(Some(std::mem::replace(child, value))),
+            Some(child) => child.set_segments_mut(path, segments, depth + 1, value),
+            None => Err(not_found(path, segments, depth + 1)),
+        },
+    }
+}
+
+/// The error for the prefix `segments[..depth]` of `path`.
+fn not_found(path: &str, segments: &[Segment<'_>], depth: usize) -> PathError {
+    let mut prefix = String::new();
+    for segment in &segments[..depth] {
+        match segment {
+            Segment::Key(key) if prefix.is_empty() => prefix.push_str(key),
+            Segment::Key(key) => {
+                prefix.push('.');
+                prefix.push_str(key);
+            }
+            Segment::Index(index) => prefix.push_str(&format!("[{}]", index)),
+        }
+    }
+    debug_assert!(path.starts_with(&prefix));
+    PathError::NotFound(prefix)
+}
+
+fn parse_path(path: &str) -> Option<Vec<Segment<'_>>> {
+    let mut segments = vec![];
+    if path.is_empty() {
+        return Some(segments);
+    }
+    for (i, part) in path.split('.').enumerate() {
+        let (key, mut indices) = match part.find('[') {
+            Some(bracket) => part.split_at(bracket),
+            None => (part, ""),
+        };
+        if key.is_empty() && i > 0 {
+            return None;
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+        while !indices.is_empty() {
+            let close = indices.find(']')?;
+            let index = indices[1..close].parse().ok()?;
+            segments.push(Segment::Index(index));
+            indices = &indices[close + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return None;
+            }
+        }
+    }
+    Some(segments)
+}
+
+impl From<bool> for MutValue {
+    fn from(b: bool) -> Self {
+        MutValue::Bool(b)
+    }
+}
+
+impl From<f64> for MutValue {
+    fn from(n: f64) -> Self {
+        MutValue::Number(n)
+    }
+}
+
+impl From<i32> for MutValue {
+    fn from(n: i32) -> Self {
+        MutValue::Number(n.into())
+    }
+}
+
+impl From<&str> for MutValue {
+    fn from(s: &str) -> Self {
+        MutValue::String(s.to_string())
+    }
+}
+
+impl From<String> for MutValue {
+    fn from(s: String) -> Self {
+        MutValue::String(s)
+    }
+}
+
+impl From<Vec<MutValue>> for MutValue {
+    #[track_caller]
+    fn from(v: Vec<MutValue>) -> Self {
+        MutValue::Array(MutVec::from(v))
+    }
+}
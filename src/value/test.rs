@@ -0,0 +1,67 @@
+#![cfg(test)]
+
+use super::*;
+
+fn doc() -> MutValue {
+    let b = MutValue::from(vec![1.into(), 2.into(), 3.into()]);
+    let a = MutValue::object();
+    a.set_path("b", b).unwrap();
+    let root = MutValue::object();
+    root.set_path("a", a).unwrap();
+    root.set_path("name", "doc".into()).unwrap();
+    root
+}
+
+#[test]
+fn get_path() {
+    let root = doc();
+    assert_eq!(root.get_path("a.b[2]").unwrap().as_f64(), Some(3.0));
+    assert_eq!(root.get_path("name").unwrap().as_str(), Some("doc"));
+    assert!(root.get_path("a.b[3]").is_none());
+    assert!(root.get_path("a.c").is_none());
+    assert!(root.get_path("name[0]").is_none());
+    assert!(root.get_path("a..b").is_none());
+}
+
+#[test]
+fn set_path() {
+    let root = doc();
+    let old = root.set_path("a.b[0]", true.into()).unwrap();
+    assert_eq!(old.unwrap().as_f64(), Some(1.0));
+    assert_eq!(root.get_path("a.b[0]").unwrap().as_bool(), Some(true));
+
+    // One past the end pushes; new keys are inserted.
+    assert!(root.set_path("a.b[3]", 4.into()).unwrap().is_none());
+    assert!(root.set_path("a.c", MutValue::Null).unwrap().is_none());
+    assert_eq!(root.get_path("a.b[3]").unwrap().as_f64(), Some(4.0));
+    assert!(root.get_path("a.c").unwrap().is_null());
+
+    assert_eq!(
+        root.set_path("a.x.y", 1.into()).unwrap_err(),
+        PathError::NotFound("a.x".to_string())
+    );
+    assert_eq!(
+        root.set_path("a.b[9]", 1.into()).unwrap_err(),
+        PathError::NotFound("a.b[9]".to_string())
+    );
+    assert_eq!(root.set_path("", 1.into()).unwrap_err(), PathError::Syntax);
+    assert_eq!(
+        root.set_path("a[", 1.into()).unwrap_err(),
+        PathError::Syntax
+    );
+}
+
+#[test]
+fn set_path_notifies_root() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let root = doc();
+    let count = Rc::new(Cell::new(0));
+    if let MutValue::Object(map) = &root {
+        let count = count.clone();
+        map.subscribe(move || count.set(count.get() + 1));
+    }
+    root.set_path("a.b[1]", MutValue::Null).unwrap();
+    assert_eq!(count.get(), 1);
+}
@@ -1,13 +1,27 @@
+use crate::diff::{self, VecDiff};
+use crate::error::{CapacityError, IndexError};
+use crate::guard::ElementGuard;
+use crate::iter_check::{IterMode, ModeState, MutationCheck};
+use crate::limit::{self, Limit, LimitPolicy};
 use crate::mcell::MCell;
+use crate::memory::MemoryUsage;
+use crate::observe;
+use crate::observe::hooks;
+use crate::observe::SubscriptionId;
+use crate::pretty::Pretty;
+use std::fmt;
+use std::ops::Range;
 use std::vec::Vec;
 
 mod test;
 
 pub struct MutVec<T> {
-    data: MCell<Vec<T>>,
+    pub(crate) data: MCell<Vec<T>>,
+    limit: Limit<()>,
 }
 
 impl<T> MutVec<T> {
+    #[track_caller]
     pub fn new() -> Self {
         Self::default()
     }
@@ -16,6 +30,10 @@ impl<T> MutVec<T> {
         self.data.borrow().len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
     /// The equivalent of `self[index]` -- load the element at the
     /// given index, panicking if there is no such element.
     pub fn at(&self, index: usize) -> T
@@ -35,16 +53,221 @@ impl<T> MutVec<T> {
         Some(data.get(index)?.clone())
     }
 
-    /// Push `value` onto the end of the vector.
+    /// Like `at`, but returns an error (rather than panicking) if
+    /// `index` is out of bounds.
+    pub fn try_at(&self, index: usize) -> Result<T, IndexError>
+    where
+        T: Clone,
+    {
+        let data = self.data.borrow();
+        match data.get(index) {
+            Some(value) => Ok(value.clone()),
+            None => Err(IndexError {
+                index,
+                len: data.len(),
+            }),
+        }
+    }
+
+    /// Get a reference to the element at the given `index`, returning
+    /// `None` if it is out of bounds. All mut-cells are read-only
+    /// while the guard exists.
+    #[track_caller]
+    pub fn get_ref(&self, index: usize) -> Option<ElementGuard<'_, T>> {
+        let guard = self.data.borrow().filter_map(|data| data.get(index))?;
+        Some(ElementGuard::new(guard))
+    }
+
+    /// Runs `f` with a reference to the element at `index`, returning
+    /// `None` if it is out of bounds. Unlike `get`, this does not need
+    /// `T: Clone`. All mut-cells are read-only while `f` runs.
+    #[track_caller]
+    pub fn with_element<R>(&self, index: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        Some(f(self.data.borrow().get(index)?))
+    }
+
+    /// Runs `f` with mutable access to the element at `index`,
+    /// returning `None` if it is out of bounds. While `f` runs, all
+    /// mut-cells are locked and read-only; attempts to read from
+    /// *this* vector will find it empty.
+    #[track_caller]
+    pub fn with_element_mut<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        if index >= self.len() {
+            return None;
+        }
+        let result = f(&mut self.data.check_out()[index]);
+        self.changed(ListChange::Updated(index));
+        Some(result)
+    }
+
+    /// Push `value` onto the end of the vector. Panics if this would
+    /// exceed the maximum length and the policy is not
+    /// `EvictOldest`; see `set_max_len`.
     pub fn push(&self, value: T) {
-        let mut data = self.data.borrow_mut();
-        data.push(value);
+        if let Err(err) = self.try_push(value) {
+            limit::exceeded(err.max_len)
+        }
+    }
+
+    /// Like `push`, but if this would exceed the maximum length and
+    /// the policy is `LimitPolicy::Error`, returns `value` in an
+    /// error instead of panicking.
+    pub fn try_push(&self, value: T) -> Result<(), CapacityError<T>> {
+        self.push_or_reject(value)
+            .map_err(|err| match self.limit.get() {
+                Some((_, LimitPolicy::Panic, ())) => limit::exceeded(err.max_len),
+                _ => err,
+            })
+    }
+
+    /// Like `try_push`, but returns an error under the `Panic` policy
+    /// as well (for `push_locked`).
+    pub(crate) fn push_or_reject(&self, value: T) -> Result<(), CapacityError<T>> {
+        let evict = match self.limit.get() {
+            Some((max_len, policy, ())) if self.len() >= max_len => match policy {
+                LimitPolicy::EvictOldest => true,
+                LimitPolicy::Error | LimitPolicy::Panic => {
+                    return Err(CapacityError { value, max_len })
+                }
+            },
+            _ => false,
+        };
+        // Unsafe proof obligation: the elements of a vector are
+        // `(T, ())` (see the `hooks` module).
+        let staged = unsafe { hooks::stage_push(self.data.existing_id(), &value, &()) };
+        let (evicted, index) = {
+            let mut data = self.data.borrow_mut();
+            let evicted = if evict { Some(data.remove(0)) } else { None };
+            data.push(value);
+            (evicted, data.len() - 1)
+        };
+        if let Some(evicted) = &evicted {
+            self.changed(ListChange::Removed(0));
+            self.removed(evicted);
+        }
+        self.changed(ListChange::Inserted(index));
+        staged.fire();
+        Ok(())
+    }
+
+    /// Limit the vector to `max_len` elements, handling a `push` that
+    /// would exceed it according to `policy`. (`EvictOldest` removes
+    /// the first element, which takes time proportional to the
+    /// length; see `MutRingBuffer` for a fixed-capacity buffer.)
+    /// Only `push` and `try_push` are limited: operations that replace
+    /// the contents wholesale are not, and lowering the limit does not
+    /// remove any elements. Clones of the vector are not limited.
+    pub fn set_max_len(&self, max_len: usize, policy: LimitPolicy) {
+        self.limit.set(max_len, policy, ());
+    }
+
+    /// Remove the limit set with `set_max_len`.
+    pub fn clear_max_len(&self) {
+        self.limit.clear();
+    }
+
+    /// The limit set with `set_max_len`, if any.
+    pub fn max_len(&self) -> Option<usize> {
+        self.limit.max_len()
     }
 
     /// Pop a value from the end of the vector, if any.
     pub fn pop(&self) -> Option<T> {
-        let mut data = self.data.borrow_mut();
-        data.pop()
+        let (value, index) = {
+            let mut data = self.data.borrow_mut();
+            (data.pop(), data.len())
+        };
+        if let Some(value) = &value {
+            self.changed(ListChange::Removed(index));
+            self.removed(value);
+        }
+        value
+    }
+
+    /// Removes the elements whose indices fall within `range`, in one
+    /// operation, and returns them. Panics if the range is out of
+    /// bounds. During the removal, all mut-cells are locked and
+    /// read-only; attempts to read from *this* vector will find it
+    /// empty.
+    pub fn remove_range(&self, range: Range<usize>) -> Vec<T> {
+        let removed: Vec<T> = self.data.check_out().drain(range.clone()).collect();
+        // Reported last to first, so that each index is valid when
+        // the changes are applied in order.
+        for index in range.rev() {
+            self.changed(ListChange::Removed(index));
+        }
+        self.removed_all(&removed);
+        removed
+    }
+
+    /// Store `value` at `index`, returning the old value. Panics if
+    /// `index` is out of bounds.
+    pub fn replace_at(&self, index: usize, value: T) -> T {
+        // Unsafe proof obligation: as in `push_or_reject`. (If `index`
+        // is out of bounds, the staged element is discarded as we
+        // unwind.)
+        let staged = unsafe { hooks::stage_insert(self.data.existing_id(), &value, &()) };
+        let old = {
+            let mut data = self.data.borrow_mut();
+            std::mem::replace(&mut data[index], value)
+        };
+        self.changed(ListChange::Updated(index));
+        self.removed(&old);
+        staged.fire();
+        old
+    }
+
+    /// The footprint of the vector.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_vec(&self.data.borrow())
+    }
+
+    /// Compute the changes that turn `older` (typically an earlier
+    /// snapshot of this vector) into the current contents, comparing
+    /// position by position. The comparison happens under a single
+    /// read borrow.
+    pub fn diff(&self, older: &[T]) -> VecDiff<T>
+    where
+        T: PartialEq + Clone,
+    {
+        diff::diff_slices(&self.data.borrow(), older)
+    }
+
+    /// The smallest element, if any; ties go to the first. The
+    /// comparison happens under a single read borrow, and only the
+    /// result is cloned.
+    pub fn min(&self) -> Option<T>
+    where
+        T: Ord + Clone,
+    {
+        self.data.borrow().iter().min().cloned()
+    }
+
+    /// The largest element, if any; ties go to the last.
+    pub fn max(&self) -> Option<T>
+    where
+        T: Ord + Clone,
+    {
+        self.data.borrow().iter().max().cloned()
+    }
+
+    /// The element for which `key` is smallest, if any. All
+    /// mut-cells are read-only while `key` runs.
+    #[track_caller]
+    pub fn min_by_key<K: Ord>(&self, mut key: impl FnMut(&T) -> K) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().iter().min_by_key(|t| key(t)).cloned()
+    }
+
+    /// The element for which `key` is largest, if any.
+    #[track_caller]
+    pub fn max_by_key<K: Ord>(&self, mut key: impl FnMut(&T) -> K) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.data.borrow().iter().max_by_key(|t| key(t)).cloned()
     }
 
     /// Iterate over the elements in `self`, cloning them as we go.
@@ -53,36 +276,274 @@ impl<T> MutVec<T> {
     /// iteration (for example, by pushing or popping elements onto
     /// it). Doing so may lead to surprising results but is not
     /// undefined behavior in any way.
+    ///
+    /// In debug builds, such a mutation logs a warning.
+    #[track_caller]
     pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        self.iter_with(IterMode::Live)
+    }
+
+    /// Like `iter`, but with `mode` determining what happens if `self`
+    /// is mutated during the iteration.
+    #[track_caller]
+    pub fn iter_with(&self, mode: IterMode) -> Iter<'_, T>
     where
         T: Clone,
     {
         Iter {
             vec: self,
             index: 0,
+            check: MutationCheck::new(&self.data),
+            mode: ModeState::new(mode, &self.data, || self.data.borrow().clone()),
+        }
+    }
+
+    /// Iterate over the elements in `self`, yielding the result of
+    /// applying `f` to each rather than a clone, so that `T` need not
+    /// be `Clone` (and a caller can extract just the part of a large
+    /// element it needs). `f` runs under a read borrow, so all
+    /// mut-cells are read-only while it runs. As with `iter`, `self`
+    /// may be mutated between steps.
+    #[track_caller]
+    pub fn iter_map<R, F>(&self, f: F) -> IterMap<'_, T, F>
+    where
+        F: FnMut(&T) -> R,
+    {
+        IterMap {
+            vec: self,
+            index: 0,
+            f,
+            check: MutationCheck::new(&self.data),
         }
     }
 
+    /// Iterate over pairs of elements of `self` and `other`, cloning
+    /// them as we go; the iteration stops at the end of the shorter
+    /// vector. Each pair is read under a single borrow of both
+    /// vectors, so it always reflects one state of the two (as with
+    /// `iter`, they may be mutated between steps).
+    pub fn zip<'a, U>(&'a self, other: &'a MutVec<U>) -> Zip<'a, T, U>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        Zip {
+            a: self,
+            b: other,
+            index: 0,
+        }
+    }
+
+    /// Runs `op` with shared access to the contents of both `self`
+    /// and `other`, for elements that cannot be cloned. All mut-cells
+    /// are read-only while `op` runs.
+    #[track_caller]
+    pub fn with_refs2<U, R>(&self, other: &MutVec<U>, op: impl FnOnce(&[T], &[U]) -> R) -> R {
+        let (a, b) = (self.data.borrow(), other.data.borrow());
+        op(&a, &b)
+    }
+
     /// Take ownership of our internal vector, replacing it with `v`.
     pub fn replace(&self, v: Vec<T>) -> Vec<T> {
-        self.data.replace(v)
+        // Staged last to first, as `Staged::fire` takes the most
+        // recently staged element. Unsafe proof obligation: as in
+        // `push_or_reject`.
+        let id = self.data.existing_id();
+        let staged = unsafe { hooks::stage_inserts(id, v.iter().rev().map(|t| (t, &()))) };
+        let old = self.data.replace(v);
+        self.changed(ListChange::Reset);
+        self.removed_all(&old);
+        staged.fire();
+        old
     }
 
     /// Take ownership of our internal vector, replacing it with an
     /// empty one.
     pub fn take(&self) -> Vec<T> {
-        self.data.take()
+        let old = self.data.take();
+        self.changed(ListChange::Reset);
+        self.removed_all(&old);
+        old
+    }
+
+    /// Take the elements out of the vector (leaving it empty), split
+    /// into those for which `pred` returns true and the rest, each in
+    /// their original order. While `pred` runs, all mut-cells are
+    /// locked and read-only.
+    #[track_caller]
+    pub fn partition(&self, mut pred: impl FnMut(&T) -> bool) -> (Vec<T>, Vec<T>) {
+        let result: (Vec<T>, Vec<T>) = {
+            let mut data = self.data.check_out();
+            data.drain(..).partition(|t| pred(t))
+        };
+        self.changed(ListChange::Reset);
+        self.removed_all(&result.0);
+        self.removed_all(&result.1);
+        result
+    }
+
+    /// Reorder the vector so that the elements for which `pred`
+    /// returns true come first, returning how many there are. The
+    /// order within each group is preserved. While `pred` runs, all
+    /// mut-cells are locked and read-only; attempts to read from
+    /// *this* vector will find it empty. `pred` is invoked with every
+    /// element before any is moved, so if it panics, the vector is
+    /// left unchanged.
+    #[track_caller]
+    pub fn partition_in_place(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        let split = {
+            let mut data = self.data.check_out();
+            let mut split = 0;
+            // `sort_by_cached_key` is stable and computes all the
+            // keys up front, once per element.
+            data.sort_by_cached_key(|t| {
+                let matches = pred(t);
+                split += matches as usize;
+                !matches
+            });
+            split
+        };
+        self.changed(ListChange::Reset);
+        split
+    }
+
+    /// Make `self` a copy of `other`, reusing the storage of `self`
+    /// (as `Vec::clone_from` does) rather than allocating anew. While
+    /// the elements are cloned, all mut-cells are locked and
+    /// read-only; attempts to read from *this* vector will find it
+    /// empty. This is reported as a `ListChange::Reset`; the element
+    /// hooks are not invoked.
+    pub fn copy_from(&self, other: &MutVec<T>)
+    where
+        T: Clone,
+    {
+        if std::ptr::eq(self, other) {
+            return;
+        }
+        self.data.check_out().clone_from(&other.data.borrow());
+        self.changed(ListChange::Reset);
+    }
+
+    /// Take ownership of our internal vector, consuming `self`.
+    pub fn into_inner(self) -> Vec<T> {
+        self.data.into_inner()
+    }
+
+    /// An adapter that displays the vector with one element per
+    /// line.
+    pub fn pretty(&self) -> Pretty<'_, Self> {
+        Pretty::new(self)
+    }
+
+    /// Register `callback` to be invoked after each mutation of
+    /// `self`. See the `observe` module for details.
+    pub fn subscribe(&self, callback: impl Fn() + 'static) -> SubscriptionId {
+        observe::subscribe(self.data.id(), callback)
+    }
+
+    /// Register `callback` to be invoked with a description of each
+    /// change to `self`, after the callbacks registered with
+    /// `subscribe`. Remove it with `unsubscribe`.
+    pub fn subscribe_changes(&self, callback: impl Fn(&ListChange) + 'static) -> SubscriptionId {
+        observe::subscribe_events(self.data.id(), callback)
+    }
+
+    /// Register `hook` to be invoked with each element added to
+    /// `self` (by `push`, `replace_at`, or `replace`), once the
+    /// operation is complete and after the callbacks registered with
+    /// `subscribe_changes`. The hook receives a clone of the element,
+    /// taken just before it was added. Remove it with `unsubscribe`.
+    pub fn on_insert(&self, hook: impl Fn(&T) + 'static) -> SubscriptionId
+    where
+        T: Clone + 'static,
+    {
+        observe::hooks::on_insert(self.data.id(), move |t: &T, _: &()| hook(t))
+    }
+
+    /// Register `hook` to be invoked with each element pushed onto
+    /// `self` (by `push` or `try_push`), like an `on_insert` hook
+    /// (and in the same order as those). Remove it with
+    /// `unsubscribe`.
+    pub fn on_push(&self, hook: impl Fn(&T) + 'static) -> SubscriptionId
+    where
+        T: Clone + 'static,
+    {
+        observe::hooks::on_push(self.data.id(), move |t: &T, _: &()| hook(t))
+    }
+
+    /// Register `hook` to be invoked with each element removed from
+    /// `self` (by `pop`, `remove_range`, `replace_at`, `replace`,
+    /// `take`, or `partition`), once the operation is complete and
+    /// after the callbacks registered with `subscribe_changes`.
+    /// Elements modified in place (e.g., with `with_element_mut`)
+    /// are not reported. Remove it with `unsubscribe`.
+    pub fn on_remove(&self, hook: impl Fn(&T) + 'static) -> SubscriptionId {
+        observe::hooks::on_remove(self.data.id(), move |t: &T, _: &()| hook(t))
+    }
+
+    /// Remove a callback registered with `subscribe`,
+    /// `subscribe_changes`, `on_insert`, `on_push`, or `on_remove`,
+    /// returning false if it was not registered.
+    pub fn unsubscribe(&self, subscription: SubscriptionId) -> bool {
+        observe::unsubscribe(self.data.id(), subscription)
+    }
+
+    pub(crate) fn changed(&self, change: ListChange) {
+        if let Some(id) = self.data.existing_id() {
+            observe::notify_event(id, &change);
+        }
+    }
+
+    /// Report `value`, just removed, to the `on_remove` hooks.
+    fn removed(&self, value: &T) {
+        // Unsafe proof obligation: as in `push_or_reject`.
+        unsafe { hooks::fire_remove(self.data.existing_id(), value, &()) }
+    }
+
+    fn removed_all(&self, values: &[T]) {
+        let id = self.data.existing_id();
+        if hooks::any(id) {
+            // Unsafe proof obligation: as in `push_or_reject`.
+            values
+                .iter()
+                .for_each(|t| unsafe { hooks::fire_remove(id, t, &()) });
+        }
     }
 }
 
+/// A change to a `MutVec`, as reported to callbacks registered with
+/// `subscribe_changes`. Indices refer to the vector after the change
+/// (for `Removed`, the index the element had).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ListChange {
+    /// An element was inserted at the given index.
+    Inserted(usize),
+
+    /// The element at the given index was removed.
+    Removed(usize),
+
+    /// The element at the given index was replaced.
+    Updated(usize),
+
+    /// The contents were replaced wholesale.
+    Reset,
+}
+
 impl<T: Clone> Clone for MutVec<T> {
     fn clone(&self) -> Self {
-        let vec = self.data.borrow().clone();
-        MutVec::from(vec.clone())
+        MutVec::from(self.data.borrow().clone())
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.copy_from(source);
     }
 }
 
 impl<A> std::iter::FromIterator<A> for MutVec<A> {
+    #[track_caller]
     fn from_iter<T>(iter: T) -> Self
     where
         T: IntoIterator<Item = A>,
@@ -93,15 +554,39 @@ impl<A> std::iter::FromIterator<A> for MutVec<A> {
 }
 
 impl<T> Default for MutVec<T> {
+    #[track_caller]
     fn default() -> Self {
         Self::from(Vec::new())
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for MutVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.borrow().iter()).finish()
+    }
+}
+
+/// Displays as `[a, b, c]`. See also `pretty`.
+impl<T: fmt::Display> fmt::Display for MutVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        write!(f, "[")?;
+        for (i, value) in data.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, "]")
+    }
+}
+
 impl<T> From<Vec<T>> for MutVec<T> {
+    #[track_caller]
     fn from(v: Vec<T>) -> MutVec<T> {
         MutVec {
-            data: MCell::new(v),
+            data: MCell::new(v).sized(MemoryUsage::of_vec),
+            limit: Limit::new(),
         }
     }
 }
@@ -112,6 +597,8 @@ where
 {
     vec: &'iter MutVec<T>,
     index: usize,
+    check: MutationCheck,
+    mode: ModeState<T>,
 }
 
 impl<'iter, T> Iterator for Iter<'iter, T>
@@ -121,8 +608,79 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
+        if let Some(snapshot) = self.mode.snapshot(&self.vec.data) {
+            return snapshot.next();
+        }
+        self.check.check();
         let value = self.vec.get(self.index)?;
         self.index += 1;
         Some(value)
     }
 }
+
+pub struct IterMap<'iter, T, F> {
+    vec: &'iter MutVec<T>,
+    index: usize,
+    f: F,
+    check: MutationCheck,
+}
+
+impl<'iter, T, R, F> Iterator for IterMap<'iter, T, F>
+where
+    F: FnMut(&T) -> R,
+{
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        self.check.check();
+        let data = self.vec.data.borrow();
+        let value = (self.f)(data.get(self.index)?);
+        self.index += 1;
+        Some(value)
+    }
+}
+
+pub struct Zip<'iter, T, U>
+where
+    T: Clone,
+    U: Clone,
+{
+    a: &'iter MutVec<T>,
+    b: &'iter MutVec<U>,
+    index: usize,
+}
+
+impl<'iter, T, U> Iterator for Zip<'iter, T, U>
+where
+    T: Clone,
+    U: Clone,
+{
+    type Item = (T, U);
+
+    fn next(&mut self) -> Option<(T, U)> {
+        let index = self.index;
+        let pair = self.a.with_refs2(self.b, |a, b| {
+            Some((a.get(index)?.clone(), b.get(index)?.clone()))
+        })?;
+        self.index += 1;
+        Some(pair)
+    }
+}
+
+/// Creates a `MutVec` holding the given elements, like `vec!`:
+///
+/// ```
+/// # use mutable::mutvec;
+/// let v = mutvec![1, 2, 3];
+/// assert_eq!(v.into_inner(), vec![1, 2, 3]);
+/// assert_eq!(mutvec!['x'; 2].into_inner(), vec!['x', 'x']);
+/// ```
+#[macro_export]
+macro_rules! mutvec {
+    ($($element:expr),* $(,)?) => {
+        $crate::MutVec::from(::std::vec![$($element),*])
+    };
+    ($element:expr; $n:expr) => {
+        $crate::MutVec::from(::std::vec![$element; $n])
+    };
+}
@@ -20,3 +20,332 @@ fn iter1() {
 
     assert_eq!(results, vec![Some(22), Some(66), Some(44), Some(44)],);
 }
+
+#[test]
+fn list_changes() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let v = MutVec::new();
+    let changes = Rc::new(RefCell::new(vec![]));
+    let c = changes.clone();
+    let sub = v.subscribe_changes(move |change| c.borrow_mut().push(*change));
+
+    v.push(1);
+    v.push(2);
+    assert_eq!(v.replace_at(0, 3), 1);
+    v.pop();
+    v.pop();
+    v.pop();
+    v.replace(vec![4]);
+
+    assert_eq!(
+        *changes.borrow(),
+        vec![
+            ListChange::Inserted(0),
+            ListChange::Inserted(1),
+            ListChange::Updated(0),
+            ListChange::Removed(1),
+            ListChange::Removed(0),
+            ListChange::Reset,
+        ]
+    );
+
+    assert!(v.unsubscribe(sub));
+    v.push(5);
+    assert_eq!(changes.borrow().len(), 6);
+}
+
+#[test]
+fn zip() {
+    let xs = MutVec::from(vec![1, 2, 3]);
+    let names = MutVec::from(vec!["a", "b"]);
+    assert_eq!(xs.zip(&names).collect::<Vec<_>>(), vec![(1, "a"), (2, "b")]);
+
+    // Mutating during the iteration is allowed.
+    let mut pairs = vec![];
+    for (x, y) in xs.zip(&xs) {
+        pairs.push((x, y));
+        if x == 1 {
+            xs.pop();
+        }
+    }
+    assert_eq!(pairs, vec![(1, 1), (2, 2)]);
+}
+
+#[test]
+fn with_refs2() {
+    let xs = MutVec::from(vec![String::from("a"), String::from("b")]);
+    let ys = MutVec::from(vec![String::from("c")]);
+    let joined = xs.with_refs2(&ys, |xs, ys| format!("{}{}", xs.concat(), ys.concat()));
+    assert_eq!(joined, "abc");
+}
+
+#[test]
+fn with_element() {
+    // Neither `Clone` nor `Default`.
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Payload(u32);
+
+    let v = MutVec::from(vec![Payload(1), Payload(2)]);
+    assert_eq!(v.with_element(1, |p| p.0), Some(2));
+    assert_eq!(v.with_element(2, |p| p.0), None);
+
+    let changes = Rc::new(RefCell::new(vec![]));
+    let c = changes.clone();
+    v.subscribe_changes(move |change| c.borrow_mut().push(*change));
+
+    assert_eq!(
+        v.with_element_mut(0, |p| {
+            // The vector is checked out, so reads find it empty.
+            assert!(v.is_empty());
+            p.0 += 10;
+            p.0
+        }),
+        Some(11)
+    );
+    assert_eq!(v.with_element_mut(5, |p| p.0), None);
+    assert_eq!(v.with_element(0, |p| p.0), Some(11));
+    assert_eq!(*changes.borrow(), vec![ListChange::Updated(0)]);
+}
+
+#[test]
+fn min_max() {
+    let scores = MutVec::from(vec![("b", 3), ("a", 7), ("c", 3)]);
+    assert_eq!(scores.min(), Some(("a", 7)));
+    assert_eq!(scores.max(), Some(("c", 3)));
+    assert_eq!(scores.min_by_key(|s| s.1), Some(("b", 3)));
+    assert_eq!(scores.max_by_key(|s| s.1), Some(("a", 7)));
+    assert_eq!(MutVec::<u8>::new().max(), None);
+}
+
+#[test]
+fn partition() {
+    let v = MutVec::from(vec![1, 2, 3, 4, 5]);
+    assert_eq!(v.partition_in_place(|n| n % 2 == 0), 2);
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![2, 4, 1, 3, 5]);
+
+    let (even, odd) = v.partition(|n| n % 2 == 0);
+    assert_eq!(even, vec![2, 4]);
+    assert_eq!(odd, vec![1, 3, 5]);
+    assert!(v.is_empty());
+}
+
+#[test]
+fn partition_in_place_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let v = MutVec::from(vec![1, 2, 3, 4, 5]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        v.partition_in_place(|&n| if n == 4 { panic!("no") } else { n % 2 == 0 })
+    }));
+    assert!(result.is_err());
+    assert_eq!(v.into_inner(), vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn remove_range() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let v: MutVec<u32> = (0..6).collect();
+    let changes = Rc::new(RefCell::new(vec![]));
+    let c = changes.clone();
+    v.subscribe_changes(move |change| c.borrow_mut().push(*change));
+
+    assert_eq!(v.remove_range(1..3), vec![1, 2]);
+    assert_eq!(v.iter().collect::<Vec<_>>(), vec![0, 3, 4, 5]);
+    assert_eq!(
+        *changes.borrow(),
+        vec![ListChange::Removed(2), ListChange::Removed(1)]
+    );
+    assert!(v.remove_range(4..4).is_empty());
+}
+
+#[test]
+fn element_hooks() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let v = MutVec::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let l = log.clone();
+    v.on_insert(move |x: &u32| l.borrow_mut().push(format!("+{}", x)));
+    let l = log.clone();
+    let sub = v.on_remove(move |x: &u32| l.borrow_mut().push(format!("-{}", x)));
+
+    v.push(1);
+    v.push(2);
+    v.replace_at(0, 3);
+    v.pop();
+    v.replace(vec![4, 5]);
+    v.take();
+    assert_eq!(
+        *log.borrow(),
+        vec!["+1", "+2", "-1", "+3", "-2", "-3", "+4", "+5", "-4", "-5"]
+    );
+
+    assert!(v.unsubscribe(sub));
+    v.push(6);
+    v.pop();
+    assert_eq!(log.borrow().last().unwrap(), "+6");
+}
+
+#[test]
+fn push_hooks() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let v = MutVec::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let l = log.clone();
+    v.on_insert(move |x: &u32| l.borrow_mut().push(format!("+{}", x)));
+    let l = log.clone();
+    let sub = v.on_push(move |x: &u32| l.borrow_mut().push(format!("push {}", x)));
+
+    v.push(1);
+    v.replace_at(0, 2);
+    v.replace(vec![3]);
+    assert_eq!(v.try_push(4), Ok(()));
+    assert_eq!(
+        *log.borrow(),
+        vec!["+1", "push 1", "+2", "+3", "+4", "push 4"]
+    );
+
+    assert!(v.unsubscribe(sub));
+    v.push(5);
+    assert_eq!(log.borrow().last().unwrap(), "+5");
+}
+
+#[test]
+fn element_hooks_after_a_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    let v = MutVec::new();
+    v.on_insert(|_: &Rc<u32>| {});
+    v.on_push(|_: &Rc<u32>| {});
+    let x = Rc::new(1);
+    v.push(x.clone());
+
+    // The insertions panic after their elements were staged: the
+    // staged clones must not linger.
+    let result = catch_unwind(AssertUnwindSafe(|| v.replace_at(5, x.clone())));
+    assert!(result.is_err());
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        v.with_element(0, |_| v.push(x.clone()))
+    }));
+    assert!(result.is_err());
+    assert_eq!(Rc::strong_count(&x), 2);
+}
+
+#[test]
+fn element_hooks_run_unlocked() {
+    use std::rc::Rc;
+
+    // A secondary index kept in sync by the hooks.
+    let v = MutVec::new();
+    let index = Rc::new(MutVec::new());
+    let i = index.clone();
+    v.on_insert(move |x: &u32| i.push(*x * 10));
+    let i = index.clone();
+    v.on_remove(move |_: &u32| {
+        i.pop();
+    });
+
+    v.push(1);
+    v.push(2);
+    assert_eq!(index.iter().collect::<Vec<_>>(), vec![10, 20]);
+    v.remove_range(0..1);
+    assert_eq!(index.len(), 1);
+}
+
+#[test]
+fn iter_map() {
+    struct Big {
+        id: u32,
+        _payload: Vec<u8>,
+    }
+
+    let v = MutVec::new();
+    v.push(Big {
+        id: 1,
+        _payload: vec![0; 64],
+    });
+    v.push(Big {
+        id: 2,
+        _payload: vec![],
+    });
+    assert_eq!(v.iter_map(|b| b.id).collect::<Vec<_>>(), vec![1, 2]);
+
+    // Mutation between steps is visible, as with `iter`.
+    let ids: Vec<u32> = v
+        .iter_map(|b| b.id)
+        .inspect(|&id| {
+            if id == 1 {
+                v.pop();
+            }
+        })
+        .collect();
+    assert_eq!(ids, vec![1]);
+}
+
+#[test]
+fn copy_from_reuses_storage() {
+    let mut dst = MutVec::from(Vec::with_capacity(16));
+    dst.push(1);
+    let capacity = dst.memory_usage().capacity;
+    let src = MutVec::from(vec![4, 5, 6]);
+
+    dst.copy_from(&src);
+    assert_eq!(dst.memory_usage().capacity, capacity);
+    assert_eq!(dst.clone().into_inner(), vec![4, 5, 6]);
+
+    dst.copy_from(&dst);
+    assert_eq!(dst.len(), 3);
+
+    dst.clone_from(&MutVec::from(vec![7]));
+    assert_eq!(dst.into_inner(), vec![7]);
+}
+
+#[test]
+fn mutvec_macro() {
+    let empty: MutVec<u8> = crate::mutvec![];
+    assert!(empty.is_empty());
+    assert_eq!(crate::mutvec![1, 2,].into_inner(), vec![1, 2]);
+    assert_eq!(crate::mutvec![0; 3].len(), 3);
+}
+
+#[test]
+fn iter_modes() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let v = MutVec::from(vec![1, 2, 3]);
+    let live: Vec<_> = v
+        .iter_with(IterMode::Live)
+        .inspect(|_| {
+            v.pop();
+        })
+        .collect();
+    assert_eq!(live, vec![1, 2]);
+
+    let v = MutVec::from(vec![1, 2, 3]);
+    let snapshot: Vec<_> = v
+        .iter_with(IterMode::SnapshotAtStart)
+        .inspect(|&x| v.push(x * 10))
+        .collect();
+    assert_eq!(snapshot, vec![1, 2, 3]);
+    assert_eq!(v.len(), 6);
+
+    let mut iter = v.iter_with(IterMode::PanicOnMutation);
+    assert_eq!(iter.next(), Some(1));
+    v.with_element_mut(0, |x| *x = 0);
+    let result = catch_unwind(AssertUnwindSafe(|| iter.next()));
+    assert!(result.is_err());
+
+    // Without mutations, it iterates as usual.
+    let all: Vec<_> = v.iter_with(IterMode::PanicOnMutation).collect();
+    assert_eq!(all, vec![0, 2, 3, 10, 20, 30]);
+}
@@ -0,0 +1,131 @@
+//! Handling of lock violations -- attempts to access mut-cells in a
+//! way the thread lock forbids (e.g., mutating a cell while iterating
+//! over a vector). By default, violations panic. Embedders can
+//! install a (thread-local) handler with `set_violation_handler` to
+//! log, capture a backtrace, abort, or panic with a payload of their
+//! choosing. The handler receives a `ViolationInfo` saying what
+//! conflicted, and in which cell.
+//!
+//! In debug builds, the thread lock also records where it was
+//! acquired, and the panic message names that location: it is the
+//! borrow (or callback, iteration, ...) that was still active when
+//! the violation happened.
+//!
+//! If the handler returns normally, the violation still panics when
+//! continuing would be unsound -- which is every case except
+//! `Violation::TooManyReaders` and `Violation::HeldAcrossAwait`. For
+//! those, the violation is recorded and execution continues.
+
+use crate::revision::CellId;
+use std::cell::Cell;
+use std::fmt;
+use std::panic::Location;
+
+mod test;
+
+/// A forbidden access to a mut-cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// Attempted to modify a cell while the thread holds a lock.
+    ModifyWhileLocked,
+
+    /// Attempted to read a cell while the thread holds a write lock.
+    ReadWhileWriteLocked,
+
+    /// Attempted to acquire a write lock while the thread holds a
+    /// lock.
+    WriteWhileLocked,
+
+    /// Too many read locks are held at once.
+    TooManyReaders,
+
+    /// A task suspended while holding a lock (see the `task` module).
+    HeldAcrossAwait,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Violation::ModifyWhileLocked => "cannot modify mutable data right now, lock is held",
+            Violation::ReadWhileWriteLocked => "cannot read from a Mut cell now",
+            Violation::WriteWhileLocked => "lock already held",
+            Violation::TooManyReaders => {
+                "too many read locks held at once (is a guard being leaked?)"
+            }
+            Violation::HeldAcrossAwait => "lock held across a suspension point",
+        };
+        f.write_str(message)
+    }
+}
+
+/// The details of a violation, as passed to the violation handler.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ViolationInfo {
+    /// The operation that conflicted with the lock.
+    pub violation: Violation,
+
+    /// The cell being accessed, or `None` if the violation does not
+    /// concern a particular cell (`Violation::HeldAcrossAwait`). Compare
+    /// with `Tracked::cell_id` to find out which cell this is.
+    pub cell: Option<CellId>,
+
+    /// Where the lock that the access conflicted with was acquired
+    /// (the outermost one, if several are held). Only recorded in
+    /// debug builds; always `None` in release builds.
+    pub held_at: Option<&'static Location<'static>>,
+}
+
+impl fmt::Display for ViolationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.violation.fmt(f)?;
+        if let Some(location) = self.held_at {
+            write!(f, " (lock acquired at {})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// A violation handler; see `set_violation_handler`.
+pub type ViolationHandler = fn(&ViolationInfo);
+
+thread_local! {
+    static HANDLER: Cell<Option<ViolationHandler>> = const { Cell::new(None) };
+}
+
+/// Install `handler` to be invoked on lock violations on this thread,
+/// returning the previously installed handler (if any).
+pub fn set_violation_handler(handler: ViolationHandler) -> Option<ViolationHandler> {
+    HANDLER.with(|h| h.replace(Some(handler)))
+}
+
+/// Remove this thread's violation handler, restoring the default
+/// behavior of panicking.
+pub fn clear_violation_handler() -> Option<ViolationHandler> {
+    HANDLER.with(|h| h.take())
+}
+
+/// Report `violation` in an access to `cell`, which conflicted with
+/// a lock acquired at `held_at`. Returns only if the violation is
+/// recoverable and the handler chose to continue.
+#[track_caller]
+pub(crate) fn report(
+    violation: Violation,
+    cell: Option<CellId>,
+    held_at: Option<&'static Location<'static>>,
+) {
+    let info = ViolationInfo {
+        violation,
+        cell,
+        held_at,
+    };
+    let handler = HANDLER.with(|h| h.get());
+    if let Some(handler) = handler {
+        handler(&info);
+        match violation {
+            Violation::TooManyReaders | Violation::HeldAcrossAwait => return,
+            _ => {}
+        }
+    }
+    panic!("{}", info);
+}
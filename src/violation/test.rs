@@ -0,0 +1,106 @@
+#![cfg(test)]
+
+use super::*;
+use crate::MutVec;
+use std::panic::{catch_unwind, panic_any, AssertUnwindSafe};
+
+thread_local! {
+    static SEEN: Cell<Option<Violation>> = const { Cell::new(None) };
+    static SEEN_CELL: Cell<Option<CellId>> = const { Cell::new(None) };
+}
+
+fn record(info: &ViolationInfo) {
+    SEEN.with(|s| s.set(Some(info.violation)));
+    SEEN_CELL.with(|s| s.set(info.cell));
+}
+
+#[derive(Debug)]
+struct CustomPayload(Violation);
+
+fn custom_panic(info: &ViolationInfo) {
+    panic_any(CustomPayload(info.violation));
+}
+
+fn push_while_iterating() {
+    let v = MutVec::from(vec![1]);
+    for _ in v.iter() {
+        let _data = v.data.borrow();
+        v.push(2);
+    }
+}
+
+#[test]
+fn handler_observes_then_panics() {
+    set_violation_handler(record);
+    let result = catch_unwind(AssertUnwindSafe(push_while_iterating));
+    assert!(result.is_err());
+    assert_eq!(SEEN.with(|s| s.get()), Some(Violation::WriteWhileLocked));
+    assert!(clear_violation_handler().is_some());
+}
+
+#[test]
+fn custom_payload() {
+    set_violation_handler(custom_panic);
+    let err = catch_unwind(AssertUnwindSafe(|| {
+        let v = MutVec::from(vec![1]);
+        let _data = v.data.borrow();
+        v.take();
+    }))
+    .unwrap_err();
+    let payload = err.downcast::<CustomPayload>().unwrap();
+    assert_eq!(payload.0, Violation::ModifyWhileLocked);
+}
+
+#[test]
+fn too_many_readers() {
+    use crate::mcell::lock;
+
+    lock::set_readers(lock::MAX_READERS);
+    assert_eq!(
+        lock::try_acquire_read_lock(lock::Domain::DEFAULT),
+        Err(Violation::TooManyReaders)
+    );
+
+    // With a handler that continues, the reader is admitted anyway.
+    set_violation_handler(record);
+    let v = MutVec::from(vec![1]);
+    assert_eq!(v.len(), 1);
+    assert_eq!(SEEN.with(|s| s.get()), Some(Violation::TooManyReaders));
+    clear_violation_handler();
+
+    lock::set_readers(0);
+    assert_eq!(lock::try_acquire_read_lock(lock::Domain::DEFAULT), Ok(()));
+    lock::release_read_lock(lock::Domain::DEFAULT);
+}
+
+#[test]
+fn reports_the_cell() {
+    use crate::revision::Tracked;
+    use crate::Mut;
+
+    set_violation_handler(record);
+    let a = Mut::new(1);
+    let b = Mut::new(2);
+    let v = MutVec::from(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        v.with_element(0, |_| a.set(b.get()));
+    }));
+    assert!(result.is_err());
+    assert_eq!(SEEN.with(|s| s.get()), Some(Violation::ModifyWhileLocked));
+    assert_eq!(SEEN_CELL.with(|s| s.get()), Some(a.cell_id()));
+    clear_violation_handler();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn names_where_the_lock_was_acquired() {
+    let v = MutVec::from(vec![1]);
+    let line = line!() + 2;
+    let err = catch_unwind(AssertUnwindSafe(|| {
+        v.with_element(0, |_| v.push(2));
+    }))
+    .unwrap_err();
+    let message = err.downcast::<String>().unwrap();
+    let expected = format!("lock acquired at {}:{}:", file!(), line);
+    assert!(message.contains(&expected), "{}", message);
+}
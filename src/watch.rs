@@ -0,0 +1,164 @@
+//! Queues of change events. `watch` registers element hooks (see the
+//! `observe` module) that record each change to a collection as an
+//! event, in order, until the `Watch` is dropped. A UI or logging
+//! layer can then drain the events once per frame, rather than
+//! comparing (or cloning) the whole collection:
+//!
+//! ```
+//! use mutable::watch::VecEvent;
+//! use mutable::MutVec;
+//!
+//! let v = MutVec::new();
+//! let changes = v.watch();
+//! v.push(1);
+//! v.replace_at(0, 2);
+//! assert_eq!(
+//!     changes.drain(),
+//!     vec![VecEvent::Pushed(1), VecEvent::Removed(1), VecEvent::Inserted(2)]
+//! );
+//! ```
+//!
+//! The events carry clones of the elements, taken just before they
+//! were added or as they were removed. Undo, redo, and transaction
+//! rollback are reported like any other change (restoring a snapshot
+//! as the removal of every element, then the insertion of the saved
+//! ones). Elements modified in place (e.g., with `with_element_mut`)
+//! are not reported.
+
+use crate::map::{MapStore, MutMap};
+use crate::observe::{self, hooks, SubscriptionId};
+use crate::vec::MutVec;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+mod test;
+
+/// A change to a `MutVec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VecEvent<T> {
+    /// An element was pushed (by `push` or `try_push`).
+    Pushed(T),
+
+    /// An element was added otherwise (by `replace_at` or `replace`).
+    Inserted(T),
+
+    /// An element was removed.
+    Removed(T),
+}
+
+/// A change to a `MutMap`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapEvent<K, V> {
+    /// An entry was inserted (replacing any entry with the same key,
+    /// which is not reported separately).
+    Inserted(K, V),
+
+    /// The entry with this key was removed.
+    Removed(K),
+}
+
+/// The events recorded for a collection since they were last taken.
+/// Dropping the `Watch` stops the recording.
+pub struct Watch<E> {
+    /// Not an `MCell`: the queue is private, and recording into it
+    /// (from a hook, under the lock of the watched collection) or
+    /// reading it must not conflict with the thread lock.
+    events: Rc<RefCell<VecDeque<E>>>,
+    cell_id: u64,
+    subscriptions: Vec<SubscriptionId>,
+}
+
+impl<E: 'static> Watch<E> {
+    fn new(cell_id: u64) -> Self {
+        Watch {
+            events: Rc::new(RefCell::new(VecDeque::new())),
+            cell_id,
+            subscriptions: vec![],
+        }
+    }
+
+    /// A hook that records the event `f` makes of an element.
+    fn recorder<A, B>(&self, f: impl Fn(&A, &B) -> E + 'static) -> impl Fn(&A, &B) + 'static {
+        let events = self.events.clone();
+        move |a, b| events.borrow_mut().push_back(f(a, b))
+    }
+
+    /// Take the oldest event, if any.
+    pub fn try_recv(&self) -> Option<E> {
+        self.events.borrow_mut().pop_front()
+    }
+
+    /// Take all the events, oldest first.
+    pub fn drain(&self) -> Vec<E> {
+        self.events.take().into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.borrow().is_empty()
+    }
+}
+
+impl<E> Drop for Watch<E> {
+    fn drop(&mut self) {
+        for subscription in self.subscriptions.drain(..) {
+            observe::unsubscribe(self.cell_id, subscription);
+        }
+    }
+}
+
+impl<T> MutVec<T>
+where
+    T: Clone + 'static,
+{
+    /// Record the changes to `self` as `VecEvent`s, until the result
+    /// is dropped. See the `watch` module.
+    pub fn watch(&self) -> Watch<VecEvent<T>> {
+        let id = self.data.id();
+        let mut watch = Watch::new(id);
+        watch.subscriptions = vec![
+            hooks::on_push(
+                id,
+                watch.recorder(|t: &T, _: &()| VecEvent::Pushed(t.clone())),
+            ),
+            hooks::on_put(
+                id,
+                watch.recorder(|t: &T, _: &()| VecEvent::Inserted(t.clone())),
+            ),
+            hooks::on_remove(
+                id,
+                watch.recorder(|t: &T, _: &()| VecEvent::Removed(t.clone())),
+            ),
+        ];
+        watch
+    }
+}
+
+impl<K, V, S> MutMap<K, V, S>
+where
+    K: Clone + 'static,
+    V: Clone + 'static,
+    S: MapStore<K, V>,
+{
+    /// Record the changes to `self` as `MapEvent`s, until the result
+    /// is dropped. See the `watch` module.
+    pub fn watch(&self) -> Watch<MapEvent<K, V>> {
+        let id = self.data.id();
+        let mut watch = Watch::new(id);
+        watch.subscriptions = vec![
+            hooks::on_insert(
+                id,
+                watch.recorder(|k: &K, v: &V| MapEvent::Inserted(k.clone(), v.clone())),
+            ),
+            hooks::on_remove(
+                id,
+                watch.recorder(|k: &K, _: &V| MapEvent::Removed(k.clone())),
+            ),
+        ];
+        watch
+    }
+}
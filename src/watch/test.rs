@@ -0,0 +1,84 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn vec_events() {
+    let v = MutVec::from(vec![0]);
+    let changes = v.watch();
+    v.push(1);
+    assert_eq!(changes.try_recv(), Some(VecEvent::Pushed(1)));
+    assert_eq!(changes.try_recv(), None);
+
+    v.replace(vec![2]);
+    v.pop();
+    assert_eq!(changes.len(), 4);
+    assert_eq!(
+        changes.drain(),
+        vec![
+            VecEvent::Removed(0),
+            VecEvent::Removed(1),
+            VecEvent::Inserted(2),
+            VecEvent::Removed(2),
+        ]
+    );
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn map_events() {
+    let m = MutMap::new();
+    let changes = m.watch();
+    m.insert("a", 1);
+    m.insert("a", 2);
+    m.clone().insert("b", 3);
+    let mut m = m;
+    m.remove("a");
+    assert_eq!(
+        changes.drain(),
+        vec![
+            MapEvent::Inserted("a", 1),
+            MapEvent::Inserted("a", 2),
+            MapEvent::Removed("a"),
+        ]
+    );
+}
+
+#[test]
+fn dropping_stops_recording() {
+    let v = MutVec::new();
+    let other_hook = v.on_insert(|_: &u32| ());
+    let changes = v.watch();
+    drop(changes);
+    v.push(1);
+
+    // Only the hooks of the watch were removed.
+    assert!(v.unsubscribe(other_hook));
+
+    let first = v.watch();
+    let second = v.watch();
+    v.push(2);
+    assert_eq!(first.drain(), second.drain());
+}
+
+#[test]
+fn record_into_another_domain() {
+    use crate::LockDomain;
+
+    let files = MutVec::from(vec![1]);
+    let log = LockDomain::new().enter(MutVec::new);
+    let changes = log.watch();
+    files.with_element(0, |f| log.push(*f));
+    assert_eq!(changes.drain(), vec![VecEvent::Pushed(1)]);
+}
+
+#[test]
+fn receive_while_reading() {
+    let v = MutVec::from(vec![1]);
+    let changes = v.watch();
+    v.push(2);
+    v.with_element(0, |_| {
+        assert_eq!(changes.try_recv(), Some(VecEvent::Pushed(2)));
+        assert!(changes.is_empty());
+    });
+}
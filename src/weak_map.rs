@@ -0,0 +1,108 @@
+//! A map whose values are held weakly, for caches of `Rc`-owned
+//! objects.
+
+use crate::mcell::lock;
+use crate::mcell::MCell;
+use indexmap::{Equivalent, IndexMap};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::{Rc, Weak};
+
+mod test;
+
+/// A map from keys to `Weak<V>`. Lookups upgrade the value, and an
+/// entry whose value has been dropped is removed the next time it is
+/// looked up (unless the map cannot be modified at the time, e.g.,
+/// during a borrow of another cell); `compact` removes all such
+/// entries at once.
+pub struct MutWeakMap<K, V> {
+    data: MCell<IndexMap<K, Weak<V>>>,
+}
+
+impl<K, V> MutWeakMap<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of entries, including any whose values have been
+    /// dropped but that have not yet been pruned.
+    pub fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().is_empty()
+    }
+
+    /// Maps `key` to a weak reference to `value`, returning the
+    /// previous value (if it was still alive). During the insertion
+    /// operation, all mut-cells are locked and read-only.
+    pub fn insert(&self, key: K, value: &Rc<V>) -> Option<Rc<V>> {
+        self.data
+            .check_out()
+            .insert(key, Rc::downgrade(value))?
+            .upgrade()
+    }
+
+    /// The value for `key`, if there is one and it is still alive. If
+    /// it has been dropped, the entry is removed, provided the map can
+    /// be modified right now; otherwise it is left for a later lookup
+    /// (or `compact`).
+    pub fn get<Q>(&self, key: &Q) -> Option<Rc<V>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let value = self.data.borrow().get(key)?.upgrade();
+        if value.is_none() && lock::check_write(Some(self.data.domain())).is_ok() {
+            self.data.check_out().swap_remove(key);
+        }
+        value
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes `key`, returning its value if it was still alive.
+    pub fn remove<Q>(&self, key: &Q) -> Option<Rc<V>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.data.check_out().swap_remove(key)?.upgrade()
+    }
+
+    /// Removes every entry whose value has been dropped, returning how
+    /// many were removed.
+    pub fn compact(&self) -> usize {
+        let mut data = self.data.check_out();
+        let before = data.len();
+        data.retain(|_, weak| weak.strong_count() > 0);
+        before - data.len()
+    }
+}
+
+impl<K, V> Default for MutWeakMap<K, V> {
+    fn default() -> Self {
+        MutWeakMap {
+            data: MCell::new(IndexMap::new()),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for MutWeakMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.data.borrow();
+        f.debug_map()
+            .entries(
+                data.iter()
+                    .filter_map(|(k, weak)| Some((k, weak.upgrade()?))),
+            )
+            .finish()
+    }
+}
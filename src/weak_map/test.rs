@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn prune_on_get() {
+    let map = MutWeakMap::new();
+    let a = Rc::new("a".to_string());
+    let b = Rc::new("b".to_string());
+    map.insert(1, &a);
+    map.insert(2, &b);
+    assert_eq!(*map.get(&1).unwrap(), "a");
+
+    drop(a);
+    assert_eq!(map.len(), 2);
+    assert!(map.get(&1).is_none());
+    assert_eq!(map.len(), 1);
+    assert!(map.contains_key(&2));
+    assert_eq!(format!("{:?}", map), r#"{2: "b"}"#);
+}
+
+#[test]
+fn compact() {
+    let map = MutWeakMap::new();
+    let mut values: Vec<_> = (0..4).map(Rc::new).collect();
+    for (i, v) in values.iter().enumerate() {
+        map.insert(i, v);
+    }
+    values.truncate(1);
+    assert_eq!(map.compact(), 3);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.compact(), 0);
+}
+
+#[test]
+fn insert_and_remove() {
+    let map = MutWeakMap::new();
+    let a = Rc::new(1);
+    let b = Rc::new(2);
+    assert!(map.insert("k", &a).is_none());
+    assert_eq!(map.insert("k", &b), Some(a));
+    assert_eq!(map.remove("k"), Some(b));
+    assert!(map.is_empty());
+}
+
+#[test]
+fn get_while_locked() {
+    let map = MutWeakMap::new();
+    let v = crate::MutVec::from(vec![1]);
+    map.insert("dead", &Rc::new(0));
+    let alive = Rc::new(1);
+    map.insert("alive", &alive);
+    v.with_element(0, |_| {
+        assert_eq!(map.get("dead"), None);
+        assert!(!map.contains_key("dead"));
+        assert_eq!(map.get("alive"), Some(alive.clone()));
+    });
+
+    // The dead entry is pruned once the map can be modified.
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get("dead"), None);
+    assert_eq!(map.len(), 1);
+}
@@ -0,0 +1,79 @@
+use crate::mcell::MCell;
+use indexmap::IndexSet;
+use std::hash::Hash;
+
+mod test;
+
+/// A deduplicating worklist, for fixpoint iterations (dataflow
+/// analysis, constraint solving, and the like): `enqueue` ignores
+/// items that are already pending *or* that have already been
+/// dequeued, so each item is processed at most once.
+pub struct MutWorkQueue<T> {
+    data: MCell<WorkList<T>>,
+}
+
+struct WorkList<T> {
+    /// Every item ever enqueued, in order.
+    items: IndexSet<T>,
+
+    /// Items before this index have been dequeued.
+    next: usize,
+}
+
+impl<T> Default for WorkList<T> {
+    fn default() -> Self {
+        WorkList {
+            items: IndexSet::new(),
+            next: 0,
+        }
+    }
+}
+
+impl<T: Hash + Eq> MutWorkQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of pending items.
+    pub fn len(&self) -> usize {
+        let data = self.data.borrow();
+        data.items.len() - data.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `item` to the end of the queue, unless it was enqueued
+    /// before. Returns true if it was added. During the operation,
+    /// all mut-cells are locked and read-only; attempts to read from
+    /// *this* queue will find it empty.
+    pub fn enqueue(&self, item: T) -> bool {
+        self.data.check_out().items.insert(item)
+    }
+
+    /// Remove the oldest pending item, if any.
+    pub fn dequeue(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let mut data = self.data.check_out();
+        let item = data.items.get_index(data.next)?.clone();
+        data.next += 1;
+        Some(item)
+    }
+
+    /// True if `item` has ever been enqueued (whether or not it is
+    /// still pending).
+    pub fn contains(&self, item: &T) -> bool {
+        self.data.borrow().items.contains(item)
+    }
+}
+
+impl<T> Default for MutWorkQueue<T> {
+    fn default() -> Self {
+        MutWorkQueue {
+            data: MCell::new(WorkList::default()),
+        }
+    }
+}
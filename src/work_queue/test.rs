@@ -0,0 +1,34 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn fixpoint() {
+    // Compute the nodes reachable from 0 in a graph with a cycle.
+    let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (4, 0)];
+    let queue = MutWorkQueue::new();
+    assert!(queue.enqueue(0));
+    let mut reached = vec![];
+    while let Some(node) = queue.dequeue() {
+        reached.push(node);
+        for &(_, to) in edges.iter().filter(|(from, _)| *from == node) {
+            queue.enqueue(to);
+        }
+    }
+    assert_eq!(reached, vec![0, 1, 2, 3]);
+    assert!(queue.is_empty());
+    assert!(!queue.enqueue(2));
+    assert!(queue.contains(&3));
+    assert!(!queue.contains(&4));
+}
+
+#[test]
+fn pending_are_deduplicated() {
+    let queue = MutWorkQueue::new();
+    queue.enqueue("a");
+    queue.enqueue("b");
+    assert!(!queue.enqueue("a"));
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.dequeue(), Some("a"));
+    assert_eq!(queue.len(), 1);
+}